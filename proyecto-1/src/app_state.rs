@@ -1,5 +1,7 @@
-use std::cmp::{max, min};
+use std::cmp::max;
+use std::fmt;
 
+use log::error;
 use rfd::FileDialog;
 use winit::keyboard::KeyCode;
 use winit::window::CursorIcon;
@@ -7,13 +9,26 @@ use winit::window::CursorIcon;
 /// We try to avoid using PIXELS package to have this state management library agnostic.
 /// The only dependency is winit and rfd, winit can be easily replaced by enums since we only use
 /// some enums definitions from it
-use crate::{
+use paint_app::{
     canvas::Canvas,
-    core::{Point, Shape, ShapeCore, ShapeImpl, UpdateOp, RGBA},
+    core::{
+        ArcKind, BlendMode, FillRule, ImageSampling, ImageSource, JointKind, KnotVector, LineCap,
+        LineJoin, Point, Shape, ShapeCore, ShapeImpl, UpdateOp, MAX_FONT_SIZE, RGBA,
+    },
     draw_state::DrawState,
-    primitives::new_shape_from_core,
+    headless::render,
+    primitives::{
+        closest_point, erased_range, flood_fill, max_deviation, new_shape_from_core,
+        regular_polygon, simplify_path, FREEHAND_TOLERANCE,
+    },
 };
 
+/// how far from the cursor the eraser reaches when cutting lines and curves, in pixels
+const ERASER_RADIUS: f32 = 6.0;
+
+/// step of the rotation of regular polygons while SHIFT is pressed, in degrees
+const ROTATION_SNAP: f32 = 15.0;
+
 /// here we dont use winit mouse events. We use our own. If this was a real app, this would make
 /// the app library agnostic, which is better for third party integrations
 #[derive(Copy, Clone, PartialEq)]
@@ -24,6 +39,30 @@ pub enum MouseEvent {
     Release,
 }
 
+/// what the eraser does with the shapes it goes over
+#[derive(Copy, Clone, PartialEq)]
+pub enum EraserMode {
+    /// deletes the whole shape
+    Object,
+    /// cuts lines and bezier curves, removing only the part under the eraser. The other shapes
+    /// are deleted
+    Split,
+}
+
+impl EraserMode {
+    /// every mode, in the order shown on the UI
+    pub const ALL: [EraserMode; 2] = [EraserMode::Object, EraserMode::Split];
+}
+
+impl fmt::Display for EraserMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EraserMode::Object => write!(f, "Object"),
+            EraserMode::Split => write!(f, "Split"),
+        }
+    }
+}
+
 /// These are user interface events definitions. They trigger actions on the app state
 #[derive(Clone)]
 pub enum GUIEvent {
@@ -33,6 +72,59 @@ pub enum GUIEvent {
     BorderColor(RGBA),
     /// change of fill color
     FillColor(RGBA),
+    /// change of border width
+    StrokeWidth(u32),
+    /// change of the ends of the border
+    LineCap(LineCap),
+    /// change of the corners of the border
+    LineJoin(LineJoin),
+    /// change of the miter limit
+    MiterLimit(f32),
+    /// change of the dashes of the border and their offset
+    DashPattern(Vec<u32>, u32),
+    /// switches the anti-aliasing on or off
+    Antialias(bool),
+    /// change of which parts of a polygon are filled
+    FillRule(FillRule),
+    /// switches between joining the points of a freehand path with lines or with a curve
+    Smooth(bool),
+    /// change of the rotation of ellipses, in degrees
+    Rotation(f32),
+    /// change of which part of an ellipse is drawn
+    Arc(ArcKind),
+    /// change of the radius of each corner of rectangles
+    CornerRadii([u32; 4]),
+    /// change of what a text says
+    Text(String),
+    /// change of the size of the letters of texts, in pixels
+    FontSize(f32),
+    /// open image button clicked. The bool is true if the png is embedded on the drawing
+    OpenImage(bool),
+    /// change of how images are scaled
+    ImageSampling(ImageSampling),
+    /// change of the degree of b-splines
+    SplineDegree(u32),
+    /// change of how the knots of b-splines are spread
+    KnotVector(KnotVector),
+    /// change of the weight of the selected control point of a bezier curve
+    PointWeight(f32),
+    /// change of the joint of new paths and of the selected point of a path
+    Joint(JointKind),
+    /// change of how different a color can be from the clicked one to be painted by the bucket
+    FillTolerance(u8),
+    /// change of what the eraser does
+    EraserMode(EraserMode),
+    /// change of the regular polygons drawn: the amount of sides, if its a star and the radius of
+    /// the inner corners of the star relative to the outer ones
+    RegularPolygon(u32, bool, f32),
+    /// the mouse button was released over the GUI. Not a button, but a drag that started on the
+    /// canvas (like the eraser one) ends there
+    PointerReleased,
+    /// the canvas changed its size (width, height). Not a button, but the bucket needs to know
+    /// where the drawing ends
+    CanvasSize(u32, u32),
+    /// change of blend mode
+    BlendMode(BlendMode),
     /// change of control points color. Is global
     PointsColor(RGBA),
     /// change on background color
@@ -48,13 +140,15 @@ pub enum GUIEvent {
     /// subdivision value changed
     SubdivisionValue(f32),
     /// paste from clipboard
-    PasteShape(ShapeCore, Point),
+    PasteShape(Box<ShapeCore>, Point),
     /// save button clicked
     Save,
     /// load button clicked
     Load,
     /// degree elevate button clicked
     DegreeElevate,
+    /// degree reduce button clicked
+    DegreeReduce,
     /// subdivide button clicked
     Subdivide,
     /// clear button clicked
@@ -74,7 +168,7 @@ pub enum EventType {
     /// keyboard event, bolean to know if its pressed or released
     Keyboard(KeyCode, bool),
     /// gui event
-    GUI(GUIEvent),
+    Gui(GUIEvent),
 }
 
 /// current shape selected. Since we might as well be selecting a control point we encapsulate that
@@ -135,6 +229,65 @@ pub struct AppState {
     color: RGBA,
     /// color of filling
     fill_color: RGBA,
+    /// width of the border in pixels
+    stroke_width: u32,
+    /// ends of the border
+    line_cap: LineCap,
+    /// corners of the border
+    line_join: LineJoin,
+    /// longest miter corner, relative to the border width
+    miter_limit: f32,
+    /// dashes and gaps of the border, empty for a solid one
+    dash_pattern: Vec<u32>,
+    /// where the border starts on the dash pattern
+    dash_offset: u32,
+    /// if new shapes are anti-aliased
+    antialias: bool,
+    /// fill rule of new polygons
+    fill_rule: FillRule,
+    /// if new freehand paths are smooth
+    smooth: bool,
+    /// rotation of new ellipses, in degrees
+    rotation: f32,
+    /// part drawn of new ellipses
+    arc: ArcKind,
+    /// radius of the corners of new rectangles
+    corner_radii: [u32; 4],
+    /// what new texts say
+    text: String,
+    /// size of the letters of new texts, in pixels
+    font_size: f32,
+    /// png of new images, None until one is opened
+    image: Option<ImageSource>,
+    /// how new images are scaled
+    image_sampling: ImageSampling,
+    /// degree of new b-splines
+    spline_degree: u32,
+    /// how the knots of new b-splines are spread
+    knot_vector: KnotVector,
+    /// joint of the points of new paths
+    joint: JointKind,
+    /// the last degree reduction: the index of the curve, the curve it left and how much it moved,
+    /// or why it couldnt be reduced. Its shown until that curve changes
+    degree_reduction: Option<(usize, ShapeCore, Result<f32, &'static str>)>,
+    /// tolerance of the bucket, per channel
+    fill_tolerance: u8,
+    /// size of the canvas (width, height), the bucket fills the drawing as its shown
+    canvas_size: (u32, u32),
+    /// what the eraser does with the shapes
+    eraser_mode: EraserMode,
+    /// last position of the cursor while erasing, None if we are not erasing
+    eraser_last: Option<Point>,
+    /// sides of new regular polygons, or points of new stars
+    polygon_sides: u32,
+    /// if new regular polygons are stars
+    star: bool,
+    /// radius of the inner corners of new stars relative to the outer ones
+    star_ratio: f32,
+    /// center of the regular polygon being drawn, None if we are not drawing one
+    polygon_center: Option<Point>,
+    /// blend mode of new shapes
+    blend_mode: BlendMode,
     /// control points global color
     points_color: RGBA,
     /// color of a selected shape. Cannot be changed
@@ -150,6 +303,35 @@ impl AppState {
             current: Shape::Line,
             color: RGBA::new(255, 255, 255, 200),
             fill_color: RGBA::new(100, 50, 10, 0),
+            stroke_width: 1,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 4.0,
+            dash_pattern: vec![],
+            dash_offset: 0,
+            antialias: false,
+            fill_rule: FillRule::NonZero,
+            smooth: true,
+            rotation: 0.0,
+            arc: ArcKind::Full,
+            corner_radii: [0; 4],
+            text: String::from("Text"),
+            font_size: 24.0,
+            image: None,
+            image_sampling: ImageSampling::Bilinear,
+            spline_degree: 3,
+            knot_vector: KnotVector::Clamped,
+            joint: JointKind::Symmetric,
+            degree_reduction: None,
+            fill_tolerance: 32,
+            canvas_size: (0, 0),
+            eraser_mode: EraserMode::Object,
+            eraser_last: None,
+            polygon_sides: 5,
+            star: false,
+            star_ratio: 0.5,
+            polygon_center: None,
+            blend_mode: BlendMode::Normal,
             points_color: RGBA::new(255, 80, 80, 255),
             bezier_control_polygon_color: RGBA::new(255, 80, 80, 255),
             selection_color: RGBA::new(80, 80, 250, 255),
@@ -162,7 +344,7 @@ impl AppState {
     }
 
     /// returns the shape type of the selected shape. useful for the UI
-    pub fn get_selected_shape(&self) -> Option<&Box<dyn ShapeImpl>> {
+    pub fn get_selected_shape(&self) -> Option<&dyn ShapeImpl> {
         let shap = self
             .selected
             .as_ref()
@@ -181,10 +363,157 @@ impl AppState {
         )
     }
 
+    /// returns the border width used for new shapes. useful for the UI
+    pub fn get_stroke_width(&self) -> u32 {
+        self.stroke_width
+    }
+
+    /// returns the caps, joins and miter limit used for new shapes. useful for the UI
+    pub fn get_line_style(&self) -> (LineCap, LineJoin, f32) {
+        (self.line_cap, self.line_join, self.miter_limit)
+    }
+
+    /// returns the dash pattern and offset used for new shapes, or the ones of the selected shape.
+    /// useful for the UI
+    pub fn get_dash_pattern(&self) -> (Vec<u32>, u32) {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) => (core.dash_pattern, core.dash_offset),
+            None => (self.dash_pattern.clone(), self.dash_offset),
+        }
+    }
+
+    /// returns if new shapes are anti-aliased. useful for the UI
+    pub fn get_antialias(&self) -> bool {
+        self.antialias
+    }
+
+    /// returns the fill rule used for new polygons, or by the selected one. useful for the UI
+    pub fn get_fill_rule(&self) -> FillRule {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Polygon => core.fill_rule,
+            _ => self.fill_rule,
+        }
+    }
+
+    /// returns if new freehand paths are smooth, or if the selected one is. useful for the UI
+    pub fn get_smooth(&self) -> bool {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Freehand => core.smooth,
+            _ => self.smooth,
+        }
+    }
+
+    /// returns the rotation and the part drawn of new ellipses, or of the selected one. useful for
+    /// the UI
+    pub fn get_ellipse_style(&self) -> (f32, ArcKind) {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Ellipse => (core.rotation, core.arc),
+            _ => (self.rotation, self.arc),
+        }
+    }
+
+    /// returns the sides of new regular polygons, if they are stars and the ratio of the inner
+    /// radius of the stars. useful for the UI
+    pub fn get_regular_polygon(&self) -> (u32, bool, f32) {
+        (self.polygon_sides, self.star, self.star_ratio)
+    }
+
+    /// returns what the text says and the size of its letters. If a text is selected its the one
+    /// of that text, so it can be edited. useful for the UI
+    pub fn get_text_style(&self) -> (String, f32) {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Text => (core.text, core.font_size),
+            _ => (self.text.clone(), self.font_size),
+        }
+    }
+
+    /// returns if there is a png to draw new images with and how images are scaled (the one of the
+    /// selected image if there is one). useful for the UI
+    pub fn get_image_style(&self) -> (bool, ImageSampling) {
+        let sampling = match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Image => core.sampling,
+            _ => self.image_sampling,
+        };
+        (self.image.is_some(), sampling)
+    }
+
+    /// returns the degree of b-splines, how their knots are spread and if they were given one by
+    /// one (after inserting a knot). If a b-spline is selected its the one of that curve. useful
+    /// for the UI
+    pub fn get_spline_style(&self) -> (u32, KnotVector, bool) {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::BSpline => {
+                (core.degree, core.knot_vector, !core.knots.is_empty())
+            }
+            _ => (self.spline_degree, self.knot_vector, false),
+        }
+    }
+
+    /// returns the weight of the selected control point when its on a bezier curve. useful for the
+    /// UI
+    pub fn get_selected_weight(&self) -> Option<f32> {
+        let index = self.selected.as_ref()?.control_point_selected?;
+        let core = self.get_selected_shape()?.get_core();
+        if core.shape_type != Shape::Bezier {
+            return None;
+        }
+        core.point_weights().get(index).copied()
+    }
+
+    /// returns the joint of new paths, or the one of the selected point if its on a path. useful
+    /// for the UI
+    pub fn get_joint(&self) -> JointKind {
+        match self.selected_path_joint() {
+            Some((core, joint)) => core.path_joints()[joint],
+            None => self.joint,
+        }
+    }
+
+    /// returns the selected path and the joint of its selected control point. A handle belongs to
+    /// the point next to it
+    fn selected_path_joint(&self) -> Option<(ShapeCore, usize)> {
+        let index = self.selected.as_ref()?.control_point_selected?;
+        let core = self.get_selected_shape()?.get_core();
+        (core.shape_type == Shape::Path).then_some((core, (index + 1) / 3))
+    }
+
+    /// returns how far the selected curve moved when its degree was reduced, or why it couldnt be
+    /// reduced, as long as it didnt change since then. useful for the UI
+    pub fn get_degree_reduction(&self) -> Option<Result<f32, &'static str>> {
+        let (index, core, deviation) = self.degree_reduction.as_ref()?;
+        let selected = self.selected.as_ref()?;
+        let current = self.get_selected_shape()?.get_core();
+        (selected.index == *index && current == *core).then_some(*deviation)
+    }
+
+    /// returns the radius of the corners of new rectangles, or of the selected one. useful for
+    /// the UI
+    pub fn get_corner_radii(&self) -> [u32; 4] {
+        match self.get_selected_shape().map(|shape| shape.get_core()) {
+            Some(core) if core.shape_type == Shape::Rectangle => core.corner_radii,
+            _ => self.corner_radii,
+        }
+    }
+
+    /// returns the tolerance of the bucket. useful for the UI
+    pub fn get_fill_tolerance(&self) -> u8 {
+        self.fill_tolerance
+    }
+
+    /// returns what the eraser does. useful for the UI
+    pub fn get_eraser_mode(&self) -> EraserMode {
+        self.eraser_mode
+    }
+
+    /// returns the blend mode used for new shapes. useful for the UI
+    pub fn get_blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
     /// handles a GUI update. Used to avoid bloating the code with event definition wrapping.
     /// Same reason for "keyboard_update" and "mouse_update"
     pub fn gui_update(&mut self, e: GUIEvent) {
-        self.update(EventType::GUI(e));
+        self.update(EventType::Gui(e));
     }
 
     /// handles keyboard update
@@ -194,7 +523,7 @@ impl AppState {
 
     /// handles mouse update
     pub fn mouse_update(&mut self, e: MouseEvent, btn: u8, point: Point) -> CursorIcon {
-        return self.update(EventType::Mouse(e, btn, point));
+        self.update(EventType::Mouse(e, btn, point))
     }
 
     /// Handles a generic update. Every update on the state goes through here, no other public
@@ -204,17 +533,40 @@ impl AppState {
     /// same place.
     /// This function also returns how the cursor should look like
     pub fn update(&mut self, event: EventType) -> CursorIcon {
+        let selected_before = self.selected.as_ref().map(|selected| selected.index);
+        let cursor = self.handle_event(event.clone());
+
+        // undo, redo, clear and load replace the shapes, the index selected before is not the
+        // same shape anymore
+        let replaces_shapes = matches!(
+            event,
+            EventType::Gui(GUIEvent::Undo | GUIEvent::Redo | GUIEvent::Clear | GUIEvent::Load)
+        );
+        if !replaces_shapes {
+            self.drop_empty_text(selected_before);
+        }
+        cursor
+    }
+
+    /// handles the event of update, see above
+    fn handle_event(&mut self, event: EventType) -> CursorIcon {
         // this is a match. Is similar to a switch but handles more detail over the data
         // comparison
         match event.clone() {
             // gui events only
-            EventType::GUI(gui_ev) => {
+            EventType::Gui(gui_ev) => {
                 self.handle_gui_event(gui_ev);
             }
             // mouse events only
             EventType::Mouse(mouse_ev, btn, point) => {
+                // any release ends the drag of the eraser, even if another tool is used now
+                if MouseEvent::Release == mouse_ev && self.current != Shape::Eraser {
+                    self.end_eraser_drag();
+                }
+
                 if MouseEvent::Click == mouse_ev && btn == 0 {
-                    if !self.is_building_bezier() {
+                    // the eraser goes over the shapes, it doesnt select them
+                    if !self.is_building_bezier() && self.current != Shape::Eraser {
                         if let Some(fig) = self.selected.as_ref() {
                             // if we fall on this condition, it means we are selecting a control point
                             if let Some(point_idx) = self.is_control_point_select(fig.index, point)
@@ -224,6 +576,12 @@ impl AppState {
                             }
                         }
 
+                        // if we fall on this condition, it means we clicked the curve of the
+                        // selected bezier
+                        if self.handle_bezier_click(point) {
+                            return CursorIcon::Grab;
+                        }
+
                         // if we fall on this condition, it means we are selecting a shape
                         if let Some(fig) = self.is_figure_selection(point) {
                             self.selected = Some(ShapeSelected::new_with_point(fig, point));
@@ -244,8 +602,8 @@ impl AppState {
                         }
 
                         // if we fall on this condition we are moving a shape
-                        if orig.is_some() {
-                            self.handle_move_selected_shape(orig.unwrap(), point);
+                        if let Some(orig) = orig {
+                            self.handle_move_selected_shape(orig, point);
                             return CursorIcon::Grabbing;
                         }
                     }
@@ -261,7 +619,7 @@ impl AppState {
         }
 
         // here we should only care about updating the cursor type
-        return self.handle_mouse_change(event);
+        self.handle_mouse_change(event)
     }
 
     /// decides which mouse type to use at the end of update if we didnt returned before
@@ -279,14 +637,13 @@ impl AppState {
                 // appear unless a shape is selected.
                 // so if we are not building a bezier curve and we hover over a shape, we have a
                 // pointer cursor
-                (false, None, Some(selected)) => {
-                    // if we are over a control point of the selected shape, then we change the cursor
+                // if we are over a control point of the selected shape, then we change the cursor
+                (false, None, Some(selected))
                     if self
                         .is_control_point_select(selected.index, point)
-                        .is_some()
-                    {
-                        return CursorIcon::Pointer;
-                    }
+                        .is_some() =>
+                {
+                    return CursorIcon::Pointer;
                 }
                 // we fall here if:
                 // "is_building_bezier" == false
@@ -310,9 +667,7 @@ impl AppState {
             (KeyCode::Delete | KeyCode::Backspace, true) => self.handle_delete_figure(),
             // we do that weird requirement where we transform ellipses and rectangles on shift
             (KeyCode::ShiftLeft | KeyCode::ShiftRight, is_pressed) => {
-                if self.shift_pressed != is_pressed {
-                    self.shift_pressed = is_pressed;
-                }
+                self.shift_pressed = is_pressed;
             }
             _ => {}
         }
@@ -321,13 +676,17 @@ impl AppState {
     /// handles a GUI event
     fn handle_gui_event(&mut self, event: GUIEvent) {
         match event {
-            GUIEvent::ShapeType(shape) => self.current = shape,
+            GUIEvent::ShapeType(shape) => {
+                self.end_eraser_drag();
+                self.current = shape;
+            }
+            GUIEvent::PointerReleased => self.end_eraser_drag(),
             GUIEvent::PointsColor(c) => self.points_color = c,
             GUIEvent::ControlPolygonColor(c) => self.bezier_control_polygon_color = c,
             GUIEvent::BackgroundColor(c) => self.draw_state.change_background_color(c),
             GUIEvent::PasteShape(shape, new_point) => {
                 // for this we take the first point as ref for the new position
-                let mut full_shape = new_shape_from_core(shape);
+                let mut full_shape = new_shape_from_core(*shape);
                 let delta = new_point - full_shape.get_geometric_center();
                 full_shape.update(&UpdateOp::Move(delta));
 
@@ -358,6 +717,186 @@ impl AppState {
                         .update_shape(selected.index, UpdateOp::ChangeFillColor(c));
                 }
             }
+            // updates the border width if a shape is selected
+            GUIEvent::StrokeWidth(width) => {
+                self.stroke_width = width;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeStrokeWidth(width));
+                }
+            }
+            // updates the border style if a shape is selected
+            GUIEvent::LineCap(cap) => {
+                self.line_cap = cap;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeLineCap(cap));
+                }
+            }
+            GUIEvent::LineJoin(join) => {
+                self.line_join = join;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeLineJoin(join));
+                }
+            }
+            GUIEvent::MiterLimit(limit) => {
+                self.miter_limit = limit;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeMiterLimit(limit));
+                }
+            }
+            GUIEvent::DashPattern(pattern, offset) => {
+                self.dash_pattern = pattern.clone();
+                self.dash_offset = offset;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeDashPattern(pattern, offset));
+                }
+            }
+            // updates how the shape is rasterized if a shape is selected
+            GUIEvent::Antialias(antialias) => {
+                self.antialias = antialias;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeAntialias(antialias));
+                }
+            }
+            // updates the fill rule if a polygon is selected, only polygons can cross themselves
+            GUIEvent::FillRule(rule) => {
+                self.fill_rule = rule;
+                if let Some(selected) = self.selected.as_ref() {
+                    let shape = self.draw_state.get_object(selected.index);
+                    if shape.get_type() == Shape::Polygon {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeFillRule(rule));
+                    }
+                }
+            }
+            // updates the smoothing if a freehand path is selected, the other shapes are not
+            // smoothed
+            GUIEvent::Smooth(smooth) => {
+                self.smooth = smooth;
+                if let Some(selected) = self.selected.as_ref() {
+                    let shape = self.draw_state.get_object(selected.index);
+                    if shape.get_type() == Shape::Freehand {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeSmooth(smooth));
+                    }
+                }
+            }
+            // updates the rotation if an ellipse is selected, the other shapes are not rotated
+            GUIEvent::Rotation(rotation) => {
+                self.rotation = rotation;
+                if let Some(selected) = self.selected.as_ref() {
+                    let shape = self.draw_state.get_object(selected.index);
+                    if shape.get_type() == Shape::Ellipse {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeRotation(rotation));
+                    }
+                }
+            }
+            // updates the part drawn if an ellipse is selected
+            GUIEvent::Arc(arc) => {
+                self.arc = arc;
+                if let Some(selected) = self.selected.as_ref() {
+                    let shape = self.draw_state.get_object(selected.index);
+                    if shape.get_type() == Shape::Ellipse {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeArc(arc));
+                    }
+                }
+            }
+            // updates the corners if a rectangle is selected, the other shapes have no corners
+            GUIEvent::CornerRadii(radii) => {
+                self.corner_radii = radii;
+                if let Some(selected) = self.selected.as_ref() {
+                    let shape = self.draw_state.get_object(selected.index);
+                    if shape.get_type() == Shape::Rectangle {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeCornerRadii(radii));
+                    }
+                }
+            }
+            GUIEvent::OpenImage(embed) => self.open_image(embed),
+            // updates the degree of the b-spline if a shape is selected
+            GUIEvent::SplineDegree(degree) => {
+                self.spline_degree = degree;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeDegree(degree));
+                }
+            }
+            // updates the knots of the b-spline if a shape is selected
+            GUIEvent::KnotVector(knot_vector) => {
+                self.knot_vector = knot_vector;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeKnotVector(knot_vector));
+                }
+            }
+            // updates the weight of the selected control point, there is no weight for new curves
+            GUIEvent::PointWeight(weight) => {
+                if let Some(selected) = self.selected.as_ref() {
+                    if let Some(index) = selected.control_point_selected {
+                        self.draw_state
+                            .update_shape(selected.index, UpdateOp::ChangeWeight(index, weight));
+                    }
+                }
+            }
+            // updates the joint of the selected point of a path
+            GUIEvent::Joint(joint) => {
+                self.joint = joint;
+                let path = self.selected_path_joint().map(|(_, index)| index);
+                if let (Some(selected), Some(index)) = (self.selected.as_ref(), path) {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeJoint(index, joint));
+                }
+            }
+            // updates how the image is scaled if a shape is selected
+            GUIEvent::ImageSampling(sampling) => {
+                self.image_sampling = sampling;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeSampling(sampling));
+                }
+            }
+            // updates what the text says if a shape is selected
+            GUIEvent::Text(text) => {
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeText(text.clone()));
+                }
+                self.text = text;
+            }
+            // updates the size of the letters if a shape is selected
+            GUIEvent::FontSize(size) => {
+                let size = size.clamp(1.0, MAX_FONT_SIZE);
+                self.font_size = size;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeFontSize(size));
+                }
+            }
+            // the tolerance is only used when filling, a fill already made is not changed
+            GUIEvent::FillTolerance(tolerance) => self.fill_tolerance = tolerance,
+            GUIEvent::CanvasSize(width, height) => self.canvas_size = (width, height),
+            GUIEvent::EraserMode(mode) => self.eraser_mode = mode,
+            // like the tolerance, a polygon already drawn is not changed
+            GUIEvent::RegularPolygon(sides, star, ratio) => {
+                self.polygon_sides = sides;
+                self.star = star;
+                self.star_ratio = ratio;
+            }
+            // updates the blend mode if a shape is selected
+            GUIEvent::BlendMode(mode) => {
+                self.blend_mode = mode;
+                if let Some(selected) = self.selected.as_ref() {
+                    self.draw_state
+                        .update_shape(selected.index, UpdateOp::ChangeBlendMode(mode));
+                }
+            }
             // moves a shape if selected
             GUIEvent::ToFront(all) => {
                 if let Some(i) = self.selected.as_ref() {
@@ -378,6 +917,7 @@ impl AppState {
                 }
             }
             GUIEvent::DegreeElevate => self.handle_degree_elevate(),
+            GUIEvent::DegreeReduce => self.handle_degree_reduce(),
             GUIEvent::Subdivide => self.handle_subdivide(),
             GUIEvent::Save => self.save_state(),
             GUIEvent::Load => self.load_state(),
@@ -403,56 +943,150 @@ impl AppState {
 
     /// Handles the drawing of a shape based on the current shape type and mouse events.
     /// This function is responsible for starting, updating, and ending the shape creation process.
-    /// Since Triangles and Bezier curves are created with a different set of events (2 clicks, n
-    /// clicks) we have to check which shape is being created before reacting to events
+    /// Since Triangles, Bezier curves, polygons and polylines are created with a different set of
+    /// events (2 clicks, n clicks) we have to check which shape is being created before reacting to
+    /// events
     fn handle_figure_draw(&mut self, event: EventType) {
         match self.current {
             Shape::NoSelect => {}
-            Shape::Triangle => match event {
-                EventType::Mouse(action, 0, point) => match action {
-                    // triangle drawing reacts to 2 clicks
-                    MouseEvent::Click => {
-                        if self.cur_shape.is_some() {
-                            self.shape_end(point);
-                        } else {
-                            self.shape_start(point);
-                        }
-                    }
-                    MouseEvent::PressDrag => self.shape_update_last_point(point),
-                    MouseEvent::Release => self.shape_add_control_point(point),
-                    MouseEvent::Move => self.shape_update_last_point(point),
-                },
-                _ => {}
-            },
-            Shape::Bezier => match event {
-                EventType::Mouse(action, button, point) => match action {
-                    // bezier drawing reacts to n clicks untl right click is done
-                    MouseEvent::Click => {
-                        if button == 0 {
+            Shape::Triangle => {
+                if let EventType::Mouse(action, 0, point) = event {
+                    match action {
+                        // triangle drawing reacts to 2 clicks
+                        MouseEvent::Click => {
                             if self.cur_shape.is_some() {
-                                self.shape_add_control_point(point);
+                                self.shape_end(point);
                             } else {
                                 self.shape_start(point);
                             }
-                        } else if self.cur_shape.is_some() {
-                            self.shape_end(point);
                         }
+                        MouseEvent::PressDrag => self.shape_update_last_point(point),
+                        MouseEvent::Release => self.shape_add_control_point(point),
+                        MouseEvent::Move => self.shape_update_last_point(point),
                     }
-                    MouseEvent::Move => self.shape_update_last_point(point),
-                    _ => {}
-                },
-                _ => {}
-            },
+                }
+            }
+            Shape::Bezier
+            | Shape::BSpline
+            | Shape::CatmullRom
+            | Shape::Polygon
+            | Shape::Polyline => {
+                if let EventType::Mouse(action, button, point) = event {
+                    match action {
+                        // bezier, spline, polygon and polyline drawing reacts to n clicks untl
+                        // right click is done
+                        MouseEvent::Click => {
+                            if button == 0 {
+                                if self.cur_shape.is_some() {
+                                    self.shape_add_control_point(point);
+                                } else {
+                                    self.shape_start(point);
+                                }
+                            } else if self.cur_shape.is_some() {
+                                self.shape_end(point);
+                            }
+                        }
+                        MouseEvent::Move => self.shape_update_last_point(point),
+                        _ => {}
+                    }
+                }
+            }
+            Shape::Path => {
+                if let EventType::Mouse(action, button, point) = event {
+                    match action {
+                        // a path is drawn like a bezier curve, but each click adds a point that
+                        // ends a new cubic curve
+                        MouseEvent::Click => {
+                            if button == 0 {
+                                if self.cur_shape.is_some() {
+                                    self.shape_add_control_point(point);
+                                } else {
+                                    self.path_start(point);
+                                }
+                            } else if self.cur_shape.is_some() {
+                                self.shape_end(point);
+                            }
+                        }
+                        // dragging after a click pulls out the handles of the point placed
+                        MouseEvent::PressDrag => self.path_pull_handle(point),
+                        MouseEvent::Move => self.shape_update_last_point(point),
+                        _ => {}
+                    }
+                }
+            }
+            Shape::Fill => {
+                // the bucket fills the region under the cursor with a single click
+                if let EventType::Mouse(MouseEvent::Click, 0, point) = event {
+                    self.bucket_fill(point);
+                }
+            }
+            Shape::Eraser => {
+                if let EventType::Mouse(action, 0, point) = event {
+                    match action {
+                        // everything erased on a drag is undone at once
+                        MouseEvent::Click => {
+                            // the selected shape could be erased
+                            self.selected = None;
+                            self.draw_state.begin_group();
+                            self.eraser_last = Some(point);
+                            self.erase(point, point);
+                        }
+                        MouseEvent::PressDrag => {
+                            if let Some(last) = self.eraser_last.replace(point) {
+                                self.erase(last, point);
+                            }
+                        }
+                        MouseEvent::Release => {
+                            if let Some(last) = self.eraser_last.take() {
+                                self.erase(last, point);
+                            }
+                            self.end_eraser_drag();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Shape::RegularPolygon => {
+                if let EventType::Mouse(action, 0, point) = event {
+                    match action {
+                        // dragged from the center to the first corner
+                        MouseEvent::Click => self.regular_polygon_start(point),
+                        MouseEvent::PressDrag => self.regular_polygon_update(point),
+                        MouseEvent::Release => self.regular_polygon_end(point),
+                        _ => {}
+                    }
+                }
+            }
+            // images are dragged like rectangles, but only once there is a png to draw
+            Shape::Image if self.image.is_none() => {}
+            Shape::Text => {
+                // the text is written where the user clicks
+                if let EventType::Mouse(MouseEvent::Click, 0, point) = event {
+                    self.text_place(point);
+                }
+            }
+            Shape::Freehand => {
+                if let EventType::Mouse(action, 0, point) = event {
+                    match action {
+                        // freehand drawing records every position of the cursor while dragging
+                        MouseEvent::Click => self.shape_start(point),
+                        MouseEvent::PressDrag => self.freehand_add_point(point),
+                        MouseEvent::Release => self.freehand_end(point),
+                        _ => {}
+                    }
+                }
+            }
             // the other shapes behave all the same
-            _ => match event {
-                EventType::Mouse(action, 0, point) => match action {
-                    MouseEvent::Click => self.shape_start(point),
-                    MouseEvent::PressDrag => self.shape_update_last_point(point),
-                    MouseEvent::Release => self.shape_end(point),
-                    _ => {}
-                },
-                _ => {}
-            },
+            _ => {
+                if let EventType::Mouse(action, 0, point) = event {
+                    match action {
+                        MouseEvent::Click => self.shape_start(point),
+                        MouseEvent::PressDrag => self.shape_update_last_point(point),
+                        MouseEvent::Release => self.shape_end(point),
+                        _ => {}
+                    }
+                }
+            }
         }
     }
 
@@ -468,12 +1102,12 @@ impl AppState {
                 shape.draw_with_color(self.selection_color, canvas);
                 shape.draw_selection(self.points_color, self.bezier_control_polygon_color, canvas);
             } else {
-                shape.draw(canvas);
+                shape.draw_blended(canvas);
             }
         }
 
         if let Some(cur) = self.cur_shape.as_ref() {
-            cur.draw(canvas);
+            cur.draw_blended(canvas);
         }
     }
 
@@ -492,13 +1126,84 @@ impl AppState {
         }
     }
 
-    /// Subdivides the currently selected shape.
+    /// Reduces the degree of the currently selected Bezier curve, and keeps how much the curve
+    /// moved to show it. A line cant be reduced. When the reduction fails nothing is recorded and
+    /// the error is shown instead
+    fn handle_degree_reduce(&mut self) {
+        if let Some(selected) = self.selected.as_ref() {
+            let before = self.draw_state.get_object(selected.index).get_core();
+            if before.shape_type != Shape::Bezier || before.points.len() <= 2 {
+                return;
+            }
+
+            // a curve that cant be reduced is left as it was, and nothing is recorded
+            if !self
+                .draw_state
+                .update_shape(selected.index, UpdateOp::DegreeReduce)
+            {
+                let err = "this curve cant be reduced";
+                error!("{err}");
+                self.degree_reduction = Some((selected.index, before, Err(err)));
+                return;
+            }
+
+            let after = self.draw_state.get_object(selected.index).get_core();
+            let deviation = max_deviation(&before, &after);
+            self.degree_reduction = Some((selected.index, after, Ok(deviation)));
+        }
+    }
+
+    /// Subdivides the currently selected shape. Splines are not cut, a knot is inserted instead
+    /// and they stay selected
     fn handle_subdivide(&mut self) {
+        if let Some(selected) = self.selected.as_ref() {
+            let shape = self.draw_state.get_object(selected.index);
+            if matches!(shape.get_type(), Shape::BSpline | Shape::CatmullRom) {
+                self.draw_state
+                    .update_shape(selected.index, UpdateOp::InsertKnot);
+                return;
+            }
+        }
+
         if let Some(selected) = self.selected.take() {
             self.draw_state.subdivide_shape(selected.index);
         }
     }
 
+    /// A click on the curve of the selected bezier moves its subdivision point to the closest
+    /// point of the curve, the curve can still be dragged after that. With shift a control point
+    /// is added under the click instead, and its grabbed so it can be dragged right away. Returns
+    /// if the click was on the curve
+    fn handle_bezier_click(&mut self, point: Point) -> bool {
+        let Some(index) = self.selected.as_ref().map(|selected| selected.index) else {
+            return false;
+        };
+        let shape = self.draw_state.get_object(index);
+        if shape.get_type() != Shape::Bezier || !shape.hit_test(point) {
+            return false;
+        }
+        let core = shape.get_core();
+
+        if self.shift_pressed {
+            self.draw_state
+                .update_shape(index, UpdateOp::InsertControlPoint(point));
+            let mut selected = ShapeSelected::new(index);
+            let points = self.draw_state.get_object(index).get_core().points;
+            if let Some(added) = points.iter().position(|p| *p == point) {
+                selected.set_control_point(added);
+            }
+            self.selected = Some(selected);
+        } else {
+            let (t, _) = closest_point(&core, point);
+            self.draw_state
+                .update_shape(index, UpdateOp::UpdateSubdivide(t));
+            self.ui_subdivision_t = t;
+            self.selected = Some(ShapeSelected::new_with_point(index, point));
+        }
+
+        true
+    }
+
     /// Reorders the selected shape to a new index in the `objects` vector.
     fn reorder_selected(&mut self, new_index: usize) {
         if let Some(selected) = self.selected.as_ref() {
@@ -540,24 +1245,20 @@ impl AppState {
         None
     }
 
-    /// Checks if a Bezier curve is currently being built.
+    /// Checks if a Bezier curve or a spline is currently being built.
     fn is_building_bezier(&self) -> bool {
         if let Some(shape) = self.cur_shape.as_ref() {
-            return shape.get_type() == Shape::Bezier;
+            return matches!(
+                shape.get_type(),
+                Shape::Bezier | Shape::BSpline | Shape::CatmullRom | Shape::Path
+            );
         }
-        return false;
+        false
     }
 
     /// Checks if a control point of a figure is selected.
     fn is_control_point_select(&self, fig: usize, target: Point) -> Option<usize> {
-        let object = self.draw_state.get_object(fig);
-        for (i, p) in object.get_core().points.iter().enumerate() {
-            let delta = target - *p;
-            if delta.0 * delta.0 + delta.1 * delta.1 <= 100 {
-                return Some(i);
-            }
-        }
-        None
+        self.draw_state.get_object(fig).control_point_at(target)
     }
 
     /// Starts the creation of a new shape.
@@ -568,10 +1269,61 @@ impl AppState {
             color: self.color,
             fill_color: self.fill_color,
             shape_type: self.current,
+            blend_mode: self.blend_mode,
+            stroke_width: self.stroke_width,
+            line_cap: self.line_cap,
+            line_join: self.line_join,
+            miter_limit: self.miter_limit,
+            dash_pattern: self.dash_pattern.clone(),
+            dash_offset: self.dash_offset,
+            antialias: self.antialias,
+            fill_rule: self.fill_rule,
+            smooth: self.smooth,
+            rotation: self.rotation,
+            arc: self.arc,
+            corner_radii: self.corner_radii,
+            text: self.text.clone(),
+            font_size: self.font_size,
+            // only images carry the png, it can be big
+            image: if self.current == Shape::Image {
+                self.image.clone()
+            } else {
+                None
+            },
+            sampling: self.image_sampling,
+            degree: self.spline_degree,
+            knot_vector: self.knot_vector,
+            // the angles of the arc start with their default and are moved with their handles
+            ..Default::default()
         };
         self.cur_shape = Some(new_shape_from_core(core));
     }
 
+    /// Starts a path on its first point. The first curve goes to the cursor, its handles are on top
+    /// of its points until they are pulled out
+    fn path_start(&mut self, start: Point) {
+        self.shape_start(start);
+        if let Some(cur) = self.cur_shape.as_ref() {
+            let core = ShapeCore {
+                points: vec![start; 4],
+                joints: vec![self.joint; 2],
+                ..cur.get_core()
+            };
+            self.cur_shape = Some(new_shape_from_core(core));
+        }
+    }
+
+    /// Moves the handle that leaves the point placed last on the path being created. The handle on
+    /// the other side of the point follows it, depending on its joint
+    fn path_pull_handle(&mut self, handle: Point) {
+        if let Some(cur) = self.cur_shape.as_mut() {
+            // the last 3 points are the curve that follows the cursor, its first handle goes after
+            // the point placed last
+            let index = cur.get_core().points.len() - 3;
+            cur.update(&UpdateOp::ControlPoint(index, handle));
+        }
+    }
+
     /// Adds a control point to the shape currently being created.
     fn shape_add_control_point(&mut self, nxt: Point) {
         if let Some(cur) = self.cur_shape.as_mut() {
@@ -579,6 +1331,168 @@ impl AppState {
         }
     }
 
+    /// Adds a position of the cursor to the freehand path being created.
+    fn freehand_add_point(&mut self, nxt: Point) {
+        if let Some(cur) = self.cur_shape.as_mut() {
+            if cur.get_core().points.last() != Some(&nxt) {
+                cur.update(&UpdateOp::AddControlPoint(nxt));
+            }
+        }
+    }
+
+    /// Ends the freehand path being created. The cursor path is simplified before storing it, so
+    /// the shape keeps only a few points.
+    fn freehand_end(&mut self, end: Point) {
+        self.freehand_add_point(end);
+        if let Some(mut cur) = self.cur_shape.take() {
+            let mut points = simplify_path(&cur.get_core().points, FREEHAND_TOLERANCE);
+            // a click without moving the cursor is a dot
+            if points.len() == 1 {
+                points.push(points[0]);
+            }
+            cur.update(&UpdateOp::RewritePoints(points));
+            self.draw_state.add_shape(cur);
+        }
+    }
+
+    /// Starts a regular polygon on its center. Its stored as a polygon, so it can be edited point
+    /// by point like any other once its drawn.
+    fn regular_polygon_start(&mut self, center: Point) {
+        self.shape_start(center);
+        if let Some(cur) = self.cur_shape.as_ref() {
+            let core = ShapeCore {
+                shape_type: Shape::Polygon,
+                ..cur.get_core()
+            };
+            self.cur_shape = Some(new_shape_from_core(core));
+            self.polygon_center = Some(center);
+        }
+    }
+
+    /// Moves the first corner of the regular polygon being drawn to the cursor, the others follow
+    /// it. With SHIFT pressed the rotation goes in steps of 15 degrees, like the rectangles and
+    /// ellipses become squares and circles.
+    fn regular_polygon_update(&mut self, corner: Point) {
+        let (Some(cur), Some(center)) = (self.cur_shape.as_mut(), self.polygon_center) else {
+            return;
+        };
+
+        let radius = center.distance(corner);
+        let (dx, dy) = ((corner.0 - center.0) as f32, (corner.1 - center.1) as f32);
+        let mut angle = dy.atan2(dx);
+        if self.shift_pressed {
+            let snap = ROTATION_SNAP.to_radians();
+            angle = (angle / snap).round() * snap;
+        }
+
+        let ratio = self.star.then_some(self.star_ratio);
+        let points = regular_polygon(center, radius, angle, self.polygon_sides, ratio);
+        cur.update(&UpdateOp::RewritePoints(points));
+    }
+
+    /// Finishes the regular polygon being drawn. A click without a drag has no radius, every
+    /// corner would be on the same pixel, so no polygon is added
+    fn regular_polygon_end(&mut self, corner: Point) {
+        self.regular_polygon_update(corner);
+        let center = self.polygon_center.take();
+        if let Some(cur) = self.cur_shape.take() {
+            if center.is_some_and(|center| center.distance(corner) >= 1.0) {
+                self.draw_state.add_shape(cur);
+            }
+        }
+    }
+
+    /// Removes the text that was selected before if its empty and its not selected anymore. An
+    /// empty text has no area, so it could never be selected or deleted again
+    fn drop_empty_text(&mut self, selected_before: Option<usize>) {
+        let Some(index) = selected_before else {
+            return;
+        };
+        if self.selected.as_ref().map(|selected| selected.index) == Some(index) {
+            return;
+        }
+        let Some(shape) = self.draw_state.get_objects().get(index) else {
+            return;
+        };
+        let core = shape.get_core();
+        if core.shape_type != Shape::Text || !core.text.is_empty() {
+            return;
+        }
+
+        // undo must not bring back the empty text
+        self.draw_state.drop_shape(index);
+        if let Some(selected) = self.selected.as_mut() {
+            if selected.index > index {
+                selected.index -= 1;
+            }
+        }
+    }
+
+    /// Writes a new text with its top left corner on the point. The new text is selected, so what
+    /// is written on the text field of the UI goes to it.
+    fn text_place(&mut self, anchor: Point) {
+        self.shape_start(anchor);
+        if let Some(mut cur) = self.cur_shape.take() {
+            cur.update(&UpdateOp::RewritePoints(vec![anchor]));
+            self.draw_state.add_shape(cur);
+            self.selected = Some(ShapeSelected::new(self.draw_state.get_objects().len() - 1));
+        }
+    }
+
+    /// Fills the region of the drawing around the point with the fill color. The region is found
+    /// on the drawing as its rendered without the selection, and its stored as a new shape so it
+    /// can be undone, moved and saved like the others.
+    fn bucket_fill(&mut self, seed: Point) {
+        let (width, height) = self.canvas_size;
+        let frame = render(&self.draw_state, width, height);
+        let spans = flood_fill(&frame, seed, self.fill_tolerance);
+        if spans.is_empty() {
+            return;
+        }
+
+        let core = ShapeCore {
+            points: spans,
+            color: self.color,
+            fill_color: self.fill_color,
+            shape_type: Shape::Fill,
+            blend_mode: self.blend_mode,
+            ..Default::default()
+        };
+        self.draw_state.add_shape(new_shape_from_core(core));
+    }
+
+    /// Erases the shapes the eraser goes over while moving from "from" to "to". With the object
+    /// mode a shape is erased if it would be selected by clicking any point in between. With the
+    /// split mode lines and bezier curves lose only the part close to the eraser. The shapes are
+    /// checked from the last one, so erasing or cutting a shape doesnt change the index of the
+    /// ones left to check
+    fn erase(&mut self, from: Point, to: Point) {
+        let steps = from.distance(to).ceil().max(1.0);
+        let path: Vec<Point> = (0..=steps as usize)
+            .map(|i| from.interpolate(to, i as f32 / steps))
+            .collect();
+
+        for i in (0..self.draw_state.get_objects().len()).rev() {
+            let shape = self.draw_state.get_object(i);
+            let is_curve = matches!(shape.get_type(), Shape::Line | Shape::Bezier);
+
+            if self.eraser_mode == EraserMode::Split && is_curve {
+                if let Some((t0, t1)) = erased_range(&shape.get_core(), from, to, ERASER_RADIUS) {
+                    self.draw_state.cut_shape(i, t0, t1);
+                }
+            } else if path.iter().any(|p| shape.hit_test(*p)) {
+                self.draw_state.delete_shape(i);
+            }
+        }
+    }
+
+    /// Ends the drag of the eraser, everything it erased is undone at once from now on. Called on
+    /// any mouse release and when the tool changes, so the group of the drag is never left open
+    fn end_eraser_drag(&mut self) {
+        self.eraser_last = None;
+        self.draw_state.end_group();
+    }
+
     /// Updates the last control point of the shape currently being created.
     fn shape_update_last_point(&mut self, nxt: Point) {
         if let Some(cur) = self.cur_shape.as_mut() {
@@ -586,11 +1500,12 @@ impl AppState {
             // the cursor
             let next_point = match (self.shift_pressed, cur.get_core().shape_type) {
                 (true, Shape::Rectangle | Shape::Ellipse) => {
-                    let core = cur.get_core();
+                    // the corner where we started, a rotated ellipse keeps it where we clicked
+                    let start = cur.control_points()[0];
 
                     // if shape created is one of these
                     // delta.x != delta.y happens, its not square
-                    let delta = nxt - core.points[0];
+                    let delta = nxt - start;
                     // we do abs here to always pick the longest distance
                     let mx = max(delta.0.abs(), delta.1.abs());
                     let (sum_x, sum_y) = match (delta.0 > 0, delta.1 > 0) {
@@ -600,7 +1515,7 @@ impl AppState {
                         (false, true) => (-mx, mx),
                     };
 
-                    Point(core.points[0].0 + sum_x, core.points[0].1 + sum_y)
+                    Point(start.0 + sum_x, start.1 + sum_y)
                 }
                 _ => nxt,
             };
@@ -629,6 +1544,39 @@ impl AppState {
         }
     }
 
+    /// Asks for a png that is used by the new images, and by the selected one if its an image.
+    /// Embedded pngs are copied into the drawing, the others are only referenced by their path
+    fn open_image(&mut self, embed: bool) {
+        let Some(path) = FileDialog::new()
+            .set_title("Open image")
+            .add_filter("PNG Images", &["png"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let image = if embed {
+            match ImageSource::embed(&path) {
+                Ok(image) => image,
+                Err(err) => {
+                    error!("could not read the image: {err}");
+                    return;
+                }
+            }
+        } else {
+            ImageSource::File(path)
+        };
+
+        if let Some(selected) = self.selected.as_ref() {
+            let core = self.draw_state.get_objects()[selected.index].get_core();
+            if core.shape_type == Shape::Image {
+                self.draw_state
+                    .update_shape(selected.index, UpdateOp::ChangeImage(image.clone()));
+            }
+        }
+        self.image = Some(image);
+    }
+
     /// Saves the current drawing state to a file.
     fn save_state(&self) {
         if let Some(path) = FileDialog::new()
@@ -637,7 +1585,9 @@ impl AppState {
             .add_filter("JSON Files", &["json"])
             .save_file()
         {
-            self.draw_state.save_to_file(path);
+            if let Err(err) = self.draw_state.save_to_file(path) {
+                error!("could not save the drawing: {err}");
+            }
         }
     }
 
@@ -648,7 +1598,9 @@ impl AppState {
             .add_filter("JSON Files", &["json"])
             .pick_file()
         {
-            self.draw_state.load_from_file(path);
+            if let Err(err) = self.draw_state.load_from_file(path) {
+                error!("could not load the drawing: {err}");
+            }
         }
     }
 }
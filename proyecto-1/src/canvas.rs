@@ -1,4 +1,4 @@
-use crate::core::RGBA;
+use crate::core::{BlendMode, RGBA};
 
 // See Canvas<'a>. "'a'" its not a type, its a lifetime. A lifetime it's about how long a reference to data is valid.
 // I made Canvas generic over a lifetime to tell the compiler that
@@ -14,36 +14,118 @@ pub struct Canvas<'a> {
     buffer: &'a mut [u8],
     /// the length of the buffer
     length: u32,
+    /// when recording, how many times each pixel of the buffer was set. Its used to check that the
+    /// shapes never draw the same pixel twice, which would make translucent colors darker there
+    writes: Option<Vec<u32>>,
+    /// blend mode used by set_pixel. Shapes set it before drawing (see ShapeImpl::draw_blended)
+    blend_mode: BlendMode,
 }
 
 // implementation of methods for canvas
 impl<'a> Canvas<'a> {
     /// initializes a new Canvas, receives the buffer and its length
     pub fn new(buffer: &'a mut [u8], length: u32) -> Self {
-        Canvas { buffer, length }
+        Canvas {
+            buffer,
+            length,
+            writes: None,
+            blend_mode: BlendMode::Normal,
+        }
     }
 
-    /// sets a pixel on the buffer. The left upper corner is the origin, x and y are checked to be
-    /// inside the buffer boundaries.
-    pub fn set_pixel(&mut self, x: i32, y: i32, color: RGBA) {
-        if x < 0 || y < 0 {
-            return;
+    /// initializes a Canvas that also counts how many times each pixel is set (see write_count)
+    pub fn new_recording(buffer: &'a mut [u8], length: u32) -> Self {
+        let writes = vec![0; buffer.len() / 4];
+        Canvas {
+            buffer,
+            length,
+            writes: Some(writes),
+            blend_mode: BlendMode::Normal,
         }
+    }
+
+    /// width of the buffer in pixels
+    pub fn width(&self) -> u32 {
+        self.length
+    }
 
-        let index = (y as u32 * self.length + x as u32) as usize * 4;
+    /// height of the buffer in pixels, the rows that fit completely in it
+    pub fn height(&self) -> u32 {
+        if self.length == 0 {
+            return 0;
+        }
+        (self.buffer.len() / 4 / self.length as usize) as u32
+    }
+
+    /// changes the blend mode used for every pixel set from now on
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// returns how many times a pixel was set since the canvas was created. Its always 0 if the
+    /// canvas is not recording or the pixel is outside the buffer
+    pub fn write_count(&self, x: i32, y: i32) -> u32 {
+        match (self.writes.as_ref(), self.pixel_index(x, y)) {
+            (Some(writes), Some(index)) => writes[index / 4],
+            _ => 0,
+        }
+    }
+
+    /// returns every pixel set at least once with the amount of times it was set. Empty if the
+    /// canvas is not recording
+    pub fn written_pixels(&self) -> Vec<(i32, i32, u32)> {
+        let Some(writes) = self.writes.as_ref() else {
+            return vec![];
+        };
+
+        let length = self.length as usize;
+        writes
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(i, count)| ((i % length) as i32, (i / length) as i32, *count))
+            .collect()
+    }
+
+    /// returns the index of the first byte of a pixel, or None if the pixel is outside the buffer
+    fn pixel_index(&self, x: i32, y: i32) -> Option<usize> {
+        // x must also be checked against the width, if not the pixel ends up on the next row
+        if x < 0 || y < 0 || x as u32 >= self.length {
+            return None;
+        }
+
+        // in usize, with u32 a big y times the width overflows before we can check it
+        let index = (y as usize * self.length as usize + x as usize) * 4;
 
         if index >= self.buffer.len() {
+            return None;
+        }
+
+        Some(index)
+    }
+
+    /// sets a pixel on the buffer. The left upper corner is the origin, x and y are checked to be
+    /// inside the buffer boundaries.
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: RGBA) {
+        let Some(index) = self.pixel_index(x, y) else {
             return;
+        };
+
+        if let Some(writes) = self.writes.as_mut() {
+            writes[index / 4] += 1;
         }
 
-        // we only perform the alpha calculation if the current color alpha is bellow max
-        if color[3] < 255 {
+        // we only perform the alpha calculation if the current color alpha is bellow max or we are
+        // blending. An opaque color painted over anything is the same color, so we save the
+        // calculation
+        if color[3] < 255 || self.blend_mode != BlendMode::Normal {
             // here we get the previous color on that position
             let raw_prev = &self.buffer[index..index + 4];
             let prev_color = RGBA::new(raw_prev[0], raw_prev[1], raw_prev[2], raw_prev[3]);
 
-            // the + operation between colors is defined at ./src/core/rgba.rs
-            let new_color = color + prev_color;
+            // the blend mode changes the color of the shape based on what is below, then the +
+            // operation between colors (defined at ./src/core/rgba.rs) paints it over
+            let new_color = self.blend_mode.blend(color, prev_color) + prev_color;
 
             // we paste the new_color which is has 4 u8 entries
             self.buffer[index..index + 4].copy_from_slice(&new_color);
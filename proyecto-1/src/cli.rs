@@ -0,0 +1,138 @@
+//! command line mode of the app. When the app receives arguments we run one of these commands
+//! instead of opening a window. This way we can script things around saved drawings
+use std::path::Path;
+
+use paint_app::{
+    core::{Point, Shape, RGBA},
+    draw_state::DrawState,
+    headless,
+};
+
+use crate::{HEIGHT, WIDTH};
+
+const USAGE: &str = "usage:
+    paint_app                          opens the app
+    paint_app render <in.json> <out.png> [--width <px>] [--height <px>] [--background <r,g,b[,a]>]
+    paint_app info <in.json>           prints the shapes, bounding box and colors of a drawing
+    paint_app validate <in.json>       checks that a drawing can be loaded
+    paint_app help                     prints this message";
+
+/// runs the command given by the arguments (without the program name) and returns the exit code
+pub fn run(args: &[String]) -> i32 {
+    let result = match args[0].as_str() {
+        "render" => render(&args[1..]),
+        "info" => info(&args[1..]),
+        "validate" => validate(&args[1..]),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        cmd => Err(format!(
+            "unknown command \"{cmd}\", run \"paint_app help\" to see the commands"
+        )),
+    };
+
+    match result {
+        Ok(_) => 0,
+        Err(msg) => {
+            eprintln!("error: {msg}");
+            1
+        }
+    }
+}
+
+/// render command. Draws the json drawing into a png
+fn render(args: &[String]) -> Result<(), String> {
+    let args = headless::parse_render_args(args, (WIDTH, HEIGHT))?;
+
+    let mut state = load(&args.input)?;
+    if let Some(color) = args.background {
+        state.change_background_color(color);
+    }
+
+    headless::render(&state, args.width, args.height)
+        .save_png(Path::new(&args.output))
+        .map_err(|err| format!("could not write {}: {err}", args.output))
+}
+
+/// info command. Prints how many shapes of each type we have, the box that contains every control
+/// point and the colors used
+fn info(args: &[String]) -> Result<(), String> {
+    let [file] = args else {
+        return Err("info needs an input json".to_string());
+    };
+    let state = load(file)?;
+    let cores: Vec<_> = state.get_objects().iter().map(|s| s.get_core()).collect();
+
+    println!("objects: {}", cores.len());
+
+    // we count with a vector to keep the order in which each type appears on the drawing
+    let mut count: Vec<(Shape, usize)> = vec![];
+    for core in cores.iter() {
+        match count
+            .iter_mut()
+            .find(|(shape, _)| *shape == core.shape_type)
+        {
+            Some((_, n)) => *n += 1,
+            None => count.push((core.shape_type, 1)),
+        }
+    }
+    for (shape, n) in count {
+        println!("    {shape}: {n}");
+    }
+
+    let points = cores.iter().flat_map(|c| c.points.iter());
+    let bounds = points.fold(None, |bounds: Option<(Point, Point)>, p| match bounds {
+        None => Some((*p, *p)),
+        Some((min, max)) => Some((
+            Point(min.0.min(p.0), min.1.min(p.1)),
+            Point(max.0.max(p.0), max.1.max(p.1)),
+        )),
+    });
+    match bounds {
+        Some((min, max)) => println!(
+            "bounding box: ({}, {}) - ({}, {})",
+            min.0, min.1, max.0, max.1
+        ),
+        None => println!("bounding box: none"),
+    }
+
+    println!("background: {}", state.get_background_color());
+
+    // transparent fills are not drawn, so they are not used
+    let mut colors: Vec<RGBA> = vec![];
+    for core in cores.iter() {
+        for color in [core.color, core.fill_color] {
+            if !color.is_transparent() && !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    println!("colors used: {}", colors.len());
+    for color in colors {
+        println!("    {color}");
+    }
+
+    Ok(())
+}
+
+/// validate command. Loading already checks the file, so we only report the result
+fn validate(args: &[String]) -> Result<(), String> {
+    let [file] = args else {
+        return Err("validate needs an input json".to_string());
+    };
+    let state = load(file)?;
+    println!("{file}: ok, {} objects", state.get_objects().len());
+
+    Ok(())
+}
+
+/// loads a drawing and turns the error into a message
+fn load(file: &str) -> Result<DrawState, String> {
+    let mut state = DrawState::new();
+    state
+        .load_from_file(file.into())
+        .map_err(|err| format!("could not load {file}: {err}"))?;
+
+    Ok(state)
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// which part of an ellipse is drawn. Every kind except the full ellipse goes from the start
+/// angle to the end angle of the shape core
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ArcKind {
+    /// the whole ellipse, the angles are not used
+    #[default]
+    Full,
+    /// only the border between both angles. Its never filled
+    Arc,
+    /// the arc closed with two lines to the center, like a slice of a pie
+    Pie,
+    /// the arc closed with a line between its ends
+    Chord,
+}
+
+impl ArcKind {
+    /// every kind, used by the UI
+    pub const ALL: [ArcKind; 4] = [ArcKind::Full, ArcKind::Arc, ArcKind::Pie, ArcKind::Chord];
+}
+
+// the names shown on the UI
+impl fmt::Display for ArcKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArcKind::Full => write!(f, "Full"),
+            ArcKind::Arc => write!(f, "Arc"),
+            ArcKind::Pie => write!(f, "Pie"),
+            ArcKind::Chord => write!(f, "Chord"),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::RGBA;
+
+/// how the color of a shape is mixed with the colors already drawn below it. These are the
+/// "separable" blend modes, each channel (r, g, b) is blended on its own
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BlendMode {
+    /// the color of the shape is painted over, like before we had blend modes
+    #[default]
+    Normal,
+    /// multiplies both colors. The result is always darker, white does nothing
+    Multiply,
+    /// the inverse of multiply. The result is always lighter, black does nothing
+    Screen,
+    /// multiply on the dark parts of the colors below, screen on the light ones
+    Overlay,
+    /// adds both colors
+    Add,
+    /// the absolute difference between both colors
+    Difference,
+    /// keeps the darkest color of each channel
+    Darken,
+    /// keeps the lightest color of each channel
+    Lighten,
+}
+
+impl BlendMode {
+    /// every blend mode, used by the UI
+    pub const ALL: [BlendMode; 8] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Add,
+        BlendMode::Difference,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+    ];
+
+    /// blends the color of a shape (src) with the color below it (dst). The result keeps the alpha of
+    /// the source and is then painted over dst as usual (see RGBA + RGBA).
+    /// If dst is transparent there's nothing to blend with, so the more transparent dst is the more
+    /// the result looks like the original source color:
+    ///
+    /// result = (1 - dst_alpha) * src + dst_alpha * blend(src, dst)
+    pub fn blend(&self, src: RGBA, dst: RGBA) -> RGBA {
+        if *self == BlendMode::Normal {
+            return src;
+        }
+
+        let dst_alpha = dst[3] as u32;
+        let channel = |i: usize| {
+            let s = src[i] as u32;
+            let blended = self.blend_channel(s, dst[i] as u32);
+            (((255 - dst_alpha) * s + dst_alpha * blended + 127) / 255) as u8
+        };
+
+        RGBA::new(channel(0), channel(1), channel(2), src[3])
+    }
+
+    /// blends one channel. Both values go from 0 to 255, we use u32 to multiply without overflow
+    fn blend_channel(&self, s: u32, d: u32) -> u32 {
+        // a * b with both values between 0 and 1, but with integers and rounding
+        let mul = |a: u32, b: u32| (a * b + 127) / 255;
+        let screen = |a: u32, b: u32| a + b - mul(a, b);
+
+        match self {
+            BlendMode::Normal => s,
+            BlendMode::Multiply => mul(s, d),
+            BlendMode::Screen => screen(s, d),
+            BlendMode::Overlay => {
+                if d <= 127 {
+                    mul(s, 2 * d)
+                } else {
+                    screen(s, 2 * d - 255)
+                }
+            }
+            BlendMode::Add => (s + d).min(255),
+            BlendMode::Difference => s.abs_diff(d),
+            BlendMode::Darken => s.min(d),
+            BlendMode::Lighten => s.max(d),
+        }
+    }
+}
+
+// the name shown on the UI
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BlendMode::Normal => write!(f, "Normal"),
+            BlendMode::Multiply => write!(f, "Multiply"),
+            BlendMode::Screen => write!(f, "Screen"),
+            BlendMode::Overlay => write!(f, "Overlay"),
+            BlendMode::Add => write!(f, "Add"),
+            BlendMode::Difference => write!(f, "Difference"),
+            BlendMode::Darken => write!(f, "Darken"),
+            BlendMode::Lighten => write!(f, "Lighten"),
+        }
+    }
+}
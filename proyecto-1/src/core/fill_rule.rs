@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// decides which parts of a polygon are inside when its border crosses itself. Both rules count
+/// the borders crossed by a ray that goes from the point to the right
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum FillRule {
+    /// inside if the border goes around the point a different amount of times clockwise than
+    /// counter clockwise. A star drawn in one stroke is filled completely
+    #[default]
+    NonZero,
+    /// inside if the ray crosses the border an odd amount of times. The center of a star drawn in
+    /// one stroke is a hole
+    EvenOdd,
+}
+
+impl FillRule {
+    /// every fill rule, used by the UI
+    pub const ALL: [FillRule; 2] = [FillRule::NonZero, FillRule::EvenOdd];
+
+    /// checks if a point is inside given its winding number: how many times the border goes
+    /// around it, counting +1 for each crossing going down and -1 going up
+    pub fn is_inside(&self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+// the names shown on the UI
+impl fmt::Display for FillRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FillRule::NonZero => write!(f, "Non-zero"),
+            FillRule::EvenOdd => write!(f, "Even-odd"),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fmt, fs, io};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+/// where the png of an image comes from
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ImageSource {
+    /// a png file outside of the drawing. The drawing stays small but the file must be kept with
+    /// it, if its moved the image cant be drawn
+    File(PathBuf),
+    /// the bytes of the png written as base64 inside the drawing, so it can be moved alone. The
+    /// text is shared by every copy of the core (undo records, the clipboard...), its the biggest
+    /// thing a drawing has
+    Embedded(Arc<str>),
+}
+
+impl ImageSource {
+    /// reads the png file and embeds its bytes
+    pub fn embed(path: &Path) -> io::Result<ImageSource> {
+        Ok(ImageSource::Embedded(
+            STANDARD.encode(fs::read(path)?).into(),
+        ))
+    }
+
+    /// the bytes of the png, read from the file or decoded from the base64 text
+    pub fn png_bytes(&self) -> io::Result<Vec<u8>> {
+        match self {
+            ImageSource::File(path) => fs::read(path),
+            ImageSource::Embedded(data) => STANDARD
+                .decode(data.as_bytes())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        }
+    }
+}
+
+/// how the color of each pixel is taken from the image when its drawn bigger or smaller
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ImageSampling {
+    /// the color of the closest pixel of the image. Enlarged images look blocky
+    Nearest,
+    /// the colors of the 4 closest pixels of the image mixed by how close they are. Enlarged
+    /// images look smooth
+    #[default]
+    Bilinear,
+}
+
+impl ImageSampling {
+    /// every sampling, used by the UI
+    pub const ALL: [ImageSampling; 2] = [ImageSampling::Nearest, ImageSampling::Bilinear];
+}
+
+// the names shown on the UI
+impl fmt::Display for ImageSampling {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageSampling::Nearest => write!(f, "Nearest"),
+            ImageSampling::Bilinear => write!(f, "Bilinear"),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// how the two cubic curves that meet at a point of a path are joined. It decides what happens
+/// with the handle on the other side when one of them is moved
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum JointKind {
+    /// the handles move on their own, the path can have a sharp corner there
+    Corner,
+    /// the handles are kept on the same line, so the path doesnt break there (G1). Each one keeps
+    /// its own length
+    Smooth,
+    /// the handles are kept on the same line and with the same length (C1), the curve goes
+    /// through the point at the same speed on both sides
+    #[default]
+    Symmetric,
+}
+
+impl JointKind {
+    /// every kind, used by the UI
+    pub const ALL: [JointKind; 3] = [JointKind::Corner, JointKind::Smooth, JointKind::Symmetric];
+}
+
+// the names shown on the UI
+impl fmt::Display for JointKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JointKind::Corner => write!(f, "Corner"),
+            JointKind::Smooth => write!(f, "Smooth"),
+            JointKind::Symmetric => write!(f, "Symmetric"),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// how the knots of a B-spline are spread when they are not given one by one. Both have the
+/// knots at the same distance, they only change on the ends
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum KnotVector {
+    /// the first and last knots are repeated degree + 1 times (also called open uniform), so the
+    /// curve starts on the first control point and ends on the last one
+    #[default]
+    Clamped,
+    /// every knot is different, the curve starts and ends near the middle of the first and last
+    /// pieces of the control polygon
+    Uniform,
+}
+
+impl KnotVector {
+    /// every kind, used by the UI
+    pub const ALL: [KnotVector; 2] = [KnotVector::Clamped, KnotVector::Uniform];
+
+    /// the knots of a B-spline with n control points of the given degree
+    pub fn knots(&self, n: usize, degree: usize) -> Vec<f32> {
+        match self {
+            KnotVector::Clamped => {
+                let inner = n - degree;
+                let mut knots = vec![0.0; degree + 1];
+                knots.extend((1..inner).map(|i| i as f32));
+                knots.extend(vec![inner as f32; degree + 1]);
+                knots
+            }
+            KnotVector::Uniform => (0..n + degree + 1).map(|i| i as f32).collect(),
+        }
+    }
+}
+
+// the names shown on the UI
+impl fmt::Display for KnotVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KnotVector::Clamped => write!(f, "Clamped"),
+            KnotVector::Uniform => write!(f, "Uniform"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// how the ends of an open border (lines, curves) are drawn when its wider than 1 pixel
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LineCap {
+    /// the border ends exactly on the end point
+    #[default]
+    Butt,
+    /// a half circle is added on each end
+    Round,
+    /// the border continues half its width after the end point
+    Square,
+}
+
+impl LineCap {
+    /// every cap, used by the UI
+    pub const ALL: [LineCap; 3] = [LineCap::Butt, LineCap::Round, LineCap::Square];
+}
+
+/// how two segments of a border wider than 1 pixel are joined (triangle and rectangle corners,
+/// the lines of a bezier curve)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum LineJoin {
+    /// the outer borders of both segments are extended until they meet, making a sharp corner.
+    /// If the corner is longer than the miter limit its drawn as a bevel
+    #[default]
+    Miter,
+    /// the corner is rounded
+    Round,
+    /// the corner is cut flat
+    Bevel,
+}
+
+impl LineJoin {
+    /// every join, used by the UI
+    pub const ALL: [LineJoin; 3] = [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel];
+}
+
+// the names shown on the UI
+impl fmt::Display for LineCap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineCap::Butt => write!(f, "Butt"),
+            LineCap::Round => write!(f, "Round"),
+            LineCap::Square => write!(f, "Square"),
+        }
+    }
+}
+
+impl fmt::Display for LineJoin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LineJoin::Miter => write!(f, "Miter"),
+            LineJoin::Round => write!(f, "Round"),
+            LineJoin::Bevel => write!(f, "Bevel"),
+        }
+    }
+}
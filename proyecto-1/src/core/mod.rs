@@ -2,16 +2,37 @@ use crate::canvas::Canvas;
 
 // this are rust modules. mod.rs defines a module and imports from other files inside its root
 // folder. Here we have access to point and rgba but we only expose what is under the "pub" keyword
+mod arc_kind;
+mod blend_mode;
+mod fill_rule;
+mod image;
+mod joint_kind;
+mod knot_vector;
+mod line_style;
 mod point;
 mod rgba;
 
-// we expose the Point and RGBA modules
+// we expose the ArcKind, BlendMode, FillRule, LineCap, LineJoin, Point and RGBA modules
+pub use arc_kind::ArcKind;
+pub use blend_mode::BlendMode;
+pub use fill_rule::FillRule;
+pub use image::{ImageSampling, ImageSource};
+pub use joint_kind::JointKind;
+pub use knot_vector::KnotVector;
+pub use line_style::{LineCap, LineJoin};
 pub use point::Point;
 pub use rgba::RGBA;
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// how far from a control point it can be grabbed, in pixels
+pub const CONTROL_POINT_REACH: f32 = 10.0;
+
+/// the biggest size of the letters of a text, in pixels. The letters are drawn into a raster of
+/// about this size, a bigger one would take too long
+pub const MAX_FONT_SIZE: f32 = 400.0;
+
 /// shape is an enum that specifies the shape
 #[derive(Copy, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Shape {
@@ -22,6 +43,31 @@ pub enum Shape {
     Triangle,
     Rectangle,
     Bezier,
+    /// B-spline curve, a chain of curves where each control point only moves the part of the
+    /// curve close to it
+    BSpline,
+    /// curve that goes through every control point
+    CatmullRom,
+    /// chain of cubic bezier curves. Each point where two of them meet has a handle on each side
+    Path,
+    /// closed shape with any amount of points
+    Polygon,
+    /// open border with any amount of points
+    Polyline,
+    /// path drawn with the cursor
+    Freehand,
+    /// region painted with the bucket tool
+    Fill,
+    /// text written with the bundled font
+    Text,
+    /// png image stretched between two corners
+    Image,
+    /// the eraser tool. Its on the list of shapes since its selected like them, but an eraser is
+    /// never created
+    Eraser,
+    /// regular polygon and star tool. Like the eraser its only a tool, what it draws is stored as
+    /// a polygon
+    RegularPolygon,
 }
 
 // this is for debugging and the UI, rust uses println!("Hello world") and to print objects we do
@@ -36,6 +82,17 @@ impl fmt::Display for Shape {
             Shape::Triangle => write!(f, "Triangle"),
             Shape::Rectangle => write!(f, "Rectangle"),
             Shape::Bezier => write!(f, "Bezier"),
+            Shape::BSpline => write!(f, "B-spline"),
+            Shape::CatmullRom => write!(f, "Catmull-Rom"),
+            Shape::Path => write!(f, "Path"),
+            Shape::Polygon => write!(f, "Polygon"),
+            Shape::Polyline => write!(f, "Polyline"),
+            Shape::Freehand => write!(f, "Freehand"),
+            Shape::Fill => write!(f, "Bucket fill"),
+            Shape::Text => write!(f, "Text"),
+            Shape::Image => write!(f, "Image"),
+            Shape::Eraser => write!(f, "Eraser"),
+            Shape::RegularPolygon => write!(f, "Regular polygon"),
         }
     }
 }
@@ -50,16 +107,68 @@ pub enum UpdateOp {
     ChangeColor(RGBA),
     /// ChangeColor changes the fill color of a given Shape. Receives as argument a color RGBA
     ChangeFillColor(RGBA),
+    /// ChangeStrokeWidth changes the width of the border of a given Shape, in pixels
+    ChangeStrokeWidth(u32),
+    /// ChangeLineCap changes how the ends of the border are drawn
+    ChangeLineCap(LineCap),
+    /// ChangeLineJoin changes how the corners of the border are drawn
+    ChangeLineJoin(LineJoin),
+    /// ChangeMiterLimit changes the longest miter corner, relative to the border width
+    ChangeMiterLimit(f32),
+    /// ChangeDashPattern changes the dashes of the border: the lengths of each dash and gap and
+    /// how far into the pattern the border starts
+    ChangeDashPattern(Vec<u32>, u32),
+    /// ChangeAntialias switches between the pixel exact and the anti-aliased rendering
+    ChangeAntialias(bool),
+    /// ChangeFillRule changes which parts of a polygon that crosses itself are filled
+    ChangeFillRule(FillRule),
+    /// ChangeSmooth switches between joining the points of a freehand path with lines or with a
+    /// curve
+    ChangeSmooth(bool),
+    /// ChangeRotation changes the angle an ellipse is rotated around its center, in degrees
+    ChangeRotation(f32),
+    /// ChangeArc changes which part of an ellipse is drawn
+    ChangeArc(ArcKind),
+    /// ChangeCornerRadii changes the radius of each corner of a rectangle
+    ChangeCornerRadii([u32; 4]),
+    /// ChangeText changes what a text says
+    ChangeText(String),
+    /// ChangeFontSize changes the size of the letters of a text, in pixels
+    ChangeFontSize(f32),
+    /// ChangeImage changes the png drawn by an image
+    ChangeImage(ImageSource),
+    /// ChangeSampling changes how an image is scaled
+    ChangeSampling(ImageSampling),
+    /// ChangeDegree changes the degree of the pieces of a B-spline
+    ChangeDegree(u32),
+    /// ChangeKnotVector changes how the knots of a B-spline are spread. The knots given one by
+    /// one (like after InsertKnot) are forgotten
+    ChangeKnotVector(KnotVector),
+    /// ChangeWeight changes the weight of a control point of a bezier curve
+    ChangeWeight(usize, f32),
+    /// ChangeJoint changes how the curves of a path are joined at one of its points. The index is
+    /// the one of the joint, the first point of the path is the joint 0
+    ChangeJoint(usize, JointKind),
+    /// ChangeBlendMode changes how the shape is mixed with what is drawn below it
+    ChangeBlendMode(BlendMode),
     /// Adds a control point to the shape. Used for triangle on drawing and bezier
     AddControlPoint(Point),
     /// Changes a control point for another
     ControlPoint(usize, Point),
     /// Rewrites all points on the shape for another set of points
     RewritePoints(Vec<Point>),
-    /// Makes a subdivide on the shape. Currently only used on bezier
+    /// Changes where the shape is cut by subdivide. Only used on bezier and line
     UpdateSubdivide(f32),
     /// Increases the degree of the shape. Only used on bezier
     DegreeElevate,
+    /// Lowers the degree of the shape, the curve can change a bit. Only used on bezier
+    DegreeReduce,
+    /// Adds a control point at the given point, between the control points around the closest
+    /// point of the curve. Only used on bezier
+    InsertControlPoint(Point),
+    /// Adds a control point where the curve is cut by subdivide, without changing the curve. Only
+    /// used on splines
+    InsertKnot,
 }
 
 ///Trait for the shape implementation. Implements some default functions and is used for rendering
@@ -84,6 +193,89 @@ pub trait ShapeImpl {
             UpdateOp::ChangeFillColor(color) => {
                 core.fill_color = *color;
             }
+            // update its border width
+            UpdateOp::ChangeStrokeWidth(width) => {
+                core.stroke_width = *width;
+            }
+            // update the style of its border
+            UpdateOp::ChangeLineCap(cap) => {
+                core.line_cap = *cap;
+            }
+            UpdateOp::ChangeLineJoin(join) => {
+                core.line_join = *join;
+            }
+            UpdateOp::ChangeMiterLimit(limit) => {
+                core.miter_limit = *limit;
+            }
+            UpdateOp::ChangeDashPattern(pattern, offset) => {
+                core.dash_pattern = pattern.clone();
+                core.dash_offset = *offset;
+            }
+            // update which parts of a polygon are filled
+            UpdateOp::ChangeFillRule(rule) => {
+                core.fill_rule = *rule;
+            }
+            // update how the points of a freehand path are joined
+            UpdateOp::ChangeSmooth(smooth) => {
+                core.smooth = *smooth;
+            }
+            // update the rotation and the part drawn of an ellipse
+            UpdateOp::ChangeRotation(rotation) => {
+                core.rotation = *rotation;
+            }
+            UpdateOp::ChangeArc(arc) => {
+                core.arc = *arc;
+            }
+            // update the corners of a rectangle
+            UpdateOp::ChangeCornerRadii(radii) => {
+                core.corner_radii = *radii;
+            }
+            // update a text
+            UpdateOp::ChangeText(text) => {
+                core.text = text.clone();
+            }
+            UpdateOp::ChangeFontSize(size) => {
+                core.font_size = *size;
+            }
+            // update an image
+            UpdateOp::ChangeImage(image) => {
+                core.image = Some(image.clone());
+            }
+            UpdateOp::ChangeSampling(sampling) => {
+                core.sampling = *sampling;
+            }
+            // update a b-spline. Different knots for the same control points would not fit the
+            // new degree
+            UpdateOp::ChangeDegree(degree) => {
+                core.degree = *degree;
+                core.knots.clear();
+            }
+            UpdateOp::ChangeKnotVector(knot_vector) => {
+                core.knot_vector = *knot_vector;
+                core.knots.clear();
+            }
+            // update how much a control point of a bezier pulls the curve. The weights are only
+            // stored once one is not 1
+            UpdateOp::ChangeWeight(index, weight) if *index < core.points.len() => {
+                core.weights = core.point_weights();
+                core.weights[*index] = *weight;
+            }
+            // update a joint of a path, the path moves its handles to fit it
+            UpdateOp::ChangeJoint(index, joint) => {
+                let mut joints = core.path_joints();
+                if let Some(current) = joints.get_mut(*index) {
+                    *current = *joint;
+                    core.joints = joints;
+                }
+            }
+            // update how its rasterized
+            UpdateOp::ChangeAntialias(antialias) => {
+                core.antialias = *antialias;
+            }
+            // update its blend mode
+            UpdateOp::ChangeBlendMode(mode) => {
+                core.blend_mode = *mode;
+            }
             // move the shape
             UpdateOp::Move(delta) => {
                 for p in core.points.iter_mut() {
@@ -94,17 +286,23 @@ pub trait ShapeImpl {
             // add a control point
             UpdateOp::AddControlPoint(point) => {
                 core.points.push(*point);
+                if !core.weights.is_empty() {
+                    core.weights.push(1.0);
+                }
             }
             // rewrite all points for others
-            // used when doing undo and redo
+            // used when doing undo and redo. The weights and joints dont fit a different number of
+            // points
             UpdateOp::RewritePoints(points) => {
+                if points.len() != core.points.len() {
+                    core.weights.clear();
+                    core.joints.clear();
+                }
                 core.points = points.clone();
             }
             // change a control point for another one
-            UpdateOp::ControlPoint(index, point) => {
-                if *index < core.points.len() {
-                    core.points[*index] = *point;
-                }
+            UpdateOp::ControlPoint(index, point) if *index < core.points.len() => {
+                core.points[*index] = *point;
             }
             // there are other modification methods that should be implemented by a concrete object
             _ => {}
@@ -120,8 +318,8 @@ pub trait ShapeImpl {
         let siz = all_x.len();
 
         (
-            all_x.sum::<f32>() as f32 / siz as f32,
-            all_y.sum::<f32>() as f32 / siz as f32,
+            all_x.sum::<f32>() / siz as f32,
+            all_y.sum::<f32>() / siz as f32,
         )
             .into()
     }
@@ -129,12 +327,25 @@ pub trait ShapeImpl {
     /// this method draws how a shape should look when is selected. For most is just drawing the
     /// control points. Receives the color to use for the control points
     fn draw_selection_basic<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
-        let points = self.get_core().points;
-        for p in points {
+        for p in self.control_points() {
             self.draw_control_point(p, color, canvas);
         }
     }
 
+    /// returns the points the user can drag when the shape is selected. For most shapes these are
+    /// all the points of the core, the index of each one is the one used by UpdateOp::ControlPoint
+    fn control_points(&self) -> Vec<Point> {
+        self.get_core().points
+    }
+
+    /// returns the index of the control point under the target, the points can be grabbed from
+    /// CONTROL_POINT_REACH pixels away. If there are several the first one is returned
+    fn control_point_at(&self, target: Point) -> Option<usize> {
+        self.control_points()
+            .iter()
+            .position(|p| p.distance(target) <= CONTROL_POINT_REACH)
+    }
+
     /// this method draws the control points of a given shape
     /// it receives the point to draw and the color
     fn draw_control_point<'a>(&self, p: Point, color: RGBA, canvas: &mut Canvas<'a>) {
@@ -166,7 +377,7 @@ pub trait ShapeImpl {
     }
 
     /// subdivision function to create 2 more shapes from the original
-    /// Currently only bezier implements this, we added it as part of the interface to keep the app
+    /// Currently only bezier and line implement this, we added it as part of the interface to keep the app
     /// state management agnostic from the shape type used
     fn subdivide(&self) -> Option<(ShapeCore, ShapeCore)> {
         None
@@ -186,6 +397,14 @@ pub trait ShapeImpl {
     /// draws the shape into a given canvas
     fn draw<'a>(&self, canvas: &mut Canvas<'a>);
 
+    /// draws the shape using its blend mode. The canvas goes back to the normal mode afterwards, so
+    /// the selection and control points are always painted over
+    fn draw_blended<'a>(&self, canvas: &mut Canvas<'a>) {
+        canvas.set_blend_mode(self.get_core().blend_mode);
+        self.draw(canvas);
+        canvas.set_blend_mode(BlendMode::Normal);
+    }
+
     /// draws the shape with a given border color
     fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>);
 
@@ -195,7 +414,7 @@ pub trait ShapeImpl {
 
 ///This is the core fields of every shape. Is used for shape serialization into json
 ///Also helps with shape management and to avoid the use of dynamic types on some parts of the application
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ShapeCore {
     /// The control points of the shape
     pub points: Vec<Point>,
@@ -205,6 +424,156 @@ pub struct ShapeCore {
     pub fill_color: RGBA,
     /// The shape type, used for identification on some parts of the app
     pub shape_type: Shape,
+    /// How the shape is mixed with what is below it. Drawings saved before blend modes existed
+    /// dont have this field, so it defaults to normal
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+    /// The width of the border in pixels. Old drawings dont have it, so it defaults to 1
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: u32,
+    /// How the ends of the border are drawn. Only used by wide borders
+    #[serde(default)]
+    pub line_cap: LineCap,
+    /// How the corners of the border are drawn. Only used by wide borders
+    #[serde(default)]
+    pub line_join: LineJoin,
+    /// Miter corners longer than this (relative to the border width) are drawn as a bevel
+    #[serde(default = "default_miter_limit")]
+    pub miter_limit: f32,
+    /// Lengths in pixels of the dashes and the gaps between them: dash, gap, dash, gap... Empty
+    /// (or only zeros) for a solid border. Like svg, a pattern with an odd amount of lengths is
+    /// repeated, so [5] is a dash of 5 and a gap of 5
+    #[serde(default)]
+    pub dash_pattern: Vec<u32>,
+    /// How far into the dash pattern the border starts, in pixels
+    #[serde(default)]
+    pub dash_offset: u32,
+    /// If true the edges are smoothed by drawing the pixels they partially cover with that
+    /// fraction of the alpha. If false (the default) every pixel is either drawn or not, with the
+    /// integer algorithms of the course
+    #[serde(default)]
+    pub antialias: bool,
+    /// Which parts of a polygon that crosses itself are inside. Only used by polygons
+    #[serde(default)]
+    pub fill_rule: FillRule,
+    /// If true the points are joined with a curve that goes through them instead of lines. Only
+    /// used by freehand paths
+    #[serde(default)]
+    pub smooth: bool,
+    /// Angle in degrees the shape is rotated around its center. Positive angles go clockwise,
+    /// since y grows down on the screen. Only used by ellipses
+    #[serde(default)]
+    pub rotation: f32,
+    /// Which part of the ellipse is drawn. Only used by ellipses
+    #[serde(default)]
+    pub arc: ArcKind,
+    /// Where the arc starts and ends, in degrees. Like the rotation they go clockwise, 0 is the
+    /// right end of the ellipse before rotating it. The angles are the ones the points would have
+    /// if the ellipse was stretched into a circle. Not used by the full ellipse
+    #[serde(default)]
+    pub start_angle: f32,
+    #[serde(default = "default_end_angle")]
+    pub end_angle: f32,
+    /// Radius in pixels of each corner: top left, top right, bottom right and bottom left. A
+    /// radius bigger than half the shortest side is drawn as half of it. Only used by rectangles
+    #[serde(default)]
+    pub corner_radii: [u32; 4],
+    /// What the text says, it can have several lines. Only used by texts
+    #[serde(default)]
+    pub text: String,
+    /// Size of the letters in pixels, the height from the lowest to the highest point a letter of
+    /// the font can reach. Only used by texts
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    /// The png drawn between both points. Only used by images, the other shapes dont have it and
+    /// dont save it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageSource>,
+    /// How the png is scaled to the size of the box. Only used by images
+    #[serde(default)]
+    pub sampling: ImageSampling,
+    /// Degree of the pieces of the curve, 3 is a cubic B-spline. A curve with less control points
+    /// than degree + 1 uses the degree those points allow. Only used by b-splines
+    #[serde(default = "default_degree")]
+    pub degree: u32,
+    /// How the knots are spread. Only used by b-splines
+    #[serde(default)]
+    pub knot_vector: KnotVector,
+    /// The knots given one by one, there must be one for each control point plus degree + 1.
+    /// Empty to use the ones of knot_vector. Only used by b-splines
+    #[serde(default)]
+    pub knots: Vec<f32>,
+    /// The weight of each control point, a point with more weight pulls the curve closer to it.
+    /// With weights a bezier can draw exact circles and other conics. Empty when they are all 1.
+    /// Only used by bezier curves
+    #[serde(default)]
+    pub weights: Vec<f32>,
+    /// How the curves are joined at each point of a path that is not a handle, the first and last
+    /// ones included. Empty when they are all symmetric. Only used by paths
+    #[serde(default)]
+    pub joints: Vec<JointKind>,
+}
+
+/// the width of the border when its not specified
+fn default_stroke_width() -> u32 {
+    1
+}
+
+/// the end of the arc when its not specified, three quarters of the ellipse
+fn default_end_angle() -> f32 {
+    270.0
+}
+
+/// the degree of b-splines when its not specified, cubic
+fn default_degree() -> u32 {
+    3
+}
+
+/// the size of the letters when its not specified
+fn default_font_size() -> f32 {
+    24.0
+}
+
+/// the miter limit when its not specified. Same as svg, cuts corners sharper than ~29 degrees
+fn default_miter_limit() -> f32 {
+    4.0
+}
+
+// an empty core, useful to build cores without listing every field:
+// ShapeCore { points, shape_type, ..Default::default() }
+impl Default for ShapeCore {
+    fn default() -> Self {
+        ShapeCore {
+            points: vec![],
+            color: RGBA::default(),
+            fill_color: RGBA::default(),
+            shape_type: Shape::NoSelect,
+            blend_mode: BlendMode::Normal,
+            stroke_width: default_stroke_width(),
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: default_miter_limit(),
+            dash_pattern: vec![],
+            dash_offset: 0,
+            antialias: false,
+            fill_rule: FillRule::NonZero,
+            smooth: false,
+            rotation: 0.0,
+            arc: ArcKind::Full,
+            start_angle: 0.0,
+            end_angle: default_end_angle(),
+            corner_radii: [0; 4],
+            text: String::new(),
+            font_size: default_font_size(),
+            image: None,
+            sampling: ImageSampling::Bilinear,
+            degree: default_degree(),
+            knot_vector: KnotVector::Clamped,
+            knots: vec![],
+            weights: vec![],
+            joints: vec![],
+        }
+    }
 }
 
 // Custom methods of the shape core
@@ -218,6 +587,151 @@ impl ShapeCore {
         }
     }
 
+    /// checks that the shape core can be drawn. Every shape indexes its control points directly, so
+    /// a core with less points than needed (for example a hand edited file) would crash the app
+    pub fn validate(&self) -> Result<(), String> {
+        let n = self.points.len();
+        let valid = match self.shape_type {
+            // NoSelect is drawn as a line (see new_shape_from_core)
+            Shape::NoSelect | Shape::Line | Shape::Ellipse | Shape::Rectangle | Shape::Image => {
+                n == 2
+            }
+            // the top left corner of the first line
+            Shape::Text => n == 1,
+            Shape::Triangle => n == 3,
+            Shape::Bezier | Shape::BSpline | Shape::CatmullRom => n >= 2,
+            // a point, two handles and the next point for each curve
+            Shape::Path => n >= 4 && (n - 1).is_multiple_of(3),
+            Shape::Polygon | Shape::Polyline | Shape::Freehand => n >= 2,
+            // the first and last pixel of each span
+            Shape::Fill => n >= 2 && n.is_multiple_of(2),
+            // the eraser is a tool, there are no erasers on a drawing
+            Shape::Eraser | Shape::RegularPolygon => false,
+        };
+
+        if !valid {
+            return Err(format!("{} with {} control points", self.shape_type, n));
+        }
+
+        if self.stroke_width == 0 {
+            return Err(format!("{} with a border of width 0", self.shape_type));
+        }
+
+        // a miter is never shorter than the border width
+        if self.miter_limit.is_nan() || self.miter_limit < 1.0 {
+            return Err(format!(
+                "{} with a miter limit of {}, it must be at least 1",
+                self.shape_type, self.miter_limit
+            ));
+        }
+
+        if self.degree == 0 {
+            return Err(format!("{} of degree 0", self.shape_type));
+        }
+
+        if !self.knots.is_empty() {
+            let knots = &self.knots;
+            let degree = self.spline_degree();
+            let expected = n + degree + 1;
+            if knots.len() != expected {
+                return Err(format!(
+                    "{} with {} knots, it needs {expected}",
+                    self.shape_type,
+                    knots.len()
+                ));
+            }
+            let sorted = knots.windows(2).all(|w| w[0] <= w[1]);
+            if knots.iter().any(|k| !k.is_finite()) || !sorted || knots[degree] >= knots[n] {
+                return Err(format!(
+                    "{} with knots that are not increasing",
+                    self.shape_type
+                ));
+            }
+        }
+
+        if !self.weights.is_empty() {
+            if self.weights.len() != n {
+                return Err(format!(
+                    "{} with {} weights for {n} control points",
+                    self.shape_type,
+                    self.weights.len()
+                ));
+            }
+            if self.weights.iter().any(|w| !w.is_finite() || *w <= 0.0) {
+                return Err(format!(
+                    "{} with a weight that is not positive",
+                    self.shape_type
+                ));
+            }
+        }
+
+        if !self.joints.is_empty() && self.joints.len() != n.div_ceil(3) {
+            return Err(format!(
+                "{} with {} joints for {} points",
+                self.shape_type,
+                self.joints.len(),
+                n.div_ceil(3)
+            ));
+        }
+
+        if self.shape_type == Shape::Image && self.image.is_none() {
+            return Err(format!("{} without a png", self.shape_type));
+        }
+
+        if !self.font_size.is_finite() || self.font_size <= 0.0 || self.font_size > MAX_FONT_SIZE {
+            return Err(format!(
+                "{} with a font size of {}",
+                self.shape_type, self.font_size
+            ));
+        }
+
+        let angles = [self.rotation, self.start_angle, self.end_angle];
+        if angles.iter().any(|angle| !angle.is_finite()) {
+            return Err(format!(
+                "{} with an angle that is not a number",
+                self.shape_type
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// the degree of the pieces of a b-spline. Its never more than the control points allow
+    pub fn spline_degree(&self) -> usize {
+        (self.degree as usize)
+            .min(self.points.len().saturating_sub(1))
+            .max(1)
+    }
+
+    /// the knots of a b-spline, the ones given one by one or the ones of the knot vector
+    pub fn spline_knots(&self) -> Vec<f32> {
+        if self.knots.is_empty() {
+            self.knot_vector
+                .knots(self.points.len(), self.spline_degree())
+        } else {
+            self.knots.clone()
+        }
+    }
+
+    /// the weight of each control point, 1 for all of them when they are not given
+    pub fn point_weights(&self) -> Vec<f32> {
+        if self.weights.len() == self.points.len() {
+            self.weights.clone()
+        } else {
+            vec![1.0; self.points.len()]
+        }
+    }
+
+    /// the joint of each point of a path that is not a handle, symmetric when they are not given
+    pub fn path_joints(&self) -> Vec<JointKind> {
+        let anchors = self.points.len().div_ceil(3);
+        if self.joints.len() == anchors {
+            self.joints.clone()
+        } else {
+            vec![JointKind::default(); anchors]
+        }
+    }
+
     /// Creates a shape core by copying the actual one but changing its control points
     pub fn copy_with_points(&self, points: Vec<Point>) -> ShapeCore {
         ShapeCore {
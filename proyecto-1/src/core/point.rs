@@ -1,4 +1,3 @@
-use egui::Options;
 // this "serde" is a library for serialization and deserialization into JSON
 use serde::{Deserialize, Serialize};
 use std::{
@@ -13,7 +12,7 @@ use std::{
 
 /// This is a rust doc comment. It has "///" instead of "//".
 /// Its used by third party tools to generate documentation
-
+///
 /// This Point struct holds x and y (a point).
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Point(pub i32, pub i32);
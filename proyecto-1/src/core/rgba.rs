@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use std::ops::{Add, Deref, Index};
+use std::{
+    fmt,
+    ops::{Add, Deref, Index},
+};
 
 /// object to hold an RGBA color
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
@@ -7,9 +10,9 @@ pub struct RGBA([u8; 4]);
 
 // public custom functions
 impl RGBA {
-    /// creates a new RGBA color
-    pub fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
-        [r, g, b, a].into()
+    /// creates a new RGBA color. Its a "const fn" so colors can be constants
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        RGBA([r, g, b, a])
     }
 
     /// checks if the current color is transparent
@@ -17,6 +20,13 @@ impl RGBA {
     pub fn is_transparent(&self) -> bool {
         self.0[3] == 0
     }
+
+    /// returns the same color with its alpha multiplied by the coverage (from 0 to 1). Used by
+    /// anti-aliasing to draw the pixels that a shape only covers partially
+    pub fn with_coverage(&self, coverage: f32) -> RGBA {
+        let alpha = (self.0[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+        RGBA([self.0[0], self.0[1], self.0[2], alpha])
+    }
 }
 
 // implementation of the default function. Returns the color BLACK with 0 alpha
@@ -27,6 +37,13 @@ impl Default for RGBA {
     }
 }
 
+// displays the color as "r,g,b,a". Used when printing colors on the command line
+impl fmt::Display for RGBA {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{}", self[0], self[1], self[2], self[3])
+    }
+}
+
 // cast to move [u8;4] to RGBA
 impl From<[u8; 4]> for RGBA {
     fn from(rgba: [u8; 4]) -> Self {
@@ -66,15 +83,38 @@ impl Index<usize> for RGBA {
 impl Add for RGBA {
     type Output = RGBA;
 
-    // implements the addition taking in account ALPHA. The caller color alpha is used to
-    // interpolate between the two colors. This way we can do color1 + color2 and get the
-    // interpolation depending on the alpha of color1
+    // implements the "source over" operation (Porter-Duff). color1 + color2 is color1 painted over
+    // color2, taking in account the alpha of BOTH colors. With alphas as fractions (a / 255):
+    //
+    // alpha = src_alpha + dst_alpha * (1 - src_alpha)
+    // color = (src * src_alpha + dst * dst_alpha * (1 - src_alpha)) / alpha
+    //
+    // the colors multiplied by their alpha are the "premultiplied" values. We multiply everything
+    // by 255 * 255 to do the whole operation with integers, and round when dividing instead of
+    // truncating, so the result doesnt get darker each time we blend
     fn add(self, other: RGBA) -> RGBA {
-        let alpha = self[3] as f32 / 255.0;
-        let new_r = (self[0] as f32 * alpha + other[0] as f32 * (1.0 - alpha)) as u8;
-        let new_g = (self[1] as f32 * alpha + other[1] as f32 * (1.0 - alpha)) as u8;
-        let new_b = (self[2] as f32 * alpha + other[2] as f32 * (1.0 - alpha)) as u8;
+        let src_alpha = self[3] as u32;
+        let dst_alpha = other[3] as u32;
+
+        // alpha * 255, goes from 0 to 255 * 255
+        let alpha = src_alpha * 255 + dst_alpha * (255 - src_alpha);
+        if alpha == 0 {
+            return RGBA::default();
+        }
+
+        let blend = |src: u8, dst: u8| {
+            // premultiplied colors * 255 * 255
+            let premultiplied =
+                src as u32 * src_alpha * 255 + dst as u32 * dst_alpha * (255 - src_alpha);
+            ((premultiplied + alpha / 2) / alpha) as u8
+        };
 
-        [new_r, new_g, new_b, 255].into()
+        [
+            blend(self[0], other[0]),
+            blend(self[1], other[1]),
+            blend(self[2], other[2]),
+            ((alpha + 127) / 255) as u8,
+        ]
+        .into()
     }
 }
@@ -1,11 +1,12 @@
 /// draw state is used to update everything that should be taken in account during a file save or
 /// an undo/redo action
-use std::{collections::VecDeque, fs, mem::discriminant, path::PathBuf};
+use std::{collections::VecDeque, fs, io, mem::discriminant, path::PathBuf};
 
 // serde is a package that allows us to serialize and deserialize rust objects into json format
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    canvas::Canvas,
     core::{ShapeCore, ShapeImpl, UpdateOp, RGBA},
     primitives::new_shape_from_core,
 };
@@ -23,6 +24,7 @@ const HISTORY_SIZE_LIMIT: usize = 100;
 /// where the app is currently at. If we move "i" backwards we use the "prev" value to update the
 /// object. If we move "i" forward we use the nxt value to update the object:
 ///
+/// ```text
 ///                i
 ///                |
 ///                |
@@ -38,23 +40,28 @@ const HISTORY_SIZE_LIMIT: usize = 100;
 ///                             |
 ///                             v
 /// [prev,nxt],[prev,"NXT"],[prev,nxt]
+/// ```
+// the shape cores are boxed, they are much bigger than the other records
 #[derive(Clone)]
 enum RecordType {
     /// the shape was moved to back or to front (z index). We store the original and previous index
     IndexChange(usize, usize),
     /// the shape changed its location or any set of control points. we save the previous and post
     /// state
-    ShapeChange(usize, UpdateOp, ShapeCore, ShapeCore),
+    ShapeChange(usize, UpdateOp, Box<ShapeCore>, Box<ShapeCore>),
     /// the shape subdivided. we store the original and their results
-    Subdivision(usize, ShapeCore, (ShapeCore, ShapeCore)),
+    Subdivision(usize, Box<ShapeCore>, Box<(ShapeCore, ShapeCore)>),
     /// the given shape was removed. We store its previous state
-    Deletion(usize, ShapeCore),
+    Deletion(usize, Box<ShapeCore>),
     /// we create a shape, we store the created shape
-    Creation(ShapeCore),
+    Creation(Box<ShapeCore>),
     /// background color change. We store the previous and next color
     BackgroundColor(RGBA, RGBA),
     /// CLEAR, we store all shapes since a CLEAR action removes everything
     Clear(Vec<ShapeCore>),
+    /// several actions done as one, like everything erased on a single drag. We store the records
+    /// of each action in the order they happened (see begin_group)
+    Group(Vec<RecordType>),
 }
 
 /// Serialized state. We use it with serde to store the shapes in json format
@@ -80,6 +87,15 @@ pub struct DrawState {
     history: VecDeque<RecordType>,
     /// the index that shows WHERE are we when we perform an undo or redo
     history_idx: usize,
+    /// records of the actions done since begin_group was called, None if there is no group
+    group: Option<Vec<RecordType>>,
+}
+
+// an empty draw state is the default one
+impl Default for DrawState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // the draw state implementation
@@ -91,17 +107,18 @@ impl DrawState {
             history: VecDeque::new(),
             history_idx: 0,
             background_color: RGBA::default(),
+            group: None,
         }
     }
 
     /// returns background color
     pub fn get_background_color(&self) -> RGBA {
-        return self.background_color;
+        self.background_color
     }
 
     /// returns a given object reference
-    pub fn get_object(&self, idx: usize) -> &Box<dyn ShapeImpl> {
-        &self.objects[idx]
+    pub fn get_object(&self, idx: usize) -> &dyn ShapeImpl {
+        self.objects[idx].as_ref()
     }
 
     /// returns a reference to the array of objects
@@ -119,6 +136,12 @@ impl DrawState {
     /// a shape we only store the initial and final location, if a user changes the shape color we
     /// only store the initial and last color
     fn push_history(&mut self, record: &RecordType) {
+        // inside a group every action is kept, they are pushed together by end_group
+        if let Some(group) = self.group.as_mut() {
+            group.push(record.clone());
+            return;
+        }
+
         self.history.truncate(self.history_idx);
 
         // There's an issue since we update objects on real time.
@@ -137,7 +160,28 @@ impl DrawState {
                 // change_last is of type UpdateOp (see src/core/mod.rs)
                 // The comparison operation of UpdateOp takes in account its arguments, thats why
                 // we compare using discriminant, this uses the enum type but not the arguments
-                if discriminant(change_last) == discriminant(change_cur) && idx_last == idx_this {
+                // each step of the degree of a bezier and each point or knot added to a curve is
+                // undone on its own, they are not a drag
+                let is_step = matches!(
+                    change_cur,
+                    UpdateOp::DegreeElevate
+                        | UpdateOp::DegreeReduce
+                        | UpdateOp::InsertControlPoint(_)
+                        | UpdateOp::InsertKnot
+                );
+                // moving a point or changing its weight or joint, and then doing the same with
+                // another point of the shape, are different steps too
+                let is_other_point = match (change_last, change_cur) {
+                    (UpdateOp::ControlPoint(a, _), UpdateOp::ControlPoint(b, _))
+                    | (UpdateOp::ChangeWeight(a, _), UpdateOp::ChangeWeight(b, _))
+                    | (UpdateOp::ChangeJoint(a, _), UpdateOp::ChangeJoint(b, _)) => a != b,
+                    _ => false,
+                };
+                if discriminant(change_last) == discriminant(change_cur)
+                    && idx_last == idx_this
+                    && !is_step
+                    && !is_other_point
+                {
                     // only the last is updated with the result record
                     let i = self.history.len() - 1;
                     self.history[i] = RecordType::ShapeChange(
@@ -175,6 +219,8 @@ impl DrawState {
     /// undo operations moves the index to the previous operation performed backwards. Updates the objects
     /// given the information of that event
     pub fn undo(&mut self) {
+        // a group left open would swallow the records of everything done after the undo
+        self.end_group();
         if self.history_idx > 0 {
             self.history_idx -= 1;
             // we get the record on that position of the array if its available
@@ -182,45 +228,56 @@ impl DrawState {
 
             // we clone the record to use it since we will clone its content anyway
             if let Some(record) = record_opt.cloned() {
-                match record {
-                    // for index change "undo" we remove the place where the shape is now
-                    // then we put that shape where it was before
-                    RecordType::IndexChange(src, dst) => {
-                        let shape = self.objects.remove(dst);
-                        self.objects.insert(src, shape);
-                    }
-                    // for shape change "undo" we just take its previous form update the object
-                    // that exists on that index
-                    RecordType::ShapeChange(idx, _, prev, _) => {
-                        self.objects[idx] = new_shape_from_core(prev);
-                    }
-                    // for subdivision we remove one of the objects generated and change the other
-                    // for the original shape
-                    RecordType::Subdivision(idx, init, _) => {
-                        self.objects[idx] = new_shape_from_core(init);
-                        self.objects.pop();
-                    }
-                    // for deletion we push the shape we deleted in its previous location
-                    RecordType::Deletion(idx, prev) => {
-                        self.objects.insert(idx, new_shape_from_core(prev));
-                    }
-                    // for clear we recreate the full set of objects
-                    RecordType::Clear(history) => {
-                        self.objects = history
-                            .iter()
-                            .map(|core| new_shape_from_core(core.clone()))
-                            .collect();
-                    }
-                    // for creation of a shape we remove the last shape. Its guaranteed its the
-                    // last one since creation allways appends to the end, and the other operations
-                    // always return to the inmediate previous state
-                    RecordType::Creation(_) => {
-                        self.objects.pop();
-                    }
-                    // for background color we just go back to the previous color
-                    RecordType::BackgroundColor(prev, _) => {
-                        self.background_color = prev;
-                    }
+                self.undo_record(record);
+            }
+        }
+    }
+
+    /// updates the objects to how they were before the action of the record
+    fn undo_record(&mut self, record: RecordType) {
+        match record {
+            // for index change "undo" we remove the place where the shape is now
+            // then we put that shape where it was before
+            RecordType::IndexChange(src, dst) => {
+                let shape = self.objects.remove(dst);
+                self.objects.insert(src, shape);
+            }
+            // for shape change "undo" we just take its previous form update the object
+            // that exists on that index
+            RecordType::ShapeChange(idx, _, prev, _) => {
+                self.objects[idx] = new_shape_from_core(*prev);
+            }
+            // for subdivision we remove one of the objects generated and change the other
+            // for the original shape
+            RecordType::Subdivision(idx, init, _) => {
+                self.objects[idx] = new_shape_from_core(*init);
+                self.objects.pop();
+            }
+            // for deletion we push the shape we deleted in its previous location
+            RecordType::Deletion(idx, prev) => {
+                self.objects.insert(idx, new_shape_from_core(*prev));
+            }
+            // for clear we recreate the full set of objects
+            RecordType::Clear(history) => {
+                self.objects = history
+                    .iter()
+                    .map(|core| new_shape_from_core(core.clone()))
+                    .collect();
+            }
+            // for creation of a shape we remove the last shape. Its guaranteed its the
+            // last one since creation allways appends to the end, and the other operations
+            // always return to the inmediate previous state
+            RecordType::Creation(_) => {
+                self.objects.pop();
+            }
+            // for background color we just go back to the previous color
+            RecordType::BackgroundColor(prev, _) => {
+                self.background_color = prev;
+            }
+            // for a group we undo each action, from the last one to the first one
+            RecordType::Group(records) => {
+                for record in records.into_iter().rev() {
+                    self.undo_record(record);
                 }
             }
         }
@@ -229,47 +286,81 @@ impl DrawState {
     /// redo operation moves the index to the previous operation performed forward. Updates the objects
     /// given the information of that event
     pub fn redo(&mut self) {
+        self.end_group();
         if self.history_idx < self.history.len() {
             let record = self.history[self.history_idx].clone();
-            match record {
-                // for index change we move the shape to its previous index
-                RecordType::IndexChange(src, dst) => {
-                    let shape = self.objects.remove(src);
-                    self.objects.insert(dst, shape);
-                }
-                // for shape change we change the idx shape to its post state
-                RecordType::ShapeChange(idx, _, _, post) => {
-                    self.objects[idx] = new_shape_from_core(post);
-                }
-                // for subdivision we recreate the two shapes generated. Update the current one and
-                // push the other two. Its like a modifycation and creation together
-                RecordType::Subdivision(idx, _, (core1, core2)) => {
-                    self.objects[idx] = new_shape_from_core(core1);
-                    self.objects.push(new_shape_from_core(core2));
-                }
-                // we just repeat what we did before. Clear all the objects
-                RecordType::Clear(_) => {
-                    self.objects.clear();
-                }
-                // deletion redo is repeating the previous action
-                RecordType::Deletion(idx, _) => {
-                    self.objects.remove(idx);
-                }
-                //creation is just repeating the action
-                RecordType::Creation(core) => {
-                    self.objects.push(new_shape_from_core(core));
-                }
-                // background color change just changes the background color
-                RecordType::BackgroundColor(_, nxt) => {
-                    self.background_color = nxt;
+            self.redo_record(record);
+            self.history_idx += 1;
+        }
+    }
+
+    /// repeats the action of the record
+    fn redo_record(&mut self, record: RecordType) {
+        match record {
+            // for index change we move the shape to its previous index
+            RecordType::IndexChange(src, dst) => {
+                let shape = self.objects.remove(src);
+                self.objects.insert(dst, shape);
+            }
+            // for shape change we change the idx shape to its post state
+            RecordType::ShapeChange(idx, _, _, post) => {
+                self.objects[idx] = new_shape_from_core(*post);
+            }
+            // for subdivision we recreate the two shapes generated. Update the current one and
+            // push the other two. Its like a modifycation and creation together
+            RecordType::Subdivision(idx, _, cores) => {
+                let (core1, core2) = *cores;
+                self.objects[idx] = new_shape_from_core(core1);
+                self.objects.push(new_shape_from_core(core2));
+            }
+            // we just repeat what we did before. Clear all the objects
+            RecordType::Clear(_) => {
+                self.objects.clear();
+            }
+            // deletion redo is repeating the previous action
+            RecordType::Deletion(idx, _) => {
+                self.objects.remove(idx);
+            }
+            //creation is just repeating the action
+            RecordType::Creation(core) => {
+                self.objects.push(new_shape_from_core(*core));
+            }
+            // background color change just changes the background color
+            RecordType::BackgroundColor(_, nxt) => {
+                self.background_color = nxt;
+            }
+            // for a group we repeat each action in the same order
+            RecordType::Group(records) => {
+                for record in records {
+                    self.redo_record(record);
                 }
             }
-            self.history_idx += 1;
+        }
+    }
+
+    /// starts a group of actions. Until end_group is called every action is stored on the group
+    /// instead of the history, so a single undo reverts all of them. Used by the eraser: everything
+    /// erased while dragging is undone at once
+    pub fn begin_group(&mut self) {
+        if self.group.is_none() {
+            self.group = Some(vec![]);
+        }
+    }
+
+    /// ends the group started with begin_group and pushes it to the history as one action. A group
+    /// without actions is not pushed. Undo, redo, clear and loading a file end the open group
+    /// first, so the group is always made of actions done one after the other
+    pub fn end_group(&mut self) {
+        if let Some(records) = self.group.take() {
+            if !records.is_empty() {
+                self.push_history(&RecordType::Group(records));
+            }
         }
     }
 
     /// clear function. Clears the objects and pushes to history
     pub fn clear(&mut self) {
+        self.end_group();
         self.push_history(&RecordType::Clear(
             self.objects.iter().map(|obj| obj.get_core()).collect(),
         ));
@@ -284,7 +375,7 @@ impl DrawState {
 
     /// Adds a shape and pushes the event to history
     pub fn add_shape(&mut self, shape: Box<dyn ShapeImpl>) {
-        self.push_history(&RecordType::Creation(shape.get_core()));
+        self.push_history(&RecordType::Creation(Box::new(shape.get_core())));
         self.objects.push(shape);
     }
 
@@ -292,11 +383,71 @@ impl DrawState {
     pub fn delete_shape(&mut self, index: usize) {
         if index < self.objects.len() {
             let core = self.objects[index].get_core();
-            self.push_history(&RecordType::Deletion(index, core));
+            self.push_history(&RecordType::Deletion(index, Box::new(core)));
             self.objects.remove(index);
         }
     }
 
+    /// removes a shape that should never be seen again (like an empty text), without an undo step
+    /// that would bring it back as it is now. If it was the last shape added and it only changed
+    /// since then its creation is forgotten. If not, the deletion is joined with the last change of
+    /// the shape, so undo gives the shape back as it was before that change. Anything else is a
+    /// normal deletion
+    pub fn drop_shape(&mut self, index: usize) {
+        if index >= self.objects.len() {
+            return;
+        }
+        if self.group.is_some() {
+            self.delete_shape(index);
+            return;
+        }
+        if index + 1 == self.objects.len() && self.forget_last_shape() {
+            return;
+        }
+
+        self.history.truncate(self.history_idx);
+        match self.history.back() {
+            Some(RecordType::ShapeChange(idx, ..)) if *idx == index => {
+                let change = self.history.pop_back().unwrap();
+                self.history_idx = self.history.len();
+                let core = self.objects.remove(index).get_core();
+                self.push_history(&RecordType::Group(vec![
+                    change,
+                    RecordType::Deletion(index, Box::new(core)),
+                ]));
+            }
+            _ => self.delete_shape(index),
+        }
+    }
+
+    /// removes the last shape together with its creation and its changes, like if it was never
+    /// added. Its only possible when every record since its creation is a change of that shape.
+    /// Returns if it was removed
+    fn forget_last_shape(&mut self) -> bool {
+        let index = self.objects.len() - 1;
+
+        // the last creation is the one of the last shape, as long as nothing else was done since
+        let mut creation = None;
+        for (i, record) in self.history.iter().enumerate().take(self.history_idx).rev() {
+            match record {
+                RecordType::ShapeChange(idx, ..) if *idx == index => {}
+                RecordType::Creation(_) => {
+                    creation = Some(i);
+                    break;
+                }
+                _ => return false,
+            }
+        }
+        let Some(creation) = creation else {
+            return false;
+        };
+
+        self.history.truncate(creation);
+        self.history_idx = creation;
+        self.objects.pop();
+        true
+    }
+
     /// moves a shape in the vector from index to index. Adds the event
     pub fn reorder_shape(&mut self, from: usize, to: usize) {
         if from < self.objects.len() && to < self.objects.len() {
@@ -315,33 +466,115 @@ impl DrawState {
 
             // only those shapes that implement subdivide can reach this
             if let Some((core1, core2)) = res.as_ref() {
-                shape.update(&UpdateOp::RewritePoints(core1.points.clone()));
+                // the shape is replaced like on redo, so whatever else the subdivision changed
+                // (like the weights of a bezier) is kept too
+                *shape = new_shape_from_core(core1.clone());
                 self.objects.push(new_shape_from_core(core2.clone()));
 
                 self.push_history(&RecordType::Subdivision(
                     shape_idx,
-                    prev_core,
-                    (core1.clone(), core2.clone()),
+                    Box::new(prev_core),
+                    Box::new((core1.clone(), core2.clone())),
                 ));
             }
         }
     }
 
-    /// this is the only function that mutates a shape, and this allows us to record shape changes
-    pub fn update_shape(&mut self, shape_idx: usize, op: UpdateOp) {
-        if let Some(shape) = self.objects.get_mut(shape_idx) {
-            let prev_core = shape.get_core().clone();
-            shape.update(&op);
-            let new_core = shape.get_core().clone();
+    /// Removes the part of a line or bezier curve between t0 and t1. The shape is subdivided at
+    /// both ends of the part, the first piece stays where the shape was and the last one goes to
+    /// the front, like with subdivide_shape. Its done with several actions, the eraser puts them on
+    /// a group (see begin_group). Shapes that cant be subdivided and empty parts change nothing
+    pub fn cut_shape(&mut self, index: usize, t0: f32, t1: f32) {
+        if t1 <= t0
+            || self
+                .objects
+                .get(index)
+                .and_then(|s| s.subdivide())
+                .is_none()
+        {
+            return;
+        }
 
-            self.push_history(&RecordType::ShapeChange(shape_idx, op, prev_core, new_core));
+        match (t0 > 0.0, t1 < 1.0) {
+            // the part is in the middle, we end with the piece before it, the part and the piece
+            // after it. The part was subdivided last so its the one before the last
+            (true, true) => {
+                self.update_shape(index, UpdateOp::UpdateSubdivide(t0));
+                self.subdivide_shape(index);
+                let rest = self.get_objects().len() - 1;
+                let t = (t1 - t0) / (1.0 - t0);
+                self.update_shape(rest, UpdateOp::UpdateSubdivide(t));
+                self.subdivide_shape(rest);
+                self.delete_shape(rest);
+            }
+            // the part is at the start
+            (false, true) => {
+                self.update_shape(index, UpdateOp::UpdateSubdivide(t1));
+                self.subdivide_shape(index);
+                self.delete_shape(index);
+            }
+            // the part is at the end
+            (true, false) => {
+                self.update_shape(index, UpdateOp::UpdateSubdivide(t0));
+                self.subdivide_shape(index);
+                self.delete_shape(self.get_objects().len() - 1);
+            }
+            // the whole shape is under the eraser
+            (false, false) => self.delete_shape(index),
         }
     }
 
-    /// loads the state from file and clears the modification history
-    pub fn load_from_file(&mut self, file_path: PathBuf) {
-        let state_str = fs::read_to_string(&file_path).unwrap();
-        let loaded_state: SerializedState = serde_json::from_str(&state_str).unwrap();
+    /// this is the only function that mutates a shape, and this allows us to record shape changes.
+    /// Changes that leave the core as it was (like moving where a bezier is subdivided) are not
+    /// recorded, undoing them would show nothing. Returns if the core changed, an op that cant be
+    /// done (like reducing a curve that cant be reduced) leaves it as it was
+    pub fn update_shape(&mut self, shape_idx: usize, op: UpdateOp) -> bool {
+        let Some(shape) = self.objects.get_mut(shape_idx) else {
+            return false;
+        };
+        let prev_core = shape.get_core().clone();
+        shape.update(&op);
+        let new_core = shape.get_core().clone();
+        if prev_core == new_core {
+            return false;
+        }
+
+        self.push_history(&RecordType::ShapeChange(
+            shape_idx,
+            op,
+            Box::new(prev_core),
+            Box::new(new_core),
+        ));
+        true
+    }
+
+    /// draws the background and every shape into the canvas. This is the drawing without any
+    /// selection or shape being created on top, used when rendering without the GUI
+    pub fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        canvas.clear(self.background_color);
+
+        for shape in self.objects.iter() {
+            shape.draw_blended(canvas);
+        }
+    }
+
+    /// loads the state from file and clears the modification history. If the file cant be read
+    /// or its not a drawing the current state is not modified
+    pub fn load_from_file(&mut self, file_path: PathBuf) -> io::Result<()> {
+        self.end_group();
+        let state_str = fs::read_to_string(&file_path)?;
+        // serde errors can be turned into io errors with "?"
+        let loaded_state: SerializedState = serde_json::from_str(&state_str)?;
+
+        // we check every shape before touching the current state
+        for (i, core) in loaded_state.objects.iter().enumerate() {
+            if let Err(msg) = core.validate() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("object {i} is not valid: {msg}"),
+                ));
+            }
+        }
 
         self.history.clear();
         self.objects.clear();
@@ -353,10 +586,11 @@ impl DrawState {
         }
 
         self.background_color = loaded_state.background_color;
+        Ok(())
     }
 
     /// saves the state to a file
-    pub fn save_to_file(&self, file_path: PathBuf) {
+    pub fn save_to_file(&self, file_path: PathBuf) -> io::Result<()> {
         let mut core_arr = vec![];
         for shape in self.get_objects().iter() {
             core_arr.push(shape.get_core());
@@ -368,7 +602,7 @@ impl DrawState {
             background_color: self.background_color,
         };
 
-        let state_str = serde_json::to_string_pretty(&saved_state).unwrap();
-        fs::write(file_path, state_str).unwrap();
+        let state_str = serde_json::to_string_pretty(&saved_state)?;
+        fs::write(file_path, state_str)
     }
 }
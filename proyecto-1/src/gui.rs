@@ -4,8 +4,16 @@ use pixels::{wgpu, PixelsContext};
 use winit::event_loop::EventLoopWindowTarget;
 use winit::window::Window;
 
-use crate::app_state::{AppState, GUIEvent};
-use crate::core::Shape;
+use crate::app_state::{AppState, EraserMode, GUIEvent};
+use paint_app::core::{
+    ArcKind, BlendMode, FillRule, ImageSampling, JointKind, KnotVector, LineCap, LineJoin, Shape,
+    MAX_FONT_SIZE,
+};
+
+/// the widest border that can be selected on the UI
+const MAX_STROKE_WIDTH: u32 = 50;
+/// the biggest miter limit that can be selected on the UI
+const MAX_MITER_LIMIT: f32 = 20.0;
 
 /// we have different panels that are drawn, this is a trait (interface) for all of them
 trait UiPanel {
@@ -41,7 +49,18 @@ impl UiPanel for TopControlsPanel {
 }
 
 /// panel to select shapes and modify them. Main one
-struct ShapePanel;
+struct ShapePanel {
+    /// if the next png opened is copied into the drawing or only referenced by its path
+    embed_image: bool,
+}
+
+impl Default for ShapePanel {
+    fn default() -> Self {
+        // embedded by default, a drawing that references a file breaks when its sent to someone
+        ShapePanel { embed_image: true }
+    }
+}
+
 impl UiPanel for ShapePanel {
     fn draw(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context, app_state: &mut AppState) {
         ui.horizontal(|ui| {
@@ -56,6 +75,17 @@ impl UiPanel for ShapePanel {
                         Shape::Triangle,
                         Shape::Rectangle,
                         Shape::Bezier,
+                        Shape::BSpline,
+                        Shape::CatmullRom,
+                        Shape::Path,
+                        Shape::Polygon,
+                        Shape::Polyline,
+                        Shape::Freehand,
+                        Shape::Fill,
+                        Shape::Eraser,
+                        Shape::RegularPolygon,
+                        Shape::Text,
+                        Shape::Image,
                     ];
                     for shape in shapes.iter() {
                         if ui
@@ -68,6 +98,268 @@ impl UiPanel for ShapePanel {
                 });
         });
 
+        if app_state.current == Shape::Eraser {
+            ui.horizontal(|ui| {
+                ui.label("Eraser:");
+                let mut mode = app_state.get_eraser_mode();
+                egui::ComboBox::from_id_source("eraser_mode")
+                    .selected_text(mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for m in EraserMode::ALL {
+                            ui.selectable_value(&mut mode, m, m.to_string());
+                        }
+                    });
+                if mode != app_state.get_eraser_mode() {
+                    app_state.gui_update(GUIEvent::EraserMode(mode));
+                }
+            });
+        }
+
+        if app_state.current == Shape::RegularPolygon {
+            let (mut sides, mut star, mut ratio) = app_state.get_regular_polygon();
+            ui.horizontal(|ui| {
+                ui.label("Sides:");
+                ui.add(egui::DragValue::new(&mut sides).clamp_range(3..=64));
+                ui.checkbox(&mut star, "Star");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Inner radius:");
+                ui.add_enabled(star, egui::Slider::new(&mut ratio, 0.1..=0.9));
+            });
+            if (sides, star, ratio) != app_state.get_regular_polygon() {
+                app_state.gui_update(GUIEvent::RegularPolygon(sides, star, ratio));
+            }
+        }
+
+        // shown while drawing new polygons and when one is selected, only polygons can cross
+        // themselves
+        let polygon_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Polygon);
+        if app_state.current == Shape::Polygon || polygon_selected {
+            let mut fill_rule = app_state.get_fill_rule();
+            ui.horizontal(|ui| {
+                ui.label("Fill rule:");
+                egui::ComboBox::from_id_source("fill_rule")
+                    .selected_text(fill_rule.to_string())
+                    .show_ui(ui, |ui| {
+                        for rule in FillRule::ALL {
+                            ui.selectable_value(&mut fill_rule, rule, rule.to_string());
+                        }
+                    });
+            });
+            if fill_rule != app_state.get_fill_rule() {
+                app_state.gui_update(GUIEvent::FillRule(fill_rule));
+            }
+        }
+
+        // shown while drawing new freehand paths and when one is selected, the other shapes are
+        // not smoothed
+        let freehand_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Freehand);
+        if app_state.current == Shape::Freehand || freehand_selected {
+            let mut smooth = app_state.get_smooth();
+            if ui.checkbox(&mut smooth, "Smooth").changed() {
+                app_state.gui_update(GUIEvent::Smooth(smooth));
+            }
+        }
+
+        // shown while using the bucket. How different a color can be from the clicked one, per
+        // channel
+        if app_state.current == Shape::Fill {
+            ui.horizontal(|ui| {
+                ui.label("Tolerance:");
+                let mut fill_tolerance = app_state.get_fill_tolerance();
+                if ui
+                    .add(egui::Slider::new(&mut fill_tolerance, 0..=255))
+                    .changed()
+                {
+                    app_state.gui_update(GUIEvent::FillTolerance(fill_tolerance));
+                }
+            });
+        }
+
+        // shown while drawing new ellipses and when one is selected, only ellipses are rotated and
+        // drawn partially
+        let ellipse_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Ellipse);
+        if app_state.current == Shape::Ellipse || ellipse_selected {
+            let (mut rotation, mut arc) = app_state.get_ellipse_style();
+            ui.horizontal(|ui| {
+                ui.label("Rotation:");
+                if ui
+                    .add(egui::Slider::new(&mut rotation, -180.0..=180.0).suffix("°"))
+                    .changed()
+                {
+                    app_state.gui_update(GUIEvent::Rotation(rotation));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Arc:");
+                egui::ComboBox::from_id_source("arc")
+                    .selected_text(arc.to_string())
+                    .show_ui(ui, |ui| {
+                        for kind in ArcKind::ALL {
+                            ui.selectable_value(&mut arc, kind, kind.to_string());
+                        }
+                    });
+            });
+            if arc != app_state.get_ellipse_style().1 {
+                app_state.gui_update(GUIEvent::Arc(arc));
+            }
+        }
+
+        // shown while drawing new rectangles and when one is selected, only rectangles have round
+        // corners. The slider changes all of them, and each one can be changed on its own below it
+        let rectangle_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Rectangle);
+        if app_state.current == Shape::Rectangle || rectangle_selected {
+            let mut radii = app_state.get_corner_radii();
+            ui.horizontal(|ui| {
+                ui.label("Radius:");
+                let mut radius = radii[0];
+                if ui
+                    .add(egui::Slider::new(&mut radius, 0..=100).suffix(" px"))
+                    .changed()
+                {
+                    radii = [radius; 4];
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Corners:");
+                for radius in radii.iter_mut() {
+                    ui.add(egui::DragValue::new(radius).clamp_range(0..=500));
+                }
+            })
+            .response
+            .on_hover_text("top left, top right, bottom right, bottom left");
+            if radii != app_state.get_corner_radii() {
+                app_state.gui_update(GUIEvent::CornerRadii(radii));
+            }
+        }
+
+        // shown while drawing new b-splines and when one is selected
+        let spline_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::BSpline);
+        if app_state.current == Shape::BSpline || spline_selected {
+            let (mut degree, mut knot_vector, custom) = app_state.get_spline_style();
+            ui.horizontal(|ui| {
+                ui.label("Degree:");
+                if ui
+                    .add(egui::DragValue::new(&mut degree).clamp_range(1..=7))
+                    .changed()
+                {
+                    app_state.gui_update(GUIEvent::SplineDegree(degree));
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Knots:");
+                // after inserting a knot they are not spread evenly anymore, choosing a kind
+                // spreads them again
+                let text = if custom {
+                    format!("{knot_vector} (inserted)")
+                } else {
+                    knot_vector.to_string()
+                };
+                let mut chosen = None;
+                egui::ComboBox::from_id_source("knot_vector")
+                    .selected_text(text)
+                    .show_ui(ui, |ui| {
+                        for k in KnotVector::ALL {
+                            if ui
+                                .selectable_value(&mut knot_vector, k, k.to_string())
+                                .clicked()
+                            {
+                                chosen = Some(k);
+                            }
+                        }
+                    });
+                if let Some(k) = chosen {
+                    app_state.gui_update(GUIEvent::KnotVector(k));
+                }
+            });
+        }
+
+        // shown while drawing new paths and when one is selected. The joint of a selected path is
+        // the one of the point clicked last (or the point of the handle clicked last)
+        let path_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Path);
+        if app_state.current == Shape::Path || path_selected {
+            let mut joint = app_state.get_joint();
+            ui.horizontal(|ui| {
+                ui.label("Joint:");
+                let mut chosen = None;
+                egui::ComboBox::from_id_source("path_joint")
+                    .selected_text(joint.to_string())
+                    .show_ui(ui, |ui| {
+                        for j in JointKind::ALL {
+                            if ui.selectable_value(&mut joint, j, j.to_string()).clicked() {
+                                chosen = Some(j);
+                            }
+                        }
+                    });
+                if let Some(j) = chosen {
+                    app_state.gui_update(GUIEvent::Joint(j));
+                }
+            });
+        }
+
+        // shown while writing new texts and when a text is selected, to edit what it says
+        let text_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Text);
+        if app_state.current == Shape::Text || text_selected {
+            let (mut text, mut size) = app_state.get_text_style();
+            ui.label("Text:");
+            if ui.text_edit_multiline(&mut text).changed() {
+                app_state.gui_update(GUIEvent::Text(text));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Font size:");
+                if ui
+                    .add(egui::Slider::new(&mut size, 6.0..=MAX_FONT_SIZE).logarithmic(true))
+                    .changed()
+                {
+                    app_state.gui_update(GUIEvent::FontSize(size));
+                }
+            });
+        }
+
+        // shown while drawing new images and when an image is selected, to change its png
+        let image_selected = app_state
+            .get_selected_shape()
+            .is_some_and(|shape| shape.get_core().shape_type == Shape::Image);
+        if app_state.current == Shape::Image || image_selected {
+            let (has_image, mut sampling) = app_state.get_image_style();
+            ui.horizontal(|ui| {
+                if ui.button("Open image...").clicked() {
+                    app_state.gui_update(GUIEvent::OpenImage(self.embed_image));
+                }
+                ui.checkbox(&mut self.embed_image, "Embed");
+            });
+            if !has_image {
+                ui.label("Open a png to draw it");
+            }
+            ui.horizontal(|ui| {
+                ui.label("Scaling:");
+                egui::ComboBox::from_id_source("image_sampling")
+                    .selected_text(sampling.to_string())
+                    .show_ui(ui, |ui| {
+                        for s in ImageSampling::ALL {
+                            ui.selectable_value(&mut sampling, s, s.to_string());
+                        }
+                    });
+            });
+            if sampling != app_state.get_image_style().1 {
+                app_state.gui_update(GUIEvent::ImageSampling(sampling));
+            }
+        }
+
         if ui.button("Clear Canvas").clicked() {
             app_state.gui_update(GUIEvent::Clear);
         }
@@ -76,7 +368,15 @@ impl UiPanel for ShapePanel {
 }
 
 /// panel for only color related stuf
-struct ColorPanel;
+#[derive(Default)]
+struct ColorPanel {
+    /// text of the dash pattern field, kept between frames so it can be edited even while its
+    /// not a valid pattern
+    dash_text: String,
+    /// the pattern the text was written for. When the pattern changes from somewhere else (like
+    /// selecting another shape) the text is written again
+    dash_shown: Vec<u32>,
+}
 impl UiPanel for ColorPanel {
     fn draw(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context, app_state: &mut AppState) {
         ui.heading("COLOR");
@@ -124,6 +424,117 @@ impl UiPanel for ColorPanel {
                     app_state.gui_update(GUIEvent::BackgroundColor(background.into()));
                 }
                 ui.end_row();
+
+                ui.label("Width");
+                let mut stroke_width = app_state.get_stroke_width();
+                if ui
+                    .add(egui::Slider::new(&mut stroke_width, 1..=MAX_STROKE_WIDTH).suffix(" px"))
+                    .changed()
+                {
+                    app_state.gui_update(GUIEvent::StrokeWidth(stroke_width));
+                }
+                ui.end_row();
+
+                let (mut cap, mut join, mut miter_limit) = app_state.get_line_style();
+                ui.label("Cap");
+                egui::ComboBox::from_id_source("line_cap")
+                    .selected_text(cap.to_string())
+                    .show_ui(ui, |ui| {
+                        for c in LineCap::ALL {
+                            ui.selectable_value(&mut cap, c, c.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Join");
+                egui::ComboBox::from_id_source("line_join")
+                    .selected_text(join.to_string())
+                    .show_ui(ui, |ui| {
+                        for j in LineJoin::ALL {
+                            ui.selectable_value(&mut join, j, j.to_string());
+                        }
+                    });
+                ui.end_row();
+
+                // the limit only matters for miter joins
+                ui.label("Miter limit");
+                let miter_changed = ui
+                    .add_enabled(
+                        join == LineJoin::Miter,
+                        egui::DragValue::new(&mut miter_limit)
+                            .clamp_range(1.0..=MAX_MITER_LIMIT)
+                            .speed(0.1),
+                    )
+                    .changed();
+                ui.end_row();
+
+                let (prev_cap, prev_join, _) = app_state.get_line_style();
+                if cap != prev_cap {
+                    app_state.gui_update(GUIEvent::LineCap(cap));
+                }
+                if join != prev_join {
+                    app_state.gui_update(GUIEvent::LineJoin(join));
+                }
+                if miter_changed {
+                    app_state.gui_update(GUIEvent::MiterLimit(miter_limit));
+                }
+
+                // lengths of dashes and gaps separated by spaces or commas, empty is a solid border
+                let (mut pattern, mut dash_offset) = app_state.get_dash_pattern();
+                if pattern != self.dash_shown {
+                    self.dash_text = format_dash_pattern(&pattern);
+                    self.dash_shown = pattern.clone();
+                }
+                ui.label("Dash");
+                let text_changed = ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.dash_text)
+                            .hint_text("solid")
+                            .desired_width(80.0),
+                    )
+                    .changed();
+                ui.end_row();
+                if text_changed {
+                    if let Some(parsed) = parse_dash_pattern(&self.dash_text) {
+                        self.dash_shown = parsed.clone();
+                        pattern = parsed;
+                    }
+                }
+
+                ui.label("Dash offset");
+                let offset_changed = ui
+                    .add(egui::DragValue::new(&mut dash_offset).suffix(" px"))
+                    .changed();
+                ui.end_row();
+
+                if text_changed || offset_changed {
+                    let (prev_pattern, prev_offset) = app_state.get_dash_pattern();
+                    if pattern != prev_pattern || dash_offset != prev_offset {
+                        app_state.gui_update(GUIEvent::DashPattern(pattern, dash_offset));
+                    }
+                }
+
+                // off is the pixel exact mode, with the integer algorithms of the course
+                ui.label("Anti-aliasing");
+                let mut antialias = app_state.get_antialias();
+                if ui.checkbox(&mut antialias, "").changed() {
+                    app_state.gui_update(GUIEvent::Antialias(antialias));
+                }
+                ui.end_row();
+
+                ui.label("Blend");
+                let mut blend_mode = app_state.get_blend_mode();
+                egui::ComboBox::from_id_source("blend_mode")
+                    .selected_text(blend_mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in BlendMode::ALL {
+                            ui.selectable_value(&mut blend_mode, mode, mode.to_string());
+                        }
+                    });
+                if blend_mode != app_state.get_blend_mode() {
+                    app_state.gui_update(GUIEvent::BlendMode(blend_mode));
+                }
+                ui.end_row();
             });
         ui.separator();
     }
@@ -181,17 +592,48 @@ struct BezierPanel;
 impl UiPanel for BezierPanel {
     fn draw(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, app_state: &mut AppState) {
         let selected = app_state.get_selected_shape();
-        let is_bezier_selected =
-            selected.is_some() && matches!(selected.unwrap().get_core().shape_type, Shape::Bezier);
+        let selected_type = selected.map(|shape| shape.get_core().shape_type);
+        let is_bezier_selected = selected_type == Some(Shape::Bezier);
+        // splines use the same settings, but a knot is inserted instead of subdividing them
+        let is_spline_selected = matches!(
+            selected_type,
+            Some(Shape::BSpline) | Some(Shape::CatmullRom)
+        );
 
         let bezier_header = egui::CollapsingHeader::new("Bezier Settings")
             .default_open(false)
             .show(ui, |ui| {
-                ui.add_enabled_ui(is_bezier_selected, |ui| {
-                    if ui.button("Degree Elevate").clicked() {
-                        app_state.gui_update(GUIEvent::DegreeElevate);
+                ui.add_enabled_ui(is_bezier_selected || is_spline_selected, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(is_bezier_selected, egui::Button::new("Degree Elevate"))
+                            .clicked()
+                        {
+                            app_state.gui_update(GUIEvent::DegreeElevate);
+                        }
+                        if ui
+                            .add_enabled(is_bezier_selected, egui::Button::new("Degree Reduce"))
+                            .clicked()
+                        {
+                            app_state.gui_update(GUIEvent::DegreeReduce);
+                        }
+                    });
+                    // most curves change a bit when their degree is reduced, and some cant be
+                    match app_state.get_degree_reduction() {
+                        Some(Ok(deviation)) => {
+                            ui.label(format!("Max deviation: {deviation:.2} px"));
+                        }
+                        Some(Err(err)) => {
+                            ui.colored_label(egui::Color32::LIGHT_RED, err);
+                        }
+                        None => {}
                     }
-                    if ui.button("Subdivide").clicked() {
+                    let subdivide = if is_spline_selected {
+                        "Insert Knot"
+                    } else {
+                        "Subdivide"
+                    };
+                    if ui.button(subdivide).clicked() {
                         app_state.gui_update(GUIEvent::Subdivide);
                     }
                     let mut subdivision_t = app_state.ui_subdivision_t;
@@ -202,6 +644,22 @@ impl UiPanel for BezierPanel {
                         app_state.gui_update(GUIEvent::SubdivisionValue(subdivision_t));
                     }
 
+                    // only the control points of bezier curves have weights, the slider is off
+                    // until one of them is clicked
+                    let weight = app_state.get_selected_weight();
+                    let mut point_weight = weight.unwrap_or(1.0);
+                    if ui
+                        .add_enabled(
+                            weight.is_some(),
+                            egui::Slider::new(&mut point_weight, 0.1..=10.0)
+                                .logarithmic(true)
+                                .text("Point Weight"),
+                        )
+                        .changed()
+                    {
+                        app_state.gui_update(GUIEvent::PointWeight(point_weight));
+                    }
+
                     let colors = app_state.get_colors();
 
                     let mut polygon_color = colors.3.into();
@@ -215,14 +673,29 @@ impl UiPanel for BezierPanel {
                 });
             });
 
-        if !is_bezier_selected && bezier_header.header_response.hovered() {
+        if !is_bezier_selected && !is_spline_selected && bezier_header.header_response.hovered() {
             egui::show_tooltip(ctx, egui::Id::new("bezier_tooltip"), |ui| {
-                ui.label("Select a Bezier curve to enable these options.");
+                ui.label("Select a Bezier curve or a spline to enable these options.");
             });
         }
     }
 }
 
+/// parses a dash pattern written as numbers separated by spaces or commas. Returns None if any of
+/// them is not a number
+fn parse_dash_pattern(text: &str) -> Option<Vec<u32>> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().ok())
+        .collect()
+}
+
+/// writes a dash pattern the way parse_dash_pattern reads it
+fn format_dash_pattern(pattern: &[u32]) -> String {
+    let lengths: Vec<String> = pattern.iter().map(|length| length.to_string()).collect();
+    lengths.join(" ")
+}
+
 /// UI
 pub(crate) struct TemplateApp {
     app_state: AppState,
@@ -236,8 +709,8 @@ impl TemplateApp {
             app_state: AppState::new(),
             panels: vec![
                 Box::new(TopControlsPanel),
-                Box::new(ShapePanel),
-                Box::new(ColorPanel),
+                Box::new(ShapePanel::default()),
+                Box::new(ColorPanel::default()),
                 Box::new(DepthPanel),
                 Box::new(BezierPanel),
             ],
@@ -324,7 +797,11 @@ impl Framework {
         };
         let renderer = Renderer::new(pixels.device(), pixels.render_texture_format(), None, 1);
         let textures = TexturesDelta::default();
-        let gui = TemplateApp::new();
+        let mut gui = TemplateApp::new();
+        // the canvas is the pixel buffer, until the first resize its not the size of the window
+        let buffer = pixels.texture();
+        gui.app_state
+            .gui_update(GUIEvent::CanvasSize(buffer.width(), buffer.height()));
 
         Self {
             egui_ctx,
@@ -342,10 +819,14 @@ impl Framework {
         let _ = self.egui_state.on_window_event(window, event);
     }
 
-    /// resize egui
-    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+    /// resize egui and let the app know the new size of the canvas, the one of the pixel buffer
+    pub(crate) fn resize(&mut self, width: u32, height: u32, pixels: &pixels::Pixels) {
         if width > 0 && height > 0 {
             self.screen_descriptor.size_in_pixels = [width, height];
+            let buffer = pixels.texture();
+            self.gui
+                .app_state
+                .gui_update(GUIEvent::CanvasSize(buffer.width(), buffer.height()));
         }
     }
 
@@ -426,4 +907,9 @@ impl Framework {
     pub(crate) fn wants_pointer_input(&self) -> bool {
         self.egui_ctx.wants_pointer_input() || self.egui_ctx.is_pointer_over_area()
     }
+
+    /// returns "true" if the user is writing on a text field of the GUI
+    pub(crate) fn wants_keyboard_input(&self) -> bool {
+        self.egui_ctx.wants_keyboard_input()
+    }
 }
@@ -0,0 +1,184 @@
+//! headless rendering. Everything here works without a window, a GPU surface or an event loop. We
+//! only need a buffer that we own and the shapes stored on a DrawState
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+
+use crate::{canvas::Canvas, core::RGBA, draw_state::DrawState};
+
+/// Frame is an RGBA buffer owned by us. On the GUI the buffer belongs to the "pixels" library, here
+/// we allocate it ourselves, so we can choose any size we want and save it to a file afterwards
+pub struct Frame {
+    /// width of the frame in pixels
+    width: u32,
+    /// height of the frame in pixels
+    height: u32,
+    /// the pixels, 4 bytes (r,g,b,a) per pixel, row by row
+    buffer: Vec<u8>,
+}
+
+impl Frame {
+    /// creates a new frame with every pixel set to transparent black
+    pub fn new(width: u32, height: u32) -> Self {
+        Frame {
+            width,
+            height,
+            buffer: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// returns the width of the frame
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// returns the height of the frame
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// returns the raw buffer of the frame
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// returns a canvas that draws over this frame. The canvas borrows the buffer, so while it
+    /// exists we cant read the frame (see the lifetime explanation on canvas.rs)
+    pub fn canvas(&mut self) -> Canvas<'_> {
+        Canvas::new(&mut self.buffer, self.width)
+    }
+
+    /// returns a canvas that draws over this frame and counts the times each pixel is set
+    pub fn recording_canvas(&mut self) -> Canvas<'_> {
+        Canvas::new_recording(&mut self.buffer, self.width)
+    }
+
+    /// returns the color of a given pixel. The pixel must be inside the frame
+    pub fn get_pixel(&self, x: u32, y: u32) -> RGBA {
+        let index = (y as usize * self.width as usize + x as usize) * 4;
+        let raw = &self.buffer[index..index + 4];
+        RGBA::new(raw[0], raw[1], raw[2], raw[3])
+    }
+
+    /// writes the frame as an 8 bit RGBA png
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        // png errors can be turned into io errors with "?"
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.buffer)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
+    /// reads an 8 bit RGBA png, like the ones written by save_png
+    pub fn load_png(path: &Path) -> io::Result<Frame> {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        let mut reader = decoder.read_info()?;
+
+        let info = reader.info();
+        if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "only 8 bit RGBA pngs are supported",
+            ));
+        }
+
+        let mut frame = Frame::new(info.width, info.height);
+        reader.next_frame(&mut frame.buffer)?;
+
+        Ok(frame)
+    }
+}
+
+/// draws a drawing into a new frame of the given size. The frame starts with the background
+/// color of the drawing, just like the GUI does
+pub fn render(state: &DrawState, width: u32, height: u32) -> Frame {
+    let mut frame = Frame::new(width, height);
+    state.draw(&mut frame.canvas());
+    frame
+}
+
+/// biggest width or height we render. A frame of this size already takes 256 MB, bigger sizes
+/// would most likely run out of memory
+pub const MAX_SIZE: u32 = 8192;
+
+/// arguments of the render command of the command line (see cli.rs)
+#[derive(Debug, PartialEq)]
+pub struct RenderArgs {
+    /// drawing saved by the app (json)
+    pub input: String,
+    /// png to write
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    /// replaces the background of the drawing if given
+    pub background: Option<RGBA>,
+}
+
+/// parses the arguments of the render command: the input and output files in that order, and
+/// the options "--width", "--height" and "--background" anywhere. Without a size we use
+/// default_size
+pub fn parse_render_args(args: &[String], default_size: (u32, u32)) -> Result<RenderArgs, String> {
+    let mut files = vec![];
+    let (mut width, mut height) = default_size;
+    let mut background = None;
+
+    let mut it = args.iter();
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            // options always have a value after them
+            "--width" | "--height" | "--background" => {
+                let value = it
+                    .next()
+                    .ok_or_else(|| format!("missing value for {arg}"))?;
+                match arg.as_str() {
+                    "--width" => width = parse_size(value)?,
+                    "--height" => height = parse_size(value)?,
+                    _ => background = Some(parse_color(value)?),
+                }
+            }
+            _ => files.push(arg.clone()),
+        }
+    }
+
+    let [input, output] = <[String; 2]>::try_from(files)
+        .map_err(|_| "render needs an input json and an output png".to_string())?;
+
+    Ok(RenderArgs {
+        input,
+        output,
+        width,
+        height,
+        background,
+    })
+}
+
+/// parses a width or height, it must be bigger than 0 and up to MAX_SIZE
+pub fn parse_size(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(size) if (1..=MAX_SIZE).contains(&size) => Ok(size),
+        Ok(size) if size > MAX_SIZE => Err(format!(
+            "\"{value}\" is too big, the biggest size is {MAX_SIZE}"
+        )),
+        _ => Err(format!("\"{value}\" is not a valid size")),
+    }
+}
+
+/// parses a color written as "r,g,b" or "r,g,b,a". Without alpha the color is opaque
+pub fn parse_color(value: &str) -> Result<RGBA, String> {
+    let channels: Result<Vec<u8>, _> = value.split(',').map(|c| c.trim().parse::<u8>()).collect();
+
+    match channels.as_deref() {
+        Ok([r, g, b]) => Ok(RGBA::new(*r, *g, *b, 255)),
+        Ok([r, g, b, a]) => Ok(RGBA::new(*r, *g, *b, *a)),
+        _ => Err(format!("\"{value}\" is not a valid color")),
+    }
+}
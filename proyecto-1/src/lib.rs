@@ -0,0 +1,13 @@
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+// This is the library part of the paint app. It holds everything that does NOT depend on a window:
+// the core definitions, the shapes, the canvas and the draw state. The binary (main.rs) adds the
+// window, the GUI and the input handling on top of this. Having it as a library allows us to render
+// drawings without a window (see headless.rs) and to test the shapes from the tests folder.
+
+pub mod canvas;
+pub mod core;
+pub mod draw_state;
+pub mod headless;
+pub mod primitives;
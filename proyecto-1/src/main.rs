@@ -1,12 +1,11 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
-use core::ShapeCore;
-
 use arboard::Clipboard;
-use canvas::Canvas;
 use error_iter::ErrorIter as _;
 use log::error;
+use paint_app::canvas::Canvas;
+use paint_app::core::ShapeCore;
 use pixels::{Error, Pixels, SurfaceTexture};
 use winit::dpi::LogicalSize;
 use winit::event::{Event, WindowEvent};
@@ -19,22 +18,29 @@ use winit_input_helper::WinitInputHelper;
 use crate::app_state::{GUIEvent, MouseEvent};
 use crate::gui::Framework;
 
+// the window dependent modules live in the binary. Everything else (shapes, canvas, draw state)
+// lives on the library, see lib.rs
 mod app_state;
-mod canvas;
-mod core;
-mod draw_state;
+mod cli;
 mod gui;
-mod primitives;
 
 // initial width and height
 const WIDTH: u32 = 640;
 const HEIGHT: u32 = 480;
 
 /// The main entry point of the application.
+/// If arguments are given it runs the command line mode (see cli.rs).
 /// This function initializes the logger, creates the event loop, window, and the `Pixels` and `Framework` instances.
 /// It then enters the event loop and handles events until the application is closed.
 fn main() -> Result<(), Error> {
     env_logger::init();
+
+    // with arguments we work as a command line tool and never open the window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let event_loop = EventLoop::new().unwrap();
     let mut input = WinitInputHelper::new();
     let mut clipboard = Clipboard::new().expect("Failed to initialize clipboard");
@@ -68,6 +74,8 @@ fn main() -> Result<(), Error> {
     let res = event_loop.run(|event, elwt| {
         if input.update(&event) {
             let is_gui = framework.wants_pointer_input();
+            // keys typed on a text field are for the text, not shortcuts
+            let is_typing = framework.wants_keyboard_input();
             let state = framework.get_state();
             let control_pressed = input.key_held(KeyCode::ControlLeft)
                 || input.key_held(KeyCode::ControlRight)
@@ -80,15 +88,15 @@ fn main() -> Result<(), Error> {
                 return;
             }
 
-            if input.key_pressed(KeyCode::Enter) {
+            if !is_typing && input.key_pressed(KeyCode::Enter) {
                 state.keyboard_update(KeyCode::Enter, true);
             }
 
-            if input.key_pressed(KeyCode::Delete) {
+            if !is_typing && input.key_pressed(KeyCode::Delete) {
                 state.keyboard_update(KeyCode::Delete, true);
             }
 
-            if input.key_pressed(KeyCode::Backspace) {
+            if !is_typing && input.key_pressed(KeyCode::Backspace) {
                 state.keyboard_update(KeyCode::Backspace, true);
             }
 
@@ -108,14 +116,14 @@ fn main() -> Result<(), Error> {
                 state.keyboard_update(KeyCode::ShiftRight, false);
             }
 
-            if control_pressed && input.key_pressed(KeyCode::KeyC) {
+            if !is_typing && control_pressed && input.key_pressed(KeyCode::KeyC) {
                 if let Some(shape) = state.get_selected_shape() {
                     let shape_str = serde_json::to_string_pretty(&shape.get_core()).unwrap();
                     clipboard.set_text(shape_str).unwrap();
                 }
             }
 
-            if control_pressed && input.key_pressed(KeyCode::KeyX) {
+            if !is_typing && control_pressed && input.key_pressed(KeyCode::KeyX) {
                 if let Some(shape) = state.get_selected_shape() {
                     let shape_str = serde_json::to_string_pretty(&shape.get_core()).unwrap();
                     clipboard.set_text(shape_str).unwrap();
@@ -123,6 +131,11 @@ fn main() -> Result<(), Error> {
                 }
             }
 
+            // a drag that started on the canvas can end over the GUI, the state still needs to know
+            if is_gui && input.mouse_released(0) {
+                state.gui_update(GUIEvent::PointerReleased);
+            }
+
             // mouse events on GUI. Avoids drawing while selecting gui buttons
             if !is_gui {
                 // here we update the app state with different mouse events
@@ -157,8 +170,10 @@ fn main() -> Result<(), Error> {
                     let (x, y) = input.cursor().unwrap();
                     if let Ok(text) = clipboard.get_text() {
                         let core_str = serde_json::from_str::<ShapeCore>(&text);
-                        if let Ok(core) = core_str {
-                            state.gui_update(GUIEvent::PasteShape(core, (x, y).into()));
+                        // a pasted shape must be valid, if not we would crash when drawing it
+                        let core_str = core_str.ok().filter(|core| core.validate().is_ok());
+                        if let Some(core) = core_str {
+                            state.gui_update(GUIEvent::PasteShape(Box::new(core), (x, y).into()));
                         } else {
                             println!("this text is not a shape");
                         }
@@ -177,60 +192,54 @@ fn main() -> Result<(), Error> {
 
         // usually the GUI handler (input variable) above would facilitate these events for us
         // but the GUI package above doesnt work well for these events. so we do it here instead
-        match event {
-            Event::WindowEvent { event, .. } => {
-                framework.handle_event(&window, &event);
-                match event {
-                    // important for resizing
-                    WindowEvent::Resized(size) => {
-                        if size.width > 0 && size.height > 0 {
-                            // on resizing we need to resize the SURFACE AND THE BUFFER
-                            // the surface is the window and the buffer is what we modify/render
-                            if let Err(err) = pixels.resize_surface(size.width, size.height) {
-                                log_error("pixels.resize_surface", err);
-                            }
-                            if let Err(err) = pixels.resize_buffer(size.width, size.height) {
-                                log_error("pixels.resize_buffer", err);
-                            }
-                            framework.resize(size.width, size.height);
-                            window.request_redraw();
-                        }
+        if let Event::WindowEvent { event, .. } = event {
+            framework.handle_event(&window, &event);
+            match event {
+                // important for resizing
+                WindowEvent::Resized(size) if size.width > 0 && size.height > 0 => {
+                    // on resizing we need to resize the SURFACE AND THE BUFFER
+                    // the surface is the window and the buffer is what we modify/render
+                    if let Err(err) = pixels.resize_surface(size.width, size.height) {
+                        log_error("pixels.resize_surface", err);
                     }
-                    // this runs every time a redraw is requested
-                    // if we dont do anything the event loop runs but a redraw is not done
-                    WindowEvent::RedrawRequested => {
-                        // we initialize a new canvas with each run
-                        // the canvas has a lifetime bounded to the buffer. So when this loop ends
-                        // the canvas is removed from memory. Check the Canvas definition for more
-                        let size = window.inner_size();
-                        // pixels (the library we're using) gives us the buffer through
-                        // "frame_mut"
-                        let mut canvas = Canvas::new(pixels.frame_mut(), size.width);
-
-                        //drawing with that buffer
-                        framework.get_state().draw(&mut canvas);
-                        framework.prepare(&window);
-
-                        //rendering result
-                        let render_result =
-                            pixels.render_with(|encoder, render_target, context| {
-                                context.scaling_renderer.render(encoder, render_target);
-                                framework.render(encoder, render_target, context);
-
-                                Ok(())
-                            });
-
-                        // simple error handling. A possible improvement would be to return a
-                        // possible error on "draw" from the state and return it here if applicable
-                        if let Err(err) = render_result {
-                            log_error("pixels.render", err);
-                            elwt.exit();
-                        }
+                    if let Err(err) = pixels.resize_buffer(size.width, size.height) {
+                        log_error("pixels.resize_buffer", err);
+                    }
+                    framework.resize(size.width, size.height, &pixels);
+                    window.request_redraw();
+                }
+                // this runs every time a redraw is requested
+                // if we dont do anything the event loop runs but a redraw is not done
+                WindowEvent::RedrawRequested => {
+                    // we initialize a new canvas with each run
+                    // the canvas has a lifetime bounded to the buffer. So when this loop ends
+                    // the canvas is removed from memory. Check the Canvas definition for more
+                    let size = window.inner_size();
+                    // pixels (the library we're using) gives us the buffer through
+                    // "frame_mut"
+                    let mut canvas = Canvas::new(pixels.frame_mut(), size.width);
+
+                    //drawing with that buffer
+                    framework.get_state().draw(&mut canvas);
+                    framework.prepare(&window);
+
+                    //rendering result
+                    let render_result = pixels.render_with(|encoder, render_target, context| {
+                        context.scaling_renderer.render(encoder, render_target);
+                        framework.render(encoder, render_target, context);
+
+                        Ok(())
+                    });
+
+                    // simple error handling. A possible improvement would be to return a
+                    // possible error on "draw" from the state and return it here if applicable
+                    if let Err(err) = render_result {
+                        log_error("pixels.render", err);
+                        elwt.exit();
                     }
-                    _ => (),
                 }
+                _ => (),
             }
-            _ => (),
         }
     });
     res.map_err(|e| Error::UserDefined(Box::new(e)))
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use crate::canvas::Canvas;
+use crate::core::{ShapeCore, RGBA};
+
+use super::stroke::{stroke_path, PointFloat};
+
+/// how much of each pixel a shape covers, from 0 to 1. Like StrokePixels we collect them before
+/// drawing, so a pixel covered by two parts of the shape is drawn once
+pub type Coverage = HashMap<(i32, i32), f32>;
+
+/// each pixel is sampled on a grid of SUBSAMPLES x SUBSAMPLES points to know how much of it is
+/// covered
+const SUBSAMPLES: i32 = 4;
+
+/// coverages this small would be drawn with an alpha of 0, we dont even add them
+const MIN_COVERAGE: f32 = 0.5 / 255.0;
+
+/// adds the coverage of a pixel. If the pixel was already covered by another part of the shape we
+/// keep the biggest value, adding them would make the joints darker than the rest of the border
+pub fn add_coverage(coverage: &mut Coverage, x: i32, y: i32, value: f32) {
+    if value < MIN_COVERAGE {
+        return;
+    }
+
+    let current = coverage.entry((x, y)).or_insert(0.0);
+    *current = current.max(value.min(1.0));
+}
+
+/// xiaolin wu line algorithm. We walk along the axis where the line advances the most and on each
+/// step the line falls between two pixels, each one gets the fraction of the line that is closer
+/// to it. Unlike the original algorithm the ends are not faded, the pixels on the end points are
+/// fully covered like on bresenham, that way the lines of a polyline meet without a dim joint
+pub fn wu_line(coverage: &mut Coverage, a: PointFloat, b: PointFloat) {
+    let steep = (b.1 - a.1).abs() > (b.0 - a.0).abs();
+    // on steep lines x and y are swapped, so we always walk over x
+    let (mut a, mut b) = if steep {
+        ((a.1, a.0), (b.1, b.0))
+    } else {
+        (a, b)
+    };
+    if a.0 > b.0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    let dx = b.0 - a.0;
+    let gradient = if dx == 0.0 { 0.0 } else { (b.1 - a.1) / dx };
+
+    for x in (a.0.round() as i32)..=(b.0.round() as i32) {
+        let y = a.1 + gradient * (x as f32 - a.0);
+        let y_floor = y.floor();
+        let fraction = y - y_floor;
+        let y_floor = y_floor as i32;
+
+        if steep {
+            add_coverage(coverage, y_floor, x, 1.0 - fraction);
+            add_coverage(coverage, y_floor + 1, x, fraction);
+        } else {
+            add_coverage(coverage, x, y_floor, 1.0 - fraction);
+            add_coverage(coverage, x, y_floor + 1, fraction);
+        }
+    }
+}
+
+/// wu lines going through every point. If closed, the last point is also joined with the first
+pub fn wu_polyline(points: &[PointFloat], closed: bool) -> Coverage {
+    let mut coverage = Coverage::new();
+    for w in points.windows(2) {
+        wu_line(&mut coverage, w[0], w[1]);
+    }
+    if closed && points.len() > 2 {
+        wu_line(&mut coverage, points[points.len() - 1], points[0]);
+    }
+    if let [single] = points {
+        wu_line(&mut coverage, *single, *single);
+    }
+    coverage
+}
+
+/// coverage of the border with the width, caps, joins and dashes of the shape core (see
+/// stroke.rs). We rasterize the stroke on a grid SUBSAMPLES times finer than the canvas, each
+/// pixel of that grid is one of the samples of a pixel of the canvas
+pub fn stroke_coverage(points: &[PointFloat], core: &ShapeCore, closed: bool) -> Coverage {
+    let scale = SUBSAMPLES as u32;
+    let fine_core = ShapeCore {
+        stroke_width: core.stroke_width * scale,
+        dash_pattern: core.dash_pattern.iter().map(|l| l * scale).collect(),
+        dash_offset: core.dash_offset * scale,
+        ..core.clone()
+    };
+
+    // the samples of the pixel x are at x - 0.5 + (i + 0.5) / SUBSAMPLES. With this shift they
+    // end up on the integer positions SUBSAMPLES * x + i of the fine grid
+    let shift = SUBSAMPLES as f32 / 2.0 - 0.5;
+    let fine_points: Vec<PointFloat> = points
+        .iter()
+        .map(|p| {
+            (
+                p.0 * SUBSAMPLES as f32 + shift,
+                p.1 * SUBSAMPLES as f32 + shift,
+            )
+        })
+        .collect();
+
+    let mut samples: HashMap<(i32, i32), u32> = HashMap::new();
+    for (x, y) in stroke_path(&fine_points, &fine_core, closed) {
+        let pixel = (x.div_euclid(SUBSAMPLES), y.div_euclid(SUBSAMPLES));
+        *samples.entry(pixel).or_insert(0) += 1;
+    }
+
+    let total = (SUBSAMPLES * SUBSAMPLES) as f32;
+    samples
+        .into_iter()
+        .map(|(pixel, count)| (pixel, count as f32 / total))
+        .collect()
+}
+
+/// coverage of a region given a test that tells if a point is inside of it. Every pixel from min
+/// to max (inclusive) is sampled
+pub fn region_coverage(
+    min: (i32, i32),
+    max: (i32, i32),
+    inside: impl Fn(PointFloat) -> bool,
+) -> Coverage {
+    let mut coverage = Coverage::new();
+    let total = (SUBSAMPLES * SUBSAMPLES) as f32;
+
+    for y in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            let mut count = 0;
+            for i in 0..SUBSAMPLES {
+                for j in 0..SUBSAMPLES {
+                    let sample = (
+                        x as f32 - 0.5 + (i as f32 + 0.5) / SUBSAMPLES as f32,
+                        y as f32 - 0.5 + (j as f32 + 0.5) / SUBSAMPLES as f32,
+                    );
+                    if inside(sample) {
+                        count += 1;
+                    }
+                }
+            }
+            add_coverage(&mut coverage, x, y, count as f32 / total);
+        }
+    }
+
+    coverage
+}
+
+/// draws a shape given the coverage of its border and of its inside. The border goes over the
+/// fill, we mix both colors first so each pixel is written once
+pub fn draw_coverage(
+    border: &Coverage,
+    fill: &Coverage,
+    color: RGBA,
+    fill_color: RGBA,
+    canvas: &mut Canvas,
+) {
+    for (&(x, y), &c) in border {
+        let mut pixel = color.with_coverage(c);
+        if let Some(f) = fill.get(&(x, y)) {
+            pixel = pixel + fill_color.with_coverage(*f);
+        }
+        canvas.set_pixel(x, y, pixel);
+    }
+
+    for (&(x, y), &f) in fill {
+        if !border.contains_key(&(x, y)) {
+            canvas.set_pixel(x, y, fill_color.with_coverage(f));
+        }
+    }
+}
@@ -1,16 +1,37 @@
-use super::line::{draw_line, line_hit_test};
+use super::antialias::{draw_coverage, Coverage};
+use super::dash::DashPattern;
+use super::freehand::distance_to_segment;
+use super::line::{antialiased_border, draw_dashed_polyline, draw_line, HIT_TEST_ERROR};
+use super::stroke::{draw_stroke, stroke_polyline, PointFloat};
 use crate::canvas::Canvas;
 use crate::core::{Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
 
-/// detail factor is used to configure how much detail the bezier curve step t will have.
-const DETAIL_FACTOR: f32 = 0.03;
+/// how far from the curve the lines it is drawn with can go, in pixels. The curve is split until
+/// each piece is this flat (see flatten)
+const FLATNESS_TOLERANCE: f32 = 0.25;
+
+/// how many times a piece of the curve can be split in halves, a limit for curves that never get
+/// flat (like control points that are not numbers)
+const MAX_FLATTEN_DEPTH: u32 = 16;
+
+/// points of the curve compared for each control point when reducing its degree
+const REDUCE_SAMPLES: usize = 16;
+
+/// points of the curve compared for each control point when looking for the closest one to a
+/// point, before refining it
+const CLOSEST_SAMPLES: usize = 16;
+
+/// times the closest point is refined, each one leaves 0.618 of the range of t it was in
+const CLOSEST_REFINE_STEPS: usize = 30;
+
+/// dashes of the control polygon drawn when the curve is selected
+pub(super) const CONTROL_POLYGON_DASH: [u32; 2] = [4, 4];
 
 pub struct Bezier {
     core: ShapeCore,
     subdivide_t: f32,
-    // auxiliar lines to CHECK if the user is clicking the bezier curve
+    // lines to DRAW the bezier curve
     // these are updated each time the bezier curve is updated
-    // then we use these lines to DRAW the bezier curve
     // This is way more efficient than drawing the shape on each render. We generate the shape only
     // if its modified and draw the lines generated
     // We ALSO store if we should draw the last line or not, used to avoid redrawing points
@@ -37,10 +58,49 @@ impl ShapeImpl for Bezier {
     fn update(&mut self, op: &UpdateOp) {
         self.update_basic(op);
 
+        // bezier is the only one that also implements degree_elevate, degree_reduce, inserting
+        // control points and subdivide update. They go first since the lines are generated from
+        // their result
         match op {
-            // ignore these two operations
+            UpdateOp::DegreeElevate => {
+                self.degree_elevate();
+            }
+            UpdateOp::DegreeReduce => {
+                self.degree_reduce();
+            }
+            UpdateOp::InsertControlPoint(p) => {
+                self.insert_control_point(*p);
+            }
+            UpdateOp::UpdateSubdivide(t) => {
+                self.subdivide_t = *t;
+            }
+            _ => {}
+        }
+
+        match op {
+            // ignore these operations
             UpdateOp::ChangeColor(_)
             | UpdateOp::ChangeFillColor(_)
+            | UpdateOp::ChangeBlendMode(_)
+            | UpdateOp::ChangeStrokeWidth(_)
+            | UpdateOp::ChangeLineCap(_)
+            | UpdateOp::ChangeLineJoin(_)
+            | UpdateOp::ChangeMiterLimit(_)
+            | UpdateOp::ChangeDashPattern(_, _)
+            | UpdateOp::ChangeAntialias(_)
+            | UpdateOp::ChangeFillRule(_)
+            | UpdateOp::ChangeSmooth(_)
+            | UpdateOp::ChangeRotation(_)
+            | UpdateOp::ChangeArc(_)
+            | UpdateOp::ChangeCornerRadii(_)
+            | UpdateOp::ChangeText(_)
+            | UpdateOp::ChangeFontSize(_)
+            | UpdateOp::ChangeImage(_)
+            | UpdateOp::ChangeSampling(_)
+            | UpdateOp::ChangeDegree(_)
+            | UpdateOp::ChangeKnotVector(_)
+            | UpdateOp::ChangeJoint(_, _)
+            | UpdateOp::InsertKnot
             | UpdateOp::UpdateSubdivide(_) => {}
             // for every other operation update the lines since every other op changes the control
             // points
@@ -48,24 +108,37 @@ impl ShapeImpl for Bezier {
                 self.lines = Bezier::generate_lines(&self.core);
             }
         }
-
-        // bezier is the only one that also implements degree_elevate and subdivide update
-        match op {
-            UpdateOp::DegreeElevate => {
-                self.degree_elevate();
-            }
-            UpdateOp::UpdateSubdivide(t) => {
-                self.subdivide_t = *t;
-            }
-            _ => {}
-        }
     }
 
     fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
-        draw_bezier(&self.lines, canvas);
+        self.draw_with_color(self.core.color, canvas);
     }
 
     fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        // the anti-aliased curve uses the points of the curve without rounding them
+        if self.core.antialias {
+            let border = antialiased_border(&flatten(&self.core), &self.core, false);
+            draw_coverage(
+                &border,
+                &Coverage::new(),
+                color,
+                self.core.fill_color,
+                canvas,
+            );
+            return;
+        }
+
+        if self.core.stroke_width > 1 {
+            self.draw_thick(color, canvas);
+            return;
+        }
+
+        // the dashes go along the whole curve, not along each line on its own
+        if let Some(dash) = DashPattern::new(&self.core) {
+            draw_dashed_polyline(&self.curve_points(), false, &dash, color, canvas);
+            return;
+        }
+
         let lines_w_color = self
             .lines
             .iter()
@@ -75,64 +148,42 @@ impl ShapeImpl for Bezier {
     }
 
     /// bezier on draw_selection also draws the subdivision point.
-    /// we we modify this function to also draw those and the control points
+    /// we we modify this function to also draw those and the control points. The control polygon
+    /// is dashed so its not confused with the curve
     fn draw_selection<'a>(&self, color1: RGBA, color2: RGBA, canvas: &mut Canvas<'a>) {
         self.draw_selection_basic(color1, canvas);
 
-        for i in 1..self.core.points.len() {
-            let mut line_core = self
-                .core
-                .copy_with_points(vec![self.core.points[i - 1], self.core.points[i]]);
-            line_core.color = color2;
-            draw_line(&line_core, canvas, true);
+        if let Some(dash) = DashPattern::from_lengths(&CONTROL_POLYGON_DASH, 0) {
+            draw_dashed_polyline(&self.core.points, false, &dash, color2, canvas);
         }
 
         let p = de_casteljau(&self.core, self.subdivide_t);
         self.draw_control_point(p, color1, canvas);
     }
 
-    /// on the bezier hit test is a hit test over all the lines generated for the curve
-    /// is a bit expensive but is the most precise way we figured out now.
+    /// the curve is hit from as far as a line is, plus half of its width. The distance is the one
+    /// to the closest point of the curve (see closest_point)
     fn hit_test(&self, point: Point) -> bool {
         if self.core.points.is_empty() {
             return false;
         }
 
-        for l in &self.lines {
-            if line_hit_test(&l.0, point) {
-                return true;
-            }
-        }
-
-        return false;
+        let reach = HIT_TEST_ERROR as f32 + (self.core.stroke_width / 2) as f32;
+        closest_point(&self.core, point).1 < reach
     }
 
     /// for subdivision we dont modify the current shape, we do that in the application state
     /// management. The subdivision only returns the resulting shapes if the method is implemented.
     /// For bezier subdivision creates the vectors for the two other shapes and runs an
     /// interpolation over the current points, then we fill both vectors with the first and last
-    /// result from the recurrent interpolation.
+    /// result from the recurrent interpolation. The interpolation is done with the weighted
+    /// points (see homogeneous), so each half keeps its part of a rational curve
     fn subdivide(&self) -> Option<(ShapeCore, ShapeCore)> {
-        let mut pts_cpy = self.core.points.clone();
-        let n = pts_cpy.len();
-
-        let mut first = vec![self.core.points[0]];
-        let mut second = vec![self.core.points[n - 1]];
-
-        for r in 1..n {
-            for i in 0..(n - r) {
-                pts_cpy[i] = pts_cpy[i].interpolate(pts_cpy[i + 1], self.subdivide_t);
-            }
-
-            first.push(pts_cpy[0]);
-            second.push(pts_cpy[n - r - 1]);
-        }
-
-        second.reverse();
+        let (first, second) = split_homogeneous(&homogeneous(&self.core), self.subdivide_t);
 
         Some((
-            self.core.copy_with_points(first),
-            self.core.copy_with_points(second),
+            self.core_from_homogeneous(&first),
+            self.core_from_homogeneous(&second),
         ))
     }
 }
@@ -140,55 +191,114 @@ impl ShapeImpl for Bezier {
 /// only bezier implements degree elevate. We dont use this outside the bezier class so we dont
 /// implement it on ShapeImpl
 impl Bezier {
-    /// degree elevate adds more control points to the current shape by performing a degree elevate
+    /// degree elevate adds more control points to the current shape by performing a degree elevate.
+    /// On rational curves the weighted points are elevated, like with subdivide
     fn degree_elevate(&mut self) {
-        let n = self.core.points.len() as f32;
+        let points = homogeneous(&self.core);
+        let n = points.len() as f32;
         // we create new points since the old ones are used for calculation
-        let mut new_points: Vec<Point> = Vec::with_capacity(n as usize + 1);
+        let mut new_points = Vec::with_capacity(points.len() + 1);
+
+        new_points.push(points[0]);
 
-        new_points.push(self.core.points[0]);
+        for i in 1..points.len() {
+            new_points.push(interpolate_homogeneous(
+                points[i],
+                points[i - 1],
+                i as f32 / n,
+            ));
+        }
+
+        new_points.push(points[points.len() - 1]);
 
-        for i in 1..(n as usize) {
-            let b_prev = self.core.points[i - 1];
-            let b = self.core.points[i];
+        self.core = self.core_from_homogeneous(&new_points);
+    }
 
-            new_points.push(b.interpolate(b_prev, i as f32 / n));
+    /// adds a control point at target. The curve goes from the control point i to the next one
+    /// around t = i / (n - 1), so the point is put after the control point of the t of the closest
+    /// point of the curve. The curve changes, it gets pulled towards the new point. On rational
+    /// curves the new point has a weight of 1
+    fn insert_control_point(&mut self, target: Point) {
+        let n = self.core.points.len();
+        if n < 2 {
+            return;
         }
 
-        new_points.push(*self.core.points.last().unwrap());
+        let (t, _) = closest_point(&self.core, target);
+        let at = ((t * (n - 1) as f32) as usize).min(n - 2) + 1;
+        self.core.points.insert(at, target);
+        if !self.core.weights.is_empty() {
+            self.core.weights.insert(at, 1.0);
+        }
+    }
 
-        // here thanks to references and borrowing in rust the original core.points are removed
-        // from memory
-        self.core.points = new_points;
+    /// degree reduce removes a control point, the inverse of degree_elevate. If the curve cant
+    /// be reduced its left as it is (see reduce_degree)
+    fn degree_reduce(&mut self) {
+        if let Some(core) = reduce_degree(&self.core) {
+            self.core = core;
+        }
     }
 
-    /// generates the lines that later we will draw, uses de_casteljau algorithm
-    fn generate_lines(core: &ShapeCore) -> Vec<(ShapeCore, bool)> {
-        let mut t = 0.0;
-        let mut prev_pts: Option<Point> = None;
-        let mut draw_last = false;
-        let mut result = vec![];
-        let detail = get_detail(&core);
-
-        while t <= 1.0 {
-            let p = de_casteljau(&core, t);
-            if let Some(prev) = prev_pts {
-                // since draw line must not draw the first point we sort the points backwards p first
-                // then prev
-                // that way we draw a line that is connected to the next one without overlapping
-                // basically given a,b,c,d points from the bezier curve we draw lines [a,b),[b,c),[c,d]
-                let line_core = core.copy_with_points(vec![p, prev]);
-                result.push((line_core, draw_last));
-            }
+    /// the core of the curve with the given weighted points (see homogeneous). The weights are
+    /// only kept if the curve had them, a curve without weights has all of them 1
+    fn core_from_homogeneous(&self, points: &[HomogeneousPoint]) -> ShapeCore {
+        let mut core = self.core.copy_with_points(
+            points
+                .iter()
+                .map(|p| Point::from((p.0 / p.2, p.1 / p.2)))
+                .collect(),
+        );
+        core.weights = if self.core.weights.is_empty() {
+            vec![]
+        } else {
+            points.iter().map(|p| p.2).collect()
+        };
+        core
+    }
 
-            prev_pts = Some(p);
-            t += detail;
-            // our lines are drawn from a to b like this [a,b). the last point must be drawn, when this
-            // is true the full line is drawn [a,b]
-            draw_last = t + detail > 1.0;
+    /// draws the curve with a border wider than 1 pixel. The generated lines are joined into a
+    /// single stroke (see stroke.rs), drawing each line on its own would draw the joints twice
+    fn draw_thick(&self, color: RGBA, canvas: &mut Canvas) {
+        let pixels = stroke_polyline(&self.curve_points(), &self.core, false);
+        draw_stroke(&pixels, color, canvas);
+    }
+
+    /// returns the points of the curve in order, the ends of the generated lines
+    fn curve_points(&self) -> Vec<Point> {
+        // each line goes from the new point to the previous one, see generate_lines
+        let mut points: Vec<Point> = self.lines.iter().map(|(l, _)| l.points[0]).collect();
+        if let Some((first, _)) = self.lines.first() {
+            points.insert(0, first.points[1]);
         }
+        points
+    }
 
-        return result;
+    /// generates the lines that later we will draw, between the points given by flatten rounded
+    /// to pixels
+    fn generate_lines(core: &ShapeCore) -> Vec<(ShapeCore, bool)> {
+        let mut points: Vec<Point> = flatten(core).into_iter().map(Point::from).collect();
+        // points of the curve close to each other can fall on the same pixel
+        points.dedup();
+        // a curve that fits in a pixel is still drawn as that pixel
+        if points.len() == 1 {
+            points.push(points[0]);
+        }
+
+        let lines = points.len() - 1;
+        points
+            .windows(2)
+            .enumerate()
+            .map(|(i, w)| {
+                // since draw line must not draw the first point we sort the points backwards p
+                // first then prev
+                // that way we draw a line that is connected to the next one without overlapping
+                // basically given a,b,c,d points from the bezier curve we draw lines [a,b),[b,c),[c,d]
+                // our lines are drawn from a to b like this [a,b). the last point must be drawn, for
+                // the last line the full line is drawn [a,b]
+                (core.copy_with_points(vec![w[1], w[0]]), i + 1 == lines)
+            })
+            .collect()
     }
 }
 
@@ -203,29 +313,344 @@ fn draw_bezier(lines: &Vec<(ShapeCore, bool)>, canvas: &mut Canvas) {
 /// bezier curve algorithm used. given a t and control points it calculates the next point in the
 /// curve by interpolating the control points in a recursive way (without using actual recursion,
 /// just loops).
+/// The point is only rounded at the end, rounding the interpolated points on each step would add
+/// up on curves with many control points
 fn de_casteljau(core: &ShapeCore, t: f32) -> Point {
-    let mut pts_cpy = core.points.clone();
+    de_casteljau_float(core, t).into()
+}
+
+/// same as de_casteljau but without rounding the points
+fn de_casteljau_float(core: &ShapeCore, t: f32) -> PointFloat {
+    project(de_casteljau_homogeneous(&homogeneous(core), t))
+}
+
+/// de_casteljau over weighted points (see homogeneous), the point is still multiplied by its
+/// weight
+fn de_casteljau_homogeneous(points: &[HomogeneousPoint], t: f32) -> HomogeneousPoint {
+    let mut pts_cpy = points.to_vec();
 
     for r in 1..pts_cpy.len() {
         for i in 0..(pts_cpy.len() - r) {
-            pts_cpy[i] = pts_cpy[i].interpolate(pts_cpy[i + 1], t);
+            pts_cpy[i] = interpolate_homogeneous(pts_cpy[i], pts_cpy[i + 1], t);
         }
     }
 
     pts_cpy[0]
 }
 
-/// get_detail gives us how much will t increase on each step
-/// its calculated by getting the distance between the control points (adding them) and then
-/// multiplying this by 1 / DETAIL_FACTOR
-fn get_detail(core: &ShapeCore) -> f32 {
-    // distance is initialized with 0.1 to avoid division by 0
-    let mut distance = 0.01;
-    let pts = &core.points;
-    for i in 0..pts.len() - 1 {
-        distance += pts[i].distance(pts[i + 1]);
+/// returns the curve with one control point less, the inverse of degree_elevate. Most curves
+/// cant be drawn exactly with one point less, so the new points are the ones that are closest to
+/// the curve (least squares over REDUCE_SAMPLES points of each control point). The ends stay where
+/// they are. Rational curves are reduced with their weighted points, the others only with x and
+/// y. Returns None for a line, or if the new points are not numbers or a weight is not positive
+pub fn reduce_degree(core: &ShapeCore) -> Option<ShapeCore> {
+    let points = homogeneous(core);
+    let n = points.len();
+    if n <= 2 {
+        return None;
+    }
+
+    // the new curve has degree n - 2, its first and last points are known, only the ones between
+    // them are searched. Its all done with f64, f32 loses the curve past 13 points or so
+    let degree = n - 2;
+    let coordinates = if core.weights.is_empty() { 2 } else { 3 };
+    let coordinate = |p: HomogeneousPoint, c: usize| [p.0, p.1, p.2][c] as f64;
+    let samples = REDUCE_SAMPLES * n;
+
+    // one row for each sample, a * x = b with x the points between the ends
+    let mut a = Vec::with_capacity(samples + 1);
+    let mut b = Vec::with_capacity(samples + 1);
+    for k in 0..=samples {
+        let t = k as f64 / samples as f64;
+        let basis: Vec<f64> = (0..=degree).map(|j| bernstein(degree, j, t)).collect();
+        let curve: Vec<f64> = (0..=n - 1).map(|j| bernstein(n - 1, j, t)).collect();
+        // what the points between the ends must add up to
+        let rest: Vec<f64> = (0..coordinates)
+            .map(|c| {
+                let p: f64 = points
+                    .iter()
+                    .zip(&curve)
+                    .map(|(p, weight)| coordinate(*p, c) * weight)
+                    .sum();
+                p - coordinate(points[0], c) * basis[0]
+                    - coordinate(points[n - 1], c) * basis[degree]
+            })
+            .collect();
+        a.push(basis[1..degree].to_vec());
+        b.push(rest);
+    }
+
+    let mut new_points = vec![points[0]];
+    for x in least_squares(a, b)? {
+        let w = x.get(2).copied().unwrap_or(1.0);
+        new_points.push((x[0] as f32, x[1] as f32, w as f32));
+    }
+    new_points.push(points[n - 1]);
+
+    let is_valid =
+        |p: &HomogeneousPoint| p.0.is_finite() && p.1.is_finite() && p.2.is_finite() && p.2 > 0.0;
+    if !new_points.iter().all(is_valid) {
+        return None;
+    }
+
+    let mut reduced = core.copy_with_points(
+        new_points
+            .iter()
+            .map(|p| Point::from((p.0 / p.2, p.1 / p.2)))
+            .collect(),
+    );
+    if !core.weights.is_empty() {
+        reduced.weights = new_points.iter().map(|p| p.2).collect();
+    }
+    Some(reduced)
+}
+
+/// how much the control point i of a curve of the given degree pulls the curve at t (the
+/// Bernstein polynomial), the weight de_casteljau ends up giving to that point
+fn bernstein(degree: usize, i: usize, t: f64) -> f64 {
+    let binomial = (0..i).fold(1.0, |acc, k| acc * (degree - k) as f64 / (k + 1) as f64);
+    binomial * t.powi(i as i32) * (1.0 - t).powi((degree - i) as i32)
+}
+
+/// solves the least squares a * x = b with Householder reflections (QR), for each column of b at
+/// once. Each row of a is an equation. The normal equations of the Bernstein polynomials lose
+/// most of their digits on curves with many points, QR works on a directly and doesnt. Returns
+/// the rows of x, or None if the columns of a are not independent
+fn least_squares(mut a: Vec<Vec<f64>>, mut b: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let cols = a[0].len();
+    for col in 0..cols {
+        // the reflection that leaves zeros below the diagonal of this column
+        let norm = a[col..]
+            .iter()
+            .map(|row| row[col].powi(2))
+            .sum::<f64>()
+            .sqrt();
+        if norm == 0.0 {
+            return None;
+        }
+        let alpha = if a[col][col] > 0.0 { -norm } else { norm };
+        let mut v: Vec<f64> = a[col..].iter().map(|row| row[col]).collect();
+        v[0] -= alpha;
+        let length: f64 = v.iter().map(|x| x * x).sum();
+
+        // every column of a and of b goes through the reflection
+        let reflect = |matrix: &mut Vec<Vec<f64>>, c: usize| {
+            let dot: f64 = matrix[col..]
+                .iter()
+                .zip(&v)
+                .map(|(row, v)| row[c] * v)
+                .sum();
+            let factor = 2.0 * dot / length;
+            for (row, v) in matrix[col..].iter_mut().zip(&v) {
+                row[c] -= factor * v;
+            }
+        };
+        for c in col..cols {
+            reflect(&mut a, c);
+        }
+        for c in 0..b[0].len() {
+            reflect(&mut b, c);
+        }
+    }
+
+    // a is upper triangular now, the rows below cols are what cant be solved
+    let mut x = vec![vec![0.0; b[0].len()]; cols];
+    for row in (0..cols).rev() {
+        for c in 0..b[0].len() {
+            let known: f64 = (row + 1..cols).map(|k| a[row][k] * x[k][c]).sum();
+            x[row][c] = (b[row][c] - known) / a[row][row];
+        }
+    }
+    Some(x)
+}
+
+/// returns the biggest distance between the points of both curves at the same t, how much a
+/// curve moved after reducing its degree. The curves are sampled about once per pixel of their
+/// control polygons, like erased_range
+pub fn max_deviation(a: &ShapeCore, b: &ShapeCore) -> f32 {
+    let length =
+        |core: &ShapeCore| -> f32 { core.points.windows(2).map(|w| w[0].distance(w[1])).sum() };
+    let steps = (length(a).max(length(b)).ceil() as usize).max(1);
+
+    (0..=steps)
+        .map(|i| {
+            let t = i as f32 / steps as f32;
+            let (p, q) = (de_casteljau_float(a, t), de_casteljau_float(b, t));
+            (p.0 - q.0).hypot(p.1 - q.1)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// a control point multiplied by its weight, with the weight as third coordinate
+type HomogeneousPoint = (f32, f32, f32);
+
+/// the control points of the curve multiplied by their weights. A rational bezier is a normal
+/// bezier on these points, its points are found by dividing by the interpolated weight. With all
+/// the weights 1 its the same curve
+fn homogeneous(core: &ShapeCore) -> Vec<HomogeneousPoint> {
+    core.points
+        .iter()
+        .zip(core.point_weights())
+        .map(|(p, w)| (p.0 as f32 * w, p.1 as f32 * w, w))
+        .collect()
+}
+
+fn interpolate_homogeneous(a: HomogeneousPoint, b: HomogeneousPoint, t: f32) -> HomogeneousPoint {
+    (
+        a.0 + (b.0 - a.0) * t,
+        a.1 + (b.1 - a.1) * t,
+        a.2 + (b.2 - a.2) * t,
+    )
+}
+
+/// splits the curve at t into two curves with de_casteljau, the points of the first one and the
+/// points of the second one
+fn split_homogeneous(
+    points: &[HomogeneousPoint],
+    t: f32,
+) -> (Vec<HomogeneousPoint>, Vec<HomogeneousPoint>) {
+    let mut pts_cpy = points.to_vec();
+    let n = pts_cpy.len();
+
+    let mut first = vec![pts_cpy[0]];
+    let mut second = vec![pts_cpy[n - 1]];
+
+    for r in 1..n {
+        for i in 0..(n - r) {
+            pts_cpy[i] = interpolate_homogeneous(pts_cpy[i], pts_cpy[i + 1], t);
+        }
+
+        first.push(pts_cpy[0]);
+        second.push(pts_cpy[n - r - 1]);
+    }
+
+    second.reverse();
+    (first, second)
+}
+
+/// returns the points of the curve without rounding them, from the first control point to the
+/// last one. The lines between them are never further than FLATNESS_TOLERANCE from the curve:
+/// the curve is split in halves until the control points of each half are that close to the line
+/// between its ends, and since a curve never leaves its control points its that close too. Tight
+/// turns are split more times than long gentle ones
+fn flatten(core: &ShapeCore) -> Vec<PointFloat> {
+    let points = homogeneous(core);
+    let mut result = vec![project(points[0])];
+    flatten_piece(&points, 0, &mut result);
+    result
+}
+
+/// adds the points of a piece of the curve to result, without its first one
+fn flatten_piece(points: &[HomogeneousPoint], depth: u32, result: &mut Vec<PointFloat>) {
+    let projected: Vec<PointFloat> = points.iter().map(|p| project(*p)).collect();
+    let (start, end) = (projected[0], projected[projected.len() - 1]);
+    let flat = projected
+        .iter()
+        .all(|p| distance_to_segment(*p, start, end) <= FLATNESS_TOLERANCE);
+
+    if flat || depth == MAX_FLATTEN_DEPTH {
+        result.push(end);
+        return;
     }
 
-    let dist = distance * DETAIL_FACTOR;
-    1.0 / dist
+    let (first, second) = split_homogeneous(points, 0.5);
+    flatten_piece(&first, depth + 1, result);
+    flatten_piece(&second, depth + 1, result);
+}
+
+/// the point a weighted point stands for
+fn project(p: HomogeneousPoint) -> PointFloat {
+    (p.0 / p.2, p.1 / p.2)
+}
+
+/// returns the t of the point of the curve closest to target and how far it is. The curve is
+/// sampled CLOSEST_SAMPLES times for each control point, then each sample that is not further
+/// than its neighbours is refined between them with a golden section search, and the closest
+/// one wins. The points are computed without rounding, rational curves included
+pub fn closest_point(core: &ShapeCore, target: Point) -> (f32, f32) {
+    let points = homogeneous(core);
+    let target: PointFloat = target.into();
+    let distance = |t: f32| {
+        let p = project(de_casteljau_homogeneous(&points, t));
+        (p.0 - target.0).hypot(p.1 - target.1)
+    };
+
+    let samples = CLOSEST_SAMPLES * points.len();
+    let distances: Vec<f32> = (0..=samples)
+        .map(|k| distance(k as f32 / samples as f32))
+        .collect();
+
+    let mut closest = (0.0, distances[0]);
+    for k in 0..=samples {
+        let (before, after) = (k.saturating_sub(1), (k + 1).min(samples));
+        if distances[k] > distances[before] || distances[k] > distances[after] {
+            continue;
+        }
+
+        let sample = (k as f32 / samples as f32, distances[k]);
+        let refined = golden_section(
+            &distance,
+            before as f32 / samples as f32,
+            after as f32 / samples as f32,
+        );
+        for candidate in [sample, refined] {
+            if candidate.1 < closest.1 {
+                closest = candidate;
+            }
+        }
+    }
+
+    closest
+}
+
+/// returns the t between lo and hi where distance is the lowest and that distance. Between them
+/// distance must only go down and then up
+fn golden_section(distance: &impl Fn(f32) -> f32, mut lo: f32, mut hi: f32) -> (f32, f32) {
+    let ratio = (5.0_f32.sqrt() - 1.0) / 2.0;
+    let (mut a, mut b) = (hi - ratio * (hi - lo), lo + ratio * (hi - lo));
+    let (mut distance_a, mut distance_b) = (distance(a), distance(b));
+
+    for _ in 0..CLOSEST_REFINE_STEPS {
+        if distance_a < distance_b {
+            (hi, b, distance_b) = (b, a, distance_a);
+            a = hi - ratio * (hi - lo);
+            distance_a = distance(a);
+        } else {
+            (lo, a, distance_a) = (a, b, distance_b);
+            b = lo + ratio * (hi - lo);
+            distance_b = distance(b);
+        }
+    }
+
+    let t = (lo + hi) / 2.0;
+    (t, distance(t))
+}
+
+/// returns the range of t (from, to) of the curve that is closer than the radius to the segment
+/// from a to b, the part of the curve that an eraser moving from a to b goes over. A line is a
+/// curve with 2 control points, so it works for lines too. If the eraser goes over the curve more
+/// than once only the first range is returned, None if it doesnt touch it. A range of a single
+/// sample (like when the eraser only touches an end) is shorter than what we can cut, so its None
+/// too.
+///
+/// The curve is sampled about once per pixel of its control polygon, which is never shorter than
+/// the curve
+pub fn erased_range(core: &ShapeCore, a: Point, b: Point, radius: f32) -> Option<(f32, f32)> {
+    let length: f32 = core.points.windows(2).map(|w| w[0].distance(w[1])).sum();
+    let steps = (length.ceil() as usize).max(1);
+    let (a, b) = (a.into(), b.into());
+    let erased = |i: usize| {
+        let p = de_casteljau_float(core, i as f32 / steps as f32);
+        distance_to_segment(p, a, b) < radius
+    };
+
+    let first = (0..=steps).find(|i| erased(*i))?;
+    let last = (first..=steps)
+        .take_while(|i| erased(*i))
+        .last()
+        .unwrap_or(first);
+    if last == first {
+        return None;
+    }
+    Some((first as f32 / steps as f32, last as f32 / steps as f32))
 }
@@ -0,0 +1,151 @@
+use crate::core::ShapeCore;
+
+use super::stroke::PointFloat;
+
+/// a dash pattern ready to be used on a border. The distance along the border decides if we are
+/// on a dash or on a gap, that distance keeps growing from one segment to the next so the pattern
+/// doesnt restart on each corner
+pub struct DashPattern {
+    /// lengths of the dashes and gaps: dash, gap, dash, gap... Always an even amount
+    lengths: Vec<f32>,
+    /// length of the whole pattern
+    total: f32,
+    /// how far into the pattern the border starts
+    offset: f32,
+}
+
+impl DashPattern {
+    /// returns the dash pattern of a shape, or None if its border is solid
+    pub fn new(core: &ShapeCore) -> Option<DashPattern> {
+        DashPattern::from_lengths(&core.dash_pattern, core.dash_offset)
+    }
+
+    /// creates a dash pattern from its lengths, or None if there's nothing to dash
+    pub fn from_lengths(pattern: &[u32], offset: u32) -> Option<DashPattern> {
+        if pattern.iter().all(|l| *l == 0) {
+            return None;
+        }
+
+        let mut lengths: Vec<f32> = pattern.iter().map(|l| *l as f32).collect();
+        // like svg, an odd pattern is repeated. [5] means a dash of 5 and a gap of 5
+        if !lengths.len().is_multiple_of(2) {
+            lengths.extend_from_within(..);
+        }
+        let total: f32 = lengths.iter().sum();
+
+        Some(DashPattern {
+            lengths,
+            total,
+            offset: offset as f32 % total,
+        })
+    }
+
+    /// returns the index of the length we are on at the given distance from the start of the
+    /// border and how much of it is left. Even indexes are dashes, odd ones are gaps
+    fn entry_at(&self, distance: f32) -> (usize, f32) {
+        let mut d = (distance + self.offset) % self.total;
+        for (i, length) in self.lengths.iter().enumerate() {
+            // a dash of length 0 is only a point, we are on it if we are exactly there
+            let on_dot = *length == 0.0 && d == 0.0 && i.is_multiple_of(2);
+            if d < *length || on_dot {
+                return (i, length - d);
+            }
+            d -= length;
+        }
+
+        // float errors can leave us right at the end of the pattern, which is its start
+        (0, self.lengths[0])
+    }
+
+    /// checks if the border is drawn at the given distance from its start
+    pub fn is_on(&self, distance: f32) -> bool {
+        self.entry_at(distance).0.is_multiple_of(2)
+    }
+
+    /// keeps the pixels of a 1 pixel border that are on a dash. The pixels must be in the order
+    /// they have on the border, the distance between each one and the next is the distance we
+    /// advance along the border (1 for straight steps and 1.41 for diagonal ones)
+    pub fn filter_pixels(&self, pixels: &[(i32, i32)]) -> Vec<(i32, i32)> {
+        let mut distance = 0.0;
+        let mut result = vec![];
+
+        for (i, p) in pixels.iter().enumerate() {
+            if i > 0 {
+                let prev = pixels[i - 1];
+                distance +=
+                    distance_between((prev.0 as f32, prev.1 as f32), (p.0 as f32, p.1 as f32));
+            }
+
+            if self.is_on(distance) {
+                result.push(*p);
+            }
+        }
+
+        result
+    }
+
+    /// splits a path into the pieces covered by the dashes. Each piece is drawn as an open path, so
+    /// each dash gets its own caps and keeps the joins of the corners inside it. A dash of length 0
+    /// is a piece with a single point, with round or square caps that is a dot
+    pub fn split(&self, points: &[PointFloat], closed: bool) -> Vec<Vec<PointFloat>> {
+        let mut path = points.to_vec();
+        if closed && !path.is_empty() {
+            path.push(path[0]);
+        }
+
+        let (mut index, mut left) = self.entry_at(0.0);
+        let starts_on = index.is_multiple_of(2);
+        let mut pieces = vec![];
+        let mut current = match (starts_on, path.first()) {
+            (true, Some(first)) => vec![*first],
+            _ => vec![],
+        };
+
+        for w in path.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let length = distance_between(a, b);
+            let mut position = 0.0;
+
+            // every length of the pattern that ends inside this segment
+            while length - position > left {
+                position += left;
+                let t = position / length;
+                let p = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+
+                if index.is_multiple_of(2) {
+                    current.push(p);
+                    pieces.push(std::mem::take(&mut current));
+                } else {
+                    current = vec![p];
+                }
+
+                index = (index + 1) % self.lengths.len();
+                left = self.lengths[index];
+            }
+
+            left -= length - position;
+            if index.is_multiple_of(2) {
+                current.push(b);
+            }
+        }
+
+        let ends_on = !current.is_empty();
+        if ends_on {
+            pieces.push(current);
+        }
+
+        // on a closed path a dash going over the start is split in two pieces, we join them back
+        if closed && starts_on && ends_on && pieces.len() > 1 {
+            let last = pieces.pop().unwrap();
+            let first = std::mem::replace(&mut pieces[0], last);
+            pieces[0].extend(first.into_iter().skip(1));
+        }
+
+        pieces
+    }
+}
+
+/// distance between two points
+fn distance_between(a: PointFloat, b: PointFloat) -> f32 {
+    ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt()
+}
@@ -1,7 +1,15 @@
+use std::f32::consts::PI;
+
 use crate::canvas::Canvas;
-use crate::core::{Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
+use crate::core::{ArcKind, Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
 
-use super::line::line_hit_test;
+use super::antialias::{add_coverage, draw_coverage, region_coverage, stroke_coverage, Coverage};
+use super::dash::DashPattern;
+use super::ellipse_arc::{
+    draw_ellipse_arc, ellipse_arc_handles, ellipse_arc_hit_test, rotate, EllipseArc,
+};
+use super::line::{antialiased_border, line_hit_test};
+use super::stroke::{draw_stroke, sample, stroke_path, stroke_polyline, PointFloat};
 
 const HIT_TEST_ERROR: i64 = 5;
 
@@ -18,8 +26,33 @@ impl ShapeImpl for Ellipse {
         &mut self.core
     }
 
+    /// the control points of a rotated ellipse or a part of an ellipse are not the points of the
+    /// core (see control_points), moving them changes the points or the angles of the arc
     fn update(&mut self, op: &UpdateOp) {
-        self.update_basic(op);
+        let (center, a, b) = get_ellipse(&self.core);
+        match op {
+            // the ends of the arc
+            UpdateOp::ControlPoint(index @ (2 | 3), p) if self.core.arc != ArcKind::Full => {
+                let arc = EllipseArc::new(&self.core, center, a, b);
+                let angle = arc.angle_of((*p).into()).to_degrees();
+                if *index == 2 {
+                    self.core.start_angle = angle;
+                } else {
+                    self.core.end_angle = angle;
+                }
+            }
+            // a corner of a rotated ellipse. The corner on the other side stays where it is on
+            // the screen, and both are rotated back around the new center
+            UpdateOp::ControlPoint(index @ (0 | 1), p) if !is_plain(&self.core) => {
+                let other = self.control_points()[1 - index];
+                let new_center = ((p.0 + other.0) as f32 / 2.0, (p.1 + other.1) as f32 / 2.0);
+                let rotation = self.core.rotation.to_radians();
+                let (cos, sin) = (rotation.cos(), -rotation.sin());
+                self.core.points[*index] = rotate((*p).into(), new_center, cos, sin).into();
+                self.core.points[1 - index] = rotate(other.into(), new_center, cos, sin).into();
+            }
+            _ => self.update_basic(op),
+        }
     }
 
     fn get_core(&self) -> ShapeCore {
@@ -34,12 +67,25 @@ impl ShapeImpl for Ellipse {
         draw_ellipse(&self.core.copy_with_color(color), canvas);
     }
 
+    fn control_points(&self) -> Vec<Point> {
+        if is_plain(&self.core) {
+            return self.core.points.clone();
+        }
+        let (center, a, b) = get_ellipse(&self.core);
+        ellipse_arc_handles(&self.core, center, a, b)
+    }
+
     /// hit test for ellipse uses ONLY integer arithmetic
     /// we just use the same formula to know if a point is within the ellipse
     /// the formula is dx*dx / (a*a) + dy * dy / (b*b) <= 1. After some simple algebra manipulation
     /// we get the formula bellow. We just multiply both sides by a^2 * b^2
     fn hit_test(&self, point: Point) -> bool {
         let (center, a, b) = get_ellipse(&self.core);
+        if !is_plain(&self.core) {
+            let error = HIT_TEST_ERROR as f32;
+            return ellipse_arc_hit_test(&self.core, center, a, b, error, point);
+        }
+
         // special case where the ellipse is completelly flat
         if a == 0 || b == 0 {
             return line_hit_test(&self.core, point);
@@ -48,13 +94,15 @@ impl ShapeImpl for Ellipse {
         // basically if its transparent we check if its within a bigger ellipse and outside a
         // smaller ellipse, that behaves like we are checking if the border is being clicked with a
         // small error (intentional to avoid the case where we cant click because we cant hit the
-        // line exact pixels)
-        return if self.core.fill_color.is_transparent() {
-            is_within_ellipse(center, a + HIT_TEST_ERROR, b + HIT_TEST_ERROR, point)
-                && !is_within_ellipse(center, a - HIT_TEST_ERROR, b - HIT_TEST_ERROR, point)
+        // line exact pixels). Half of the border is outside the ellipse and half inside
+        let half_width = self.core.stroke_width as i64 / 2;
+        let error = HIT_TEST_ERROR + half_width;
+        if self.core.fill_color.is_transparent() {
+            is_within_ellipse(center, a + error, b + error, point)
+                && !is_within_ellipse(center, a - error, b - error, point)
         } else {
-            is_within_ellipse(center, a, b, point)
-        };
+            is_within_ellipse(center, a + half_width, b + half_width, point)
+        }
     }
 }
 
@@ -69,11 +117,60 @@ fn is_within_ellipse(center: Point, a: i64, b: i64, point: Point) -> bool {
     position_calc <= 0
 }
 
-/// we draw an ellipse using an integer only algorithm. Same as the one used on homework 1 with the
-/// optimizations included.
+/// checks if the ellipse is the whole ellipse without rotation, the one drawn with the integer
+/// algorithm. The others are drawn by ellipse_arc.rs
+fn is_plain(core: &ShapeCore) -> bool {
+    core.arc == ArcKind::Full && core.rotation.rem_euclid(360.0) == 0.0
+}
+
+/// we draw an ellipse using an integer only algorithm (see midpoint_ellipse). The border pixels
+/// are collected while the fill is drawn, and drawn at the end, that way we can sort them around
+/// the ellipse to apply the dashes
 fn draw_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
     let (center, a, b) = get_ellipse(core);
 
+    if !is_plain(core) {
+        let midpoint_ring = || midpoint_ellipse(center, a, b, |_, _, _| {});
+        draw_ellipse_arc(core, center, a, b, midpoint_ring, canvas);
+        return;
+    }
+
+    if core.antialias {
+        draw_antialiased_ellipse(core, canvas);
+        return;
+    }
+
+    if core.stroke_width > 1 {
+        draw_thick_ellipse(core, canvas);
+        return;
+    }
+
+    let draw_fill = !core.fill_color.is_transparent();
+    let mut border = midpoint_ellipse(center, a, b, |x_start, x_end, y| {
+        if draw_fill {
+            draw_fill_line(canvas, center, x_start, x_end, y, core.fill_color);
+        }
+    });
+
+    if let Some(dash) = DashPattern::new(core) {
+        sort_around_ellipse(&mut border, center, a, b);
+        border = dash.filter_pixels(&border);
+    }
+
+    for (x, y) in border {
+        canvas.set_pixel(x, y, core.color);
+    }
+}
+
+/// the integer midpoint algorithm of the ellipse. Same as the one used on homework 1 with the
+/// optimizations included. Returns the pixels of the border, and calls fill_row with the pixels
+/// inside on each different "y" (from x_start to x_end, on the rows center + y and center - y)
+pub(super) fn midpoint_ellipse(
+    center: Point,
+    a: i64,
+    b: i64,
+    mut fill_row: impl FnMut(i32, i32, i32),
+) -> Vec<(i32, i32)> {
     let mut x: i64 = 0;
     let mut y: i64 = b;
 
@@ -84,22 +181,13 @@ fn draw_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
     let sum_mx: i64 = 8 * b * b;
     let sum_my: i64 = 8 * a * a;
     let const_d1: i64 = (4 * b * b) + (4 * a * a);
-    let draw_fill = !core.fill_color.is_transparent();
+    let mut border = vec![];
 
-    draw_symmetric(canvas, center, x, y, core.color);
+    add_symmetric(&mut border, center, x, y);
 
     // here we added an extra condition that draws the inside of the ellipse.
     // it should only be used on each different "y"
-    if draw_fill {
-        draw_fill_line(
-            canvas,
-            center,
-            center.0 - x as i32 + 1,
-            center.0 + x as i32 - 1,
-            y as i32,
-            core.fill_color,
-        );
-    }
+    fill_row(center.0 - x as i32 + 1, center.0 + x as i32 - 1, y as i32);
 
     while m_x < m_y {
         if d < 0 {
@@ -108,24 +196,19 @@ fn draw_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
             d += m_x - m_y + const_d1;
             y -= 1;
             m_y -= sum_my;
-            if draw_fill {
-                draw_fill_line(
-                    canvas,
-                    center,
-                    center.0 - (x + 1) as i32 + 1,
-                    center.0 + (x + 1) as i32 - 1,
-                    y as i32,
-                    core.fill_color,
-                );
-            }
+            fill_row(
+                center.0 - (x + 1) as i32 + 1,
+                center.0 + (x + 1) as i32 - 1,
+                y as i32,
+            );
         }
         x += 1;
         m_x += sum_mx;
-        draw_symmetric(canvas, center, x, y, core.color);
+        add_symmetric(&mut border, center, x, y);
     }
 
     if y <= 0 {
-        draw_edge_case(canvas, center, a as i32, x as i32, core.color);
+        add_edge_case(&mut border, center, a as i32, x as i32);
     }
 
     let aux2 = (8 * a * a) + (4 * b * b);
@@ -146,20 +229,205 @@ fn draw_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
 
         y -= 1;
         m_y -= sum_my;
-        if draw_fill {
-            draw_fill_line(
-                canvas,
-                center,
-                center.0 - x as i32 + 1,
-                center.0 + x as i32 - 1,
-                y as i32,
-                core.fill_color,
-            );
+        fill_row(center.0 - x as i32 + 1, center.0 + x as i32 - 1, y as i32);
+        add_symmetric(&mut border, center, x, y);
+    }
+
+    border
+}
+
+/// sorts the pixels of the border of an ellipse by their angle around the center, so they are in
+/// the order a dash pattern goes over them. The angle is the one the pixel would have if the
+/// ellipse was a circle, that way pixels on the flatter parts dont get bunched together. A flat
+/// ellipse is a line, we just go from one end to the other
+fn sort_around_ellipse(pixels: &mut [(i32, i32)], center: Point, a: i64, b: i64) {
+    if a == 0 || b == 0 {
+        pixels.sort();
+        return;
+    }
+
+    let angle = |(x, y): (i32, i32)| {
+        let (dx, dy) = ((x - center.0) as f32, (y - center.1) as f32);
+        (dy * a as f32).atan2(dx * b as f32)
+    };
+    pixels.sort_by(|p, q| angle(*p).total_cmp(&angle(*q)));
+}
+
+/// draws an ellipse with a border wider than 1 pixel. The border is the ring between an ellipse
+/// half the width bigger and another one half the width smaller. We go over the box of the bigger
+/// one and decide for each pixel if its part of the border, the inside or nothing, so each pixel is
+/// drawn once
+fn draw_thick_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
+    let (center, a, b) = get_ellipse(core);
+    let half_width = core.stroke_width as f32 / 2.0;
+    let outer = (a as f32 + half_width, b as f32 + half_width);
+    let inner = (a as f32 - half_width, b as f32 - half_width);
+    let draw_fill = !core.fill_color.is_transparent();
+
+    // a flat ellipse is a line, like on the hit test
+    if a == 0 || b == 0 {
+        let (a, b) = (a as i32, b as i32);
+        let ends = [
+            Point(center.0 - a, center.1 - b),
+            Point(center.0 + a, center.1 + b),
+        ];
+        draw_stroke(&stroke_polyline(&ends, core, false), core.color, canvas);
+        return;
+    }
+
+    if DashPattern::new(core).is_some() {
+        draw_dashed_thick_ellipse(core, canvas);
+        return;
+    }
+
+    let (max_x, max_y) = (outer.0.ceil() as i32, outer.1.ceil() as i32);
+    for y in -max_y..=max_y {
+        for x in -max_x..=max_x {
+            let p = sample(x, y);
+            if !is_within_ellipse_float(outer, p) {
+                continue;
+            }
+
+            if !is_within_ellipse_float(inner, p) {
+                canvas.set_pixel(center.0 + x, center.1 + y, core.color);
+            } else if draw_fill {
+                canvas.set_pixel(center.0 + x, center.1 + y, core.fill_color);
+            }
+        }
+    }
+}
+
+/// a dashed border is not a ring anymore, we flatten the ellipse into a closed path and draw it
+/// as a stroke so each dash gets its caps. The fill goes until the middle of the border like on
+/// svg, so it shows on the gaps
+fn draw_dashed_thick_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
+    let (center, a, b) = get_ellipse(core);
+    let (a, b) = (a as f32, b as f32);
+
+    let border = stroke_path(&ellipse_path(center.into(), a, b), core, true);
+    draw_stroke(&border, core.color, canvas);
+
+    if core.fill_color.is_transparent() {
+        return;
+    }
+
+    let (max_x, max_y) = (a.ceil() as i32, b.ceil() as i32);
+    for y in -max_y..=max_y {
+        for x in -max_x..=max_x {
+            let pixel = (center.0 + x, center.1 + y);
+            if is_within_ellipse_float((a, b), sample(x, y)) && !border.contains(&pixel) {
+                canvas.set_pixel(pixel.0, pixel.1, core.fill_color);
+            }
         }
-        draw_symmetric(canvas, center, x, y, core.color);
     }
 }
 
+/// flattens the ellipse into a closed path
+fn ellipse_path(center: PointFloat, a: f32, b: f32) -> Vec<PointFloat> {
+    // ramanujan's approximation of the perimeter, one point every 2 pixels is enough to not
+    // notice the segments
+    let perimeter = PI * (3.0 * (a + b) - ((3.0 * a + b) * (a + 3.0 * b)).sqrt());
+    let n = ((perimeter / 2.0) as usize).max(16);
+    (0..n)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / n as f32;
+            (center.0 + a * angle.cos(), center.1 + b * angle.sin())
+        })
+        .collect()
+}
+
+/// draws an anti-aliased ellipse (see antialias.rs). A solid border of 1 pixel uses wu_ellipse,
+/// wider ones are the ring between two ellipses like draw_thick_ellipse but sampled, and dashed
+/// ones are a sampled stroke. The fill goes until the middle of the border
+fn draw_antialiased_ellipse(core: &ShapeCore, canvas: &mut Canvas) {
+    let (center, a, b) = get_ellipse(core);
+    let c: PointFloat = center.into();
+    let (af, bf) = (a as f32, b as f32);
+
+    // a flat ellipse is a line, like on the hit test
+    if a == 0 || b == 0 {
+        let ends = [(c.0 - af, c.1 - bf), (c.0 + af, c.1 + bf)];
+        let border = antialiased_border(&ends, core, false);
+        draw_coverage(
+            &border,
+            &Coverage::new(),
+            core.color,
+            core.fill_color,
+            canvas,
+        );
+        return;
+    }
+
+    let (a, b) = (a as i32, b as i32);
+    let border = if DashPattern::new(core).is_some() {
+        stroke_coverage(&ellipse_path(c, af, bf), core, true)
+    } else if core.stroke_width > 1 {
+        let half_width = core.stroke_width as f32 / 2.0;
+        let outer = (af + half_width, bf + half_width);
+        let inner = (af - half_width, bf - half_width);
+        let (max_x, max_y) = (outer.0.ceil() as i32, outer.1.ceil() as i32);
+        region_coverage(
+            (center.0 - max_x, center.1 - max_y),
+            (center.0 + max_x, center.1 + max_y),
+            |(x, y)| {
+                let p = (x - c.0, y - c.1);
+                is_within_ellipse_float(outer, p) && !is_within_ellipse_float(inner, p)
+            },
+        )
+    } else {
+        wu_ellipse(center, af, bf)
+    };
+
+    let fill = if core.fill_color.is_transparent() {
+        Coverage::new()
+    } else {
+        region_coverage(
+            (center.0 - a, center.1 - b),
+            (center.0 + a, center.1 + b),
+            |(x, y)| is_within_ellipse_float((af, bf), (x - c.0, y - c.1)),
+        )
+    };
+
+    draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+}
+
+/// xiaolin wu algorithm for ellipses. Where the border is closer to horizontal we walk over x and
+/// split each column between the two pixels around the exact y, like wu_line. Where its closer to
+/// vertical we do the same walking over y. Each step gives the 4 symmetric pixels
+fn wu_ellipse(center: Point, a: f32, b: f32) -> Coverage {
+    let mut coverage = Coverage::new();
+    let mut plot = |x: i32, y: i32, value: f32| {
+        for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+            add_coverage(&mut coverage, center.0 + sx * x, center.1 + sy * y, value);
+        }
+    };
+
+    // the border is at 45 degrees on (a^2, b^2) / sqrt(a^2 + b^2)
+    let diagonal = (a * a + b * b).sqrt();
+
+    for x in 0..=(a * a / diagonal) as i32 {
+        let y = b * (1.0 - (x as f32 / a).powi(2)).max(0.0).sqrt();
+        let fraction = y - y.floor();
+        plot(x, y.floor() as i32, 1.0 - fraction);
+        plot(x, y.floor() as i32 + 1, fraction);
+    }
+
+    for y in 0..=(b * b / diagonal) as i32 {
+        let x = a * (1.0 - (y as f32 / b).powi(2)).max(0.0).sqrt();
+        let fraction = x - x.floor();
+        plot(x.floor() as i32, y, 1.0 - fraction);
+        plot(x.floor() as i32 + 1, y, fraction);
+    }
+
+    coverage
+}
+
+/// same as is_within_ellipse for an ellipse centered on the origin, but with floats. An ellipse
+/// without width or height has nothing inside
+fn is_within_ellipse_float((a, b): (f32, f32), (x, y): (f32, f32)) -> bool {
+    a > 0.0 && b > 0.0 && (x * x) / (a * a) + (y * y) / (b * b) <= 1.0
+}
+
 /// this just draws a line from center-x+1 to center+x-1.
 /// its always an horizontal line
 fn draw_fill_line(
@@ -179,41 +447,42 @@ fn draw_fill_line(
     }
 }
 
-/// draws 4 points symetric given the first one on the first quadrant
-/// we must check that we are not drawing the same points again so we have conditions on
+/// adds 4 points symetric given the first one on the first quadrant
+/// we must check that we are not adding the same points again so we have conditions on
 /// each quadrant
-fn draw_symmetric(canvas: &mut Canvas, center: Point, x: i64, y: i64, color: RGBA) {
+fn add_symmetric(pixels: &mut Vec<(i32, i32)>, center: Point, x: i64, y: i64) {
     let (_x, _y) = (x as i32, y as i32);
     let Point(cx, cy) = center;
 
-    canvas.set_pixel(cx + _x, cy + _y, color);
+    pixels.push((cx + _x, cy + _y));
 
     if _x != 0 {
-        canvas.set_pixel(cx - _x, cy + _y, color);
+        pixels.push((cx - _x, cy + _y));
     }
 
     if _y != 0 {
-        canvas.set_pixel(cx + _x, cy - _y, color);
+        pixels.push((cx + _x, cy - _y));
     }
 
     if _x != 0 && _y != 0 {
-        canvas.set_pixel(cx - _x, cy - _y, color);
+        pixels.push((cx - _x, cy - _y));
     }
 }
 
-/// draws the edge case when the ellipse is flat on the x axis. Done in homework 1 as well
-fn draw_edge_case(canvas: &mut Canvas, center: Point, a: i32, x_drawn: i32, color: RGBA) {
+/// adds the edge case when the ellipse is flat on the x axis. Done in homework 1 as well
+fn add_edge_case(pixels: &mut Vec<(i32, i32)>, center: Point, a: i32, x_drawn: i32) {
     let mut x = center.0 - a;
     let end = center.0 - x_drawn;
     while x < end {
-        canvas.set_pixel(x, center.1, color);
+        pixels.push((x, center.1));
         x += 1;
     }
 
+    // the right side is inclusive, like the left one starts on center - a
     x = center.0 + x_drawn + 1;
     let end = center.0 + a;
-    while x < end {
-        canvas.set_pixel(x, center.1, color);
+    while x <= end {
+        pixels.push((x, center.1));
         x += 1;
     }
 }
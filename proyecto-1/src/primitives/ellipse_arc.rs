@@ -0,0 +1,402 @@
+use std::collections::HashSet;
+use std::f32::consts::PI;
+
+use crate::canvas::Canvas;
+use crate::core::{ArcKind, Point, ShapeCore};
+
+use super::antialias::{draw_coverage, region_coverage, Coverage};
+use super::dash::DashPattern;
+use super::freehand::distance_to_segment;
+use super::line::{
+    antialiased_border, draw_styled_line, line_hit_test, line_pixels, polyline_pixels,
+};
+use super::stroke::{draw_stroke, sample, stroke_path, PointFloat};
+
+/// rotated ellipses and the parts of an ellipse (arc, pie and chord). The full ellipse without
+/// rotation is drawn by ellipse.rs with the integer algorithm, everything else goes through here.
+///
+/// The angles are the ones a point would have if the ellipse was stretched into a circle (its
+/// parametric angle), that way the point at an angle is always (a * cos, b * sin) before rotating
+/// it and the pixels of the flat parts dont get bunched together
+pub(super) struct EllipseArc {
+    center: PointFloat,
+    a: f32,
+    b: f32,
+    /// cosine and sine of the rotation
+    cos: f32,
+    sin: f32,
+    arc: ArcKind,
+    /// where the arc starts, in radians from 0 to 2 pi
+    start: f32,
+    /// how much the arc goes around, in radians. More than 0 and up to 2 pi
+    sweep: f32,
+}
+
+impl EllipseArc {
+    /// center and radius of the ellipse, the ones used by the integer algorithm
+    pub(super) fn new(core: &ShapeCore, center: Point, a: i64, b: i64) -> Self {
+        let rotation = core.rotation.to_radians();
+        let start = core.start_angle.to_radians().rem_euclid(2.0 * PI);
+        let sweep = (core.end_angle - core.start_angle).rem_euclid(360.0);
+        // the same angle twice is the whole ellipse, the angles of a full ellipse dont matter
+        let sweep = if sweep == 0.0 || core.arc == ArcKind::Full {
+            360.0
+        } else {
+            sweep
+        };
+
+        EllipseArc {
+            center: center.into(),
+            a: a as f32,
+            b: b as f32,
+            cos: rotation.cos(),
+            sin: rotation.sin(),
+            arc: core.arc,
+            start,
+            sweep: sweep.to_radians(),
+        }
+    }
+
+    /// position of the point at the given angle
+    pub(super) fn point_at(&self, angle: f32) -> PointFloat {
+        let (x, y) = (self.a * angle.cos(), self.b * angle.sin());
+        (
+            self.center.0 + x * self.cos - y * self.sin,
+            self.center.1 + x * self.sin + y * self.cos,
+        )
+    }
+
+    /// the point relative to the center, without the rotation
+    fn to_local(&self, p: PointFloat) -> PointFloat {
+        let (dx, dy) = (p.0 - self.center.0, p.1 - self.center.1);
+        (dx * self.cos + dy * self.sin, dy * self.cos - dx * self.sin)
+    }
+
+    /// the angle of a point, from 0 to 2 pi
+    pub(super) fn angle_of(&self, p: PointFloat) -> f32 {
+        let (x, y) = self.to_local(p);
+        (y * self.a).atan2(x * self.b).rem_euclid(2.0 * PI)
+    }
+
+    /// ends of the arc
+    pub(super) fn ends(&self) -> (PointFloat, PointFloat) {
+        (
+            self.point_at(self.start),
+            self.point_at(self.start + self.sweep),
+        )
+    }
+
+    /// checks if the point is inside the whole ellipse
+    fn is_inside(&self, p: PointFloat) -> bool {
+        let (x, y) = self.to_local(p);
+        (x * x) / (self.a * self.a) + (y * y) / (self.b * self.b) <= 1.0
+    }
+
+    /// the angle of the point of the ellipse closest to p. For points close to a flat ellipse the
+    /// angle_of two pixels next to each other can be far apart, this one follows the border. It
+    /// starts from angle_of and moves towards the closest point with a few Newton steps on the
+    /// derivative of the distance
+    fn nearest_angle(&self, p: PointFloat) -> f32 {
+        let (x, y) = self.to_local(p);
+        let (a, b) = (self.a, self.b);
+        let mut t = self.angle_of(p);
+        for _ in 0..4 {
+            let (sin, cos) = t.sin_cos();
+            let f = (a * a - b * b) * sin * cos - x * a * sin + y * b * cos;
+            let df = (a * a - b * b) * (2.0 * t).cos() - x * a * cos - y * b * sin;
+            if df.abs() < 1e-6 {
+                break;
+            }
+            t -= f / df;
+        }
+        t.rem_euclid(2.0 * PI)
+    }
+
+    /// checks if the angle is between the start and the end of the arc
+    fn in_sweep(&self, angle: f32) -> bool {
+        (angle - self.start).rem_euclid(2.0 * PI) <= self.sweep + 1e-4
+    }
+
+    /// checks if the point is on the part of the ellipse that is filled: between both lines of
+    /// the pie, or on the side of the chord where the arc is
+    fn in_part(&self, p: PointFloat) -> bool {
+        match self.arc {
+            ArcKind::Full | ArcKind::Arc => true,
+            ArcKind::Pie => self.in_sweep(self.angle_of(p)),
+            ArcKind::Chord => {
+                let (s, e) = self.ends();
+                let middle = self.point_at(self.start + self.sweep / 2.0);
+                let side = |q: PointFloat| (e.0 - s.0) * (q.1 - s.1) - (e.1 - s.1) * (q.0 - s.0);
+                side(p) * side(middle) >= 0.0
+            }
+        }
+    }
+
+    /// checks if the point is inside the filled part
+    fn is_filled_at(&self, p: PointFloat) -> bool {
+        self.arc != ArcKind::Arc && self.is_inside(p) && self.in_part(p)
+    }
+
+    /// flattens the border into a path, and returns if its closed. The arc is open, the pie goes
+    /// through the center and the chord is closed by the line between the ends
+    fn outline(&self) -> (Vec<PointFloat>, bool) {
+        // ramanujan's approximation of the perimeter, one point every 2 pixels
+        let (a, b) = (self.a, self.b);
+        let perimeter = PI * (3.0 * (a + b) - ((3.0 * a + b) * (a + 3.0 * b)).sqrt());
+        let n = ((perimeter * self.sweep / (4.0 * PI)) as usize).max(8);
+        let arc = (0..=n).map(|i| self.point_at(self.start + self.sweep * i as f32 / n as f32));
+
+        match self.arc {
+            ArcKind::Full => (arc.take(n).collect(), true),
+            ArcKind::Arc => (arc.collect(), false),
+            ArcKind::Pie => (std::iter::once(self.center).chain(arc).collect(), true),
+            ArcKind::Chord => (arc.collect(), true),
+        }
+    }
+
+    /// half the amount of rows of spans
+    fn radius(&self) -> i32 {
+        self.a.max(self.b).ceil() as i32
+    }
+
+    /// the first and last pixel of each row from -radius to radius (relative to the center) whose
+    /// point moved by offset is inside the ellipse, or None if the row has none. On each row we
+    /// solve x^2 * qa + x * qb + qc <= 0, the equation of the rotated ellipse
+    fn spans(&self, offset: PointFloat) -> Vec<Option<(i32, i32)>> {
+        let (a2, b2) = (self.a * self.a, self.b * self.b);
+        let (c, s) = (self.cos, self.sin);
+        let qa = c * c / a2 + s * s / b2;
+        let radius = self.radius();
+
+        (-radius..=radius)
+            .map(|y| {
+                let y = y as f32 + offset.1;
+                let qb = 2.0 * y * c * s * (1.0 / a2 - 1.0 / b2);
+                let qc = y * y * (s * s / a2 + c * c / b2) - 1.0;
+                let discriminant = qb * qb - 4.0 * qa * qc;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let root = discriminant.sqrt();
+                let start = ((-qb - root) / (2.0 * qa) - offset.0).ceil() as i32;
+                let end = ((-qb + root) / (2.0 * qa) - offset.0).floor() as i32;
+                (start <= end).then_some((start, end))
+            })
+            .collect()
+    }
+
+    /// the border of the rotated ellipse. On each row we solve the equation of the ellipse to get
+    /// the pixels whose center is inside (see spans), like the decision variable of the midpoint
+    /// algorithm checks the sign of the equation between two pixels. The border are the pixels of
+    /// each row that are missing a pixel of the region above or below them, except the last one
+    /// before the next row starts (its diagonal is enough), so its 8-connected without extra
+    /// corners
+    fn ring(&self, center: Point) -> Vec<(i32, i32)> {
+        let radius = self.radius();
+        let spans = self.spans((0.0, 0.0));
+
+        let mut ring = vec![];
+        for (i, span) in spans.iter().enumerate() {
+            let Some((start, end)) = *span else {
+                continue;
+            };
+            let y = center.1 + i as i32 - radius;
+            let neighbours = [i.checked_sub(1), Some(i + 1)]
+                .map(|j| j.and_then(|j| spans.get(j).copied().flatten()));
+
+            // the pixels on the left until the pixel next to where both rows around start, the
+            // same on the right
+            let (left_end, right_start) = match neighbours {
+                [Some(up), Some(down)] => (
+                    (up.0.max(down.0) - 1).clamp(start, end),
+                    (up.1.min(down.1) + 1).clamp(start, end),
+                ),
+                _ => (end, end + 1),
+            };
+
+            for x in (start..=left_end).chain((right_start.max(left_end + 1))..=end) {
+                ring.push((center.0 + x, y));
+            }
+        }
+
+        ring
+    }
+}
+
+/// draws a rotated ellipse or a part of an ellipse (see EllipseArc). The solid 1 pixel border uses
+/// the pixels of the ellipse, the integer midpoint ones if its not rotated, and the lines of the
+/// pie or chord. Wide, dashed and anti-aliased borders flatten the border into a path. The fill is
+/// every pixel inside that is not part of the border
+pub(super) fn draw_ellipse_arc(
+    core: &ShapeCore,
+    center: Point,
+    a: i64,
+    b: i64,
+    midpoint_ring: impl FnOnce() -> Vec<(i32, i32)>,
+    canvas: &mut Canvas,
+) {
+    let ellipse = EllipseArc::new(core, center, a, b);
+
+    // a flat ellipse is a line, like on the hit test
+    if a == 0 || b == 0 {
+        let ends = flat_ends(&ellipse);
+        draw_styled_line(&core.copy_with_points(ends.to_vec()), canvas);
+        return;
+    }
+
+    if core.antialias {
+        let (path, closed) = ellipse.outline();
+        let border = antialiased_border(&path, core, closed);
+        let fill = if core.fill_color.is_transparent() {
+            Coverage::new()
+        } else {
+            let r = a.max(b) as i32 + 1;
+            region_coverage(
+                (center.0 - r, center.1 - r),
+                (center.0 + r, center.1 + r),
+                |p| ellipse.is_filled_at(p),
+            )
+        };
+        draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+        return;
+    }
+
+    let dash = DashPattern::new(core);
+    let border: HashSet<(i32, i32)> = if core.stroke_width > 1 || dash.is_some() {
+        let (path, closed) = ellipse.outline();
+        if core.stroke_width > 1 {
+            let border = stroke_path(&path, core, closed);
+            draw_stroke(&border, core.color, canvas);
+            border
+        } else {
+            // like the polygon, the whole border is used to know where not to fill
+            let points: Vec<Point> = path.into_iter().map(Point::from).collect();
+            let pixels = polyline_pixels(&points, closed);
+            for (x, y) in dash.unwrap().filter_pixels(&pixels) {
+                canvas.set_pixel(x, y, core.color);
+            }
+            pixels.into_iter().collect()
+        }
+    } else {
+        let ring = if core.rotation.rem_euclid(360.0) == 0.0 {
+            midpoint_ring()
+        } else {
+            ellipse.ring(center)
+        };
+        let border = arc_pixels(&ellipse, &ring, center);
+        for (x, y) in border.iter() {
+            canvas.set_pixel(*x, *y, core.color);
+        }
+        border.into_iter().collect()
+    };
+
+    if core.fill_color.is_transparent() || core.arc == ArcKind::Arc {
+        return;
+    }
+
+    // only the pixels of the rows inside the ellipse, and only the ones on the canvas. The spans
+    // are moved like the samples so they are the same pixels is_filled_at would find
+    let radius = ellipse.radius();
+    let (width, height) = (canvas.width() as i32, canvas.height() as i32);
+    for (i, span) in ellipse.spans(sample(0, 0)).into_iter().enumerate() {
+        let y = center.1 + i as i32 - radius;
+        let Some((start, end)) = span.filter(|_| (0..height).contains(&y)) else {
+            continue;
+        };
+        let (start, end) = ((center.0 + start).max(0), (center.0 + end).min(width - 1));
+        for x in start..=end {
+            if ellipse.in_part(sample(x, y)) && !border.contains(&(x, y)) {
+                canvas.set_pixel(x, y, core.fill_color);
+            }
+        }
+    }
+}
+
+/// the pixels of the 1 pixel border: the ones of the ring on the arc and the lines that close the
+/// pie or the chord, without repeating any of them
+fn arc_pixels(ellipse: &EllipseArc, ring: &[(i32, i32)], center: Point) -> Vec<(i32, i32)> {
+    let mut pixels: Vec<(i32, i32)> = ring
+        .iter()
+        .filter(|(x, y)| {
+            let angle = ellipse.nearest_angle((*x as f32, *y as f32));
+            ellipse.arc == ArcKind::Full || ellipse.in_sweep(angle)
+        })
+        .copied()
+        .collect();
+
+    let (start, end) = ellipse.ends();
+    let (start, end) = (Point::from(start), Point::from(end));
+    match ellipse.arc {
+        ArcKind::Pie => {
+            pixels.extend(line_pixels(center, start, true));
+            pixels.extend(line_pixels(center, end, true));
+        }
+        ArcKind::Chord => pixels.extend(line_pixels(start, end, true)),
+        _ => {}
+    }
+
+    let mut seen = HashSet::new();
+    pixels.retain(|p| seen.insert(*p));
+    pixels
+}
+
+/// ends of an ellipse without width or height, its drawn as the line between them
+fn flat_ends(ellipse: &EllipseArc) -> [Point; 2] {
+    [ellipse.point_at(PI).into(), ellipse.point_at(0.0).into()]
+}
+
+/// hit test of a rotated ellipse or part of an ellipse. Its hit close to the border, or inside
+/// the filled part if its filled
+pub(super) fn ellipse_arc_hit_test(
+    core: &ShapeCore,
+    center: Point,
+    a: i64,
+    b: i64,
+    error: f32,
+    point: Point,
+) -> bool {
+    let ellipse = EllipseArc::new(core, center, a, b);
+    if a == 0 || b == 0 {
+        return line_hit_test(&core.copy_with_points(flat_ends(&ellipse).to_vec()), point);
+    }
+
+    let p: PointFloat = point.into();
+    if !core.fill_color.is_transparent() && ellipse.is_filled_at(p) {
+        return true;
+    }
+
+    let (path, closed) = ellipse.outline();
+    let threshold = error + core.stroke_width as f32 / 2.0;
+    let n = path.len();
+    let segments = if closed { n } else { n - 1 };
+    (0..segments).any(|i| distance_to_segment(p, path[i], path[(i + 1) % n]) <= threshold)
+}
+
+/// the points that can be dragged on a rotated ellipse or a part of an ellipse: both corners of
+/// the box of the ellipse (rotated with it) and the ends of the arc
+pub(super) fn ellipse_arc_handles(core: &ShapeCore, center: Point, a: i64, b: i64) -> Vec<Point> {
+    let ellipse = EllipseArc::new(core, center, a, b);
+    let c: PointFloat = center.into();
+    let mut handles: Vec<Point> = core
+        .points
+        .iter()
+        .map(|p| rotate((*p).into(), c, ellipse.cos, ellipse.sin).into())
+        .collect();
+
+    if core.arc != ArcKind::Full {
+        let (start, end) = ellipse.ends();
+        handles.push(start.into());
+        handles.push(end.into());
+    }
+
+    handles
+}
+
+/// rotates p around the center, given the cosine and sine of the angle
+pub(super) fn rotate(p: PointFloat, center: PointFloat, cos: f32, sin: f32) -> PointFloat {
+    let (dx, dy) = (p.0 - center.0, p.1 - center.1);
+    (
+        center.0 + dx * cos - dy * sin,
+        center.1 + dx * sin + dy * cos,
+    )
+}
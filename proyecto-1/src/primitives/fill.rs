@@ -0,0 +1,117 @@
+use crate::canvas::Canvas;
+use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
+use crate::headless::Frame;
+
+/// region painted with the bucket tool. Its not a geometric shape, its the pixels the flood fill
+/// found, stored as horizontal spans: each pair of points of the core is the first and last pixel
+/// of a span on the same row. The region is painted with the fill color
+pub struct Fill {
+    core: ShapeCore,
+}
+
+impl ShapeImpl for Fill {
+    fn new(core: ShapeCore) -> Fill {
+        Fill { core }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        self.draw_with_color(self.core.fill_color, canvas);
+    }
+
+    /// the fill has no border, so the whole region is drawn with the color. This way a selected
+    /// fill is drawn with the selection color like the border of other shapes
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        for span in self.core.points.chunks_exact(2) {
+            for x in span[0].0..=span[1].0 {
+                canvas.set_pixel(x, span[0].1, color);
+            }
+        }
+    }
+
+    /// the ends of the spans are not control points, moving one of them would break the region
+    fn control_points(&self) -> Vec<Point> {
+        vec![]
+    }
+
+    fn hit_test(&self, point: Point) -> bool {
+        self.core
+            .points
+            .chunks_exact(2)
+            .any(|span| span[0].1 == point.1 && span[0].0 <= point.0 && point.0 <= span[1].0)
+    }
+}
+
+/// returns the region of the frame connected to the seed whose colors are similar to the color of
+/// the seed, as the spans used by Fill. Two colors are similar if none of their channels (alpha
+/// included) differ by more than the tolerance. Empty if the seed is outside the frame.
+///
+/// This is the scanline flood fill: from a pixel of the region we go left and right until the
+/// color changes, that whole span is part of the region. Then the rows above and below the span are
+/// checked and every span found there that was not visited yet is filled the same way. Using spans
+/// instead of single pixels keeps the stack small
+pub fn flood_fill(frame: &Frame, seed: Point, tolerance: u8) -> Vec<Point> {
+    let (width, height) = (frame.width() as i32, frame.height() as i32);
+    if seed.0 < 0 || seed.1 < 0 || seed.0 >= width || seed.1 >= height {
+        return vec![];
+    }
+
+    let target = frame.get_pixel(seed.0 as u32, seed.1 as u32);
+    let similar = |x: i32, y: i32| {
+        let color = frame.get_pixel(x as u32, y as u32);
+        (0..4).all(|i| color[i].abs_diff(target[i]) <= tolerance)
+    };
+
+    let mut visited = vec![false; (width * height) as usize];
+    let index = |x: i32, y: i32| (y * width + x) as usize;
+    let mut spans: Vec<(i32, i32, i32)> = vec![];
+    let mut stack = vec![seed];
+
+    while let Some(Point(x, y)) = stack.pop() {
+        if visited[index(x, y)] {
+            continue;
+        }
+
+        let mut start = x;
+        while start > 0 && !visited[index(start - 1, y)] && similar(start - 1, y) {
+            start -= 1;
+        }
+        let mut end = x;
+        while end < width - 1 && !visited[index(end + 1, y)] && similar(end + 1, y) {
+            end += 1;
+        }
+
+        for i in start..=end {
+            visited[index(i, y)] = true;
+        }
+        spans.push((y, start, end));
+
+        // one seed for each run of similar pixels on the rows next to the span
+        for ny in [y - 1, y + 1] {
+            if ny < 0 || ny >= height {
+                continue;
+            }
+            let mut in_run = false;
+            for i in start..=end {
+                let inside = !visited[index(i, ny)] && similar(i, ny);
+                if inside && !in_run {
+                    stack.push(Point(i, ny));
+                }
+                in_run = inside;
+            }
+        }
+    }
+
+    spans.sort();
+    spans
+        .into_iter()
+        .flat_map(|(y, start, end)| [Point(start, y), Point(end, y)])
+        .collect()
+}
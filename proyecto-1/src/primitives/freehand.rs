@@ -0,0 +1,189 @@
+use super::antialias::{draw_coverage, Coverage};
+use super::dash::DashPattern;
+use super::line::{antialiased_border, line_hit_test, polyline_pixels};
+use super::stroke::{draw_stroke, stroke_path, PointFloat};
+use crate::canvas::Canvas;
+use crate::core::{Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
+
+/// how far (in pixels) the simplified path can be from the cursor path
+pub const FREEHAND_TOLERANCE: f32 = 1.5;
+
+/// freehand stroke. While drawing it gets a point for each cursor position, when its finished the
+/// path is simplified (see simplify_path) so only a few points are stored. The points are joined
+/// with lines, or with a curve that goes through them if the core is smooth
+pub struct Freehand {
+    core: ShapeCore,
+    // points of the path that is drawn, with the smoothing already applied. Like the lines of the
+    // bezier curve they are generated each time the shape changes
+    path: Vec<PointFloat>,
+}
+
+impl ShapeImpl for Freehand {
+    fn new(core: ShapeCore) -> Freehand {
+        let path = generate_path(&core);
+        Freehand { core, path }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn update(&mut self, op: &UpdateOp) {
+        self.update_basic(op);
+        self.path = generate_path(&self.core);
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        self.draw_with_color(self.core.color, canvas);
+    }
+
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        draw_path(&self.path, &self.core, color, canvas);
+    }
+
+    /// like the bezier curve, the hit test is done over the lines of the path
+    fn hit_test(&self, point: Point) -> bool {
+        path_hit_test(&self.path, &self.core, point)
+    }
+}
+
+/// draws an open path with the border of the core. The splines are drawn the same way
+pub(super) fn draw_path(path: &[PointFloat], core: &ShapeCore, color: RGBA, canvas: &mut Canvas) {
+    if core.antialias {
+        let border = antialiased_border(path, core, false);
+        draw_coverage(&border, &Coverage::new(), color, core.fill_color, canvas);
+    } else if core.stroke_width > 1 {
+        draw_stroke(&stroke_path(path, core, false), color, canvas);
+    } else {
+        let points: Vec<Point> = path.iter().map(|p| Point::from(*p)).collect();
+        let mut pixels = polyline_pixels(&points, false);
+        if let Some(dash) = DashPattern::new(core) {
+            pixels = dash.filter_pixels(&pixels);
+        }
+        for (x, y) in pixels {
+            canvas.set_pixel(x, y, color);
+        }
+    }
+}
+
+/// checks if the point is over any of the lines of an open path
+pub(super) fn path_hit_test(path: &[PointFloat], core: &ShapeCore, point: Point) -> bool {
+    let points: Vec<Point> = path.iter().map(|p| Point::from(*p)).collect();
+    points
+        .windows(2)
+        .map(|w| core.copy_with_points(w.to_vec()))
+        .any(|line| line_hit_test(&line, point))
+}
+
+/// returns the path that is drawn: the points of the core or the smooth curve through them
+fn generate_path(core: &ShapeCore) -> Vec<PointFloat> {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    if core.smooth {
+        smooth_path(&points)
+    } else {
+        points
+    }
+}
+
+/// simplifies a path with the Ramer-Douglas-Peucker algorithm. The point of the path furthest from
+/// the line between both ends is kept if its further than the tolerance, and both halves are
+/// simplified the same way. Otherwise every point between the ends is removed
+pub fn simplify_path(points: &[Point], tolerance: f32) -> Vec<Point> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+
+    // ranges left to simplify, we use a stack instead of recursion
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = ranges.pop() {
+        let (a, b) = (points[start].into(), points[end].into());
+        let furthest = (start + 1..end)
+            .map(|i| (i, distance_to_segment(points[i].into(), a, b)))
+            .max_by(|p, q| p.1.total_cmp(&q.1));
+
+        if let Some((i, distance)) = furthest {
+            if distance > tolerance {
+                keep[i] = true;
+                ranges.push((start, i));
+                ranges.push((i, end));
+            }
+        }
+    }
+
+    points
+        .into_iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(p, _)| p)
+        .collect()
+}
+
+/// distance from p to the segment ab. Used instead of the distance to the line so a path that
+/// comes back to its start is not simplified into a single point. The eraser also uses it
+pub(super) fn distance_to_segment(p: PointFloat, a: PointFloat, b: PointFloat) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sqr = dx * dx + dy * dy;
+    let t = if length_sqr == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sqr).clamp(0.0, 1.0)
+    };
+
+    let closest = (a.0 + dx * t, a.1 + dy * t);
+    ((p.0 - closest.0).powi(2) + (p.1 - closest.1).powi(2)).sqrt()
+}
+
+/// joins the points with a Catmull-Rom spline, a curve that goes through every point. Each piece
+/// between two points also uses the points before and after them to decide its direction, the
+/// ends use themselves as the missing neighbour. Each piece is sampled every 2 pixels or so
+pub(super) fn smooth_path(points: &[PointFloat]) -> Vec<PointFloat> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let n = points.len();
+    let mut path = vec![points[0]];
+
+    for i in 0..n - 1 {
+        let p0 = points[i.saturating_sub(1)];
+        let (p1, p2) = (points[i], points[i + 1]);
+        let p3 = points[(i + 2).min(n - 1)];
+
+        let length = ((p2.0 - p1.0).powi(2) + (p2.1 - p1.1).powi(2)).sqrt();
+        let steps = ((length / 2.0).ceil() as usize).max(1);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            path.push(catmull_rom(p0, p1, p2, p3, t));
+        }
+    }
+
+    path
+}
+
+/// point at t (from 0 to 1) of the Catmull-Rom piece that goes from p1 to p2
+pub(super) fn catmull_rom(
+    p0: PointFloat,
+    p1: PointFloat,
+    p2: PointFloat,
+    p3: PointFloat,
+    t: f32,
+) -> PointFloat {
+    let (t2, t3) = (t * t, t * t * t);
+    let blend = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b
+            + (c - a) * t
+            + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2
+            + (3.0 * b - a - 3.0 * c + d) * t3)
+    };
+    (blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1))
+}
@@ -0,0 +1,212 @@
+use std::io::{self, Cursor};
+
+use crate::canvas::Canvas;
+use crate::core::{ImageSampling, ImageSource, Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
+
+use super::line::draw_line;
+use super::rectangle::{get_box, Rectangle};
+
+/// the pixels of a decoded png, row by row from the top left corner
+struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<RGBA>,
+}
+
+impl Raster {
+    /// decodes a png of any color type and bit depth into 8 bit RGBA pixels
+    fn decode(source: &ImageSource) -> io::Result<Raster> {
+        let bytes = source.png_bytes()?;
+        let mut decoder = png::Decoder::new(Cursor::new(bytes));
+        // palettes are turned into colors and 16 bit channels into 8 bit ones
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer
+                .chunks_exact(4)
+                .map(|c| RGBA::new(c[0], c[1], c[2], c[3]))
+                .collect(),
+            png::ColorType::Rgb => buffer
+                .chunks_exact(3)
+                .map(|c| RGBA::new(c[0], c[1], c[2], 255))
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buffer
+                .chunks_exact(2)
+                .map(|c| RGBA::new(c[0], c[0], c[0], c[1]))
+                .collect(),
+            png::ColorType::Grayscale => {
+                buffer.iter().map(|g| RGBA::new(*g, *g, *g, 255)).collect()
+            }
+            png::ColorType::Indexed => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "palette was not expanded",
+                ))
+            }
+        };
+
+        Ok(Raster {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    fn get(&self, x: usize, y: usize) -> RGBA {
+        self.pixels[y * self.width + x]
+    }
+
+    /// the color at (u, v), where (0, 0) is the top left corner of the image and (1, 1) the
+    /// bottom right one
+    fn sample(&self, u: f32, v: f32, sampling: ImageSampling) -> RGBA {
+        // pixel i of the image goes from i to i + 1
+        let (x, y) = (u * self.width as f32, v * self.height as f32);
+        let clamp_x = |x: f32| (x.max(0.0) as usize).min(self.width - 1);
+        let clamp_y = |y: f32| (y.max(0.0) as usize).min(self.height - 1);
+
+        match sampling {
+            ImageSampling::Nearest => self.get(clamp_x(x), clamp_y(y)),
+            ImageSampling::Bilinear => {
+                // the 4 pixels with their center around (x, y)
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (left, top) = (x.floor(), y.floor());
+                let (tx, ty) = (x - left, y - top);
+                let corners = [
+                    (
+                        self.get(clamp_x(left), clamp_y(top)),
+                        (1.0 - tx) * (1.0 - ty),
+                    ),
+                    (self.get(clamp_x(left + 1.0), clamp_y(top)), tx * (1.0 - ty)),
+                    (self.get(clamp_x(left), clamp_y(top + 1.0)), (1.0 - tx) * ty),
+                    (self.get(clamp_x(left + 1.0), clamp_y(top + 1.0)), tx * ty),
+                ];
+                mix(&corners)
+            }
+        }
+    }
+}
+
+/// mixes colors with the given weights (that add up to 1). The colors are weighted by their alpha
+/// too, if not the color of a transparent pixel (usually black) would darken its neighbours
+fn mix(colors: &[(RGBA, f32)]) -> RGBA {
+    let mut sum = [0.0; 4];
+    for (color, weight) in colors {
+        let alpha = color[3] as f32 * weight;
+        for (channel, total) in sum.iter_mut().take(3).enumerate() {
+            *total += color[channel] as f32 * alpha;
+        }
+        sum[3] += alpha;
+    }
+
+    if sum[3] == 0.0 {
+        return RGBA::new(0, 0, 0, 0);
+    }
+    let channel = |i: usize| (sum[i] / sum[3]).round() as u8;
+    RGBA::new(channel(0), channel(1), channel(2), sum[3].round() as u8)
+}
+
+/// png image stretched to fill the box between both points. The image is flipped if the second
+/// point is to the left or above the first one
+pub struct Image {
+    core: ShapeCore,
+    /// the decoded png, None if it couldnt be read (like a file that was moved). Decoding it on
+    /// every frame would be too slow
+    raster: Option<Raster>,
+}
+
+impl Image {
+    fn decode(core: &ShapeCore) -> Option<Raster> {
+        core.image
+            .as_ref()
+            .and_then(|image| Raster::decode(image).ok())
+    }
+
+    /// draws the border of the box with the given color
+    fn draw_frame(&self, color: RGBA, canvas: &mut Canvas) {
+        let frame = ShapeCore {
+            fill_color: RGBA::new(0, 0, 0, 0),
+            stroke_width: 1,
+            dash_pattern: vec![],
+            corner_radii: [0; 4],
+            antialias: false,
+            ..self.core.copy_with_color(color)
+        };
+        Rectangle::new(frame).draw(canvas);
+    }
+}
+
+impl ShapeImpl for Image {
+    fn new(core: ShapeCore) -> Image {
+        let raster = Image::decode(&core);
+        Image { core, raster }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn update(&mut self, op: &UpdateOp) {
+        self.update_basic(op);
+        if let UpdateOp::ChangeImage(_) = op {
+            self.raster = Image::decode(&self.core);
+        }
+    }
+
+    /// each pixel of the box takes its color from the image at the same relative position, and
+    /// is painted over the drawing like any translucent color. An image that cant be read is
+    /// drawn as a crossed box
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        let Some(raster) = self.raster.as_ref() else {
+            self.draw_frame(self.core.color, canvas);
+            let (a, b) = (self.core.points[0], self.core.points[1]);
+            for diagonal in [[a, b], [Point(a.0, b.1), Point(b.0, a.1)]] {
+                draw_line(&self.core.copy_with_points(diagonal.to_vec()), canvas, true);
+            }
+            return;
+        };
+
+        let (first, second) = (self.core.points[0], self.core.points[1]);
+        let (min, max) = get_box(&self.core);
+        let width = (max.0 - min.0 + 1) as f32;
+        let height = (max.1 - min.1 + 1) as f32;
+
+        for y in min.1..=max.1 {
+            let mut v = ((y - min.1) as f32 + 0.5) / height;
+            if second.1 < first.1 {
+                v = 1.0 - v;
+            }
+            for x in min.0..=max.0 {
+                let mut u = ((x - min.0) as f32 + 0.5) / width;
+                if second.0 < first.0 {
+                    u = 1.0 - u;
+                }
+                let color = raster.sample(u, v, self.core.sampling);
+                if color[3] > 0 {
+                    canvas.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// the selected image is drawn as it is with a border of the color, painting all of it with
+    /// one color would hide what is being traced
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        self.draw(canvas);
+        self.draw_frame(color, canvas);
+    }
+
+    /// an image is hit anywhere inside its box, even on its transparent pixels
+    fn hit_test(&self, point: Point) -> bool {
+        let (min, max) = get_box(&self.core);
+        (min.0..=max.0).contains(&point.0) && (min.1..=max.1).contains(&point.1)
+    }
+}
@@ -1,18 +1,37 @@
+use std::collections::HashSet;
+
 use crate::canvas::Canvas;
 
-use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
+use crate::core::{Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
+
+use super::antialias::{draw_coverage, stroke_coverage, wu_polyline, Coverage};
+use super::dash::DashPattern;
+use super::stroke::{draw_stroke, stroke_polyline, PointFloat};
 
-const HIT_TEST_ERROR: u64 = 30;
+pub(super) const HIT_TEST_ERROR: u64 = 30;
 
 /// line object definition
 pub struct Line {
     core: ShapeCore,
+    /// where the line is cut by subdivide, like the bezier curve. 0 is the first point and 1 the
+    /// last one
+    subdivide_t: f32,
 }
 
 /// line object shape implementation
 impl ShapeImpl for Line {
     fn new(core: ShapeCore) -> Line {
-        Line { core }
+        Line {
+            core,
+            subdivide_t: 0.5,
+        }
+    }
+
+    fn update(&mut self, op: &UpdateOp) {
+        self.update_basic(op);
+        if let UpdateOp::UpdateSubdivide(t) = op {
+            self.subdivide_t = *t;
+        }
     }
 
     fn get_core_mut(&mut self) -> &mut ShapeCore {
@@ -24,29 +43,131 @@ impl ShapeImpl for Line {
     }
 
     fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
-        draw_line(&self.core, canvas, true);
+        draw_styled_line(&self.core, canvas);
     }
 
     fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
-        draw_line(&self.core.copy_with_color(color), canvas, true);
+        draw_styled_line(&self.core.copy_with_color(color), canvas);
     }
 
     fn hit_test(&self, point: Point) -> bool {
-        return line_hit_test(&self.core, point);
+        line_hit_test(&self.core, point)
+    }
+
+    /// a line is cut in two lines that share the point at subdivide_t. Used by the eraser
+    fn subdivide(&self) -> Option<(ShapeCore, ShapeCore)> {
+        let (a, b) = (self.core.points[0], self.core.points[1]);
+        let middle = a.interpolate(b, self.subdivide_t);
+
+        Some((
+            self.core.copy_with_points(vec![a, middle]),
+            self.core.copy_with_points(vec![middle, b]),
+        ))
     }
 }
 
-/// draws a line given a shape core. Used by other shapes
+/// draws a line with the width and dashes of the shape core. Lines of 1 pixel use draw_line (or
+/// its pixels with the dashes), wider ones are rasterized as a stroke (see stroke.rs). Except for
+/// the solid 1 pixel line, every case goes through all the points of the core
+pub fn draw_styled_line<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>) {
+    if core.antialias {
+        let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+        let border = antialiased_border(&points, core, false);
+        draw_coverage(
+            &border,
+            &Coverage::new(),
+            core.color,
+            core.fill_color,
+            canvas,
+        );
+    } else if core.stroke_width <= 1 {
+        match DashPattern::new(core) {
+            Some(dash) => draw_dashed_polyline(&core.points, false, &dash, core.color, canvas),
+            None => draw_line(core, canvas, true),
+        }
+    } else {
+        let pixels = stroke_polyline(&core.points, core, false);
+        draw_stroke(&pixels, core.color, canvas);
+    }
+}
+
+/// draws a line given a shape core. Used by other shapes. Its always 1 pixel wide
 /// draw first is used to NOT draw the first point, used for other shapes to avoid overlapping
 pub fn draw_line<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>, draw_first: bool) {
-    let points = &core.points;
-    let a = points[0];
-    let b = points[1];
+    bresenham(core.points[0], core.points[1], draw_first, |x, y| {
+        canvas.set_pixel(x, y, core.color)
+    });
+}
+
+/// returns the pixels of a 1 pixel line from a to b in order, the same ones draw_line draws
+pub fn line_pixels(a: Point, b: Point, draw_first: bool) -> Vec<(i32, i32)> {
+    let mut pixels = vec![];
+    bresenham(a, b, draw_first, |x, y| pixels.push((x, y)));
+    pixels
+}
+
+/// returns the pixels of a 1 pixel border going through every point, in order and without
+/// repeating any pixel. If closed, the last point is also joined with the first one
+pub fn polyline_pixels(points: &[Point], closed: bool) -> Vec<(i32, i32)> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    // with 2 points the closed border would go and come back over the same pixels
+    if points.len() > 2 && closed {
+        points.push(points[0]);
+    }
+
+    // lines meeting at a narrow angle can share pixels besides the corner (see
+    // draw_line_for_triangle), so we keep track of the pixels already added
+    let mut added = HashSet::new();
+    let mut pixels = vec![];
+    for (i, w) in points.windows(2).enumerate() {
+        for p in line_pixels(w[0], w[1], i == 0) {
+            if added.insert(p) {
+                pixels.push(p);
+            }
+        }
+    }
+
+    if let [single] = points[..] {
+        pixels.push((single.0, single.1));
+    }
+
+    pixels
+}
+
+/// draws a 1 pixel border going through every point with a dash pattern. The pattern continues
+/// from one segment to the next
+pub fn draw_dashed_polyline(
+    points: &[Point],
+    closed: bool,
+    dash: &DashPattern,
+    color: RGBA,
+    canvas: &mut Canvas,
+) {
+    for (x, y) in dash.filter_pixels(&polyline_pixels(points, closed)) {
+        canvas.set_pixel(x, y, color);
+    }
+}
+
+/// coverage of an anti-aliased border going through every point. Solid borders of 1 pixel use wu
+/// lines, the rest are sampled strokes (see antialias.rs)
+pub fn antialiased_border(points: &[PointFloat], core: &ShapeCore, closed: bool) -> Coverage {
+    if core.stroke_width <= 1 && DashPattern::new(core).is_none() {
+        wu_polyline(points, closed)
+    } else {
+        stroke_coverage(points, core, closed)
+    }
+}
 
-    let mut dx = (b.0 - a.0) as i32;
-    let mut dy = (b.1 - a.1) as i32;
-    let x_inc = if dx < 0 { -1 as i32 } else { 1 };
-    let y_inc = if dy < 0 { -1 as i32 } else { 1 };
+/// bresenham line algorithm from a to b, calls "plot" with each pixel in order
+fn bresenham(a: Point, b: Point, draw_first: bool, mut plot: impl FnMut(i32, i32)) {
+    let mut dx = b.0 - a.0;
+    let mut dy = b.1 - a.1;
+    let x_inc = if dx < 0 { -1 } else { 1 };
+    let y_inc = if dy < 0 { -1 } else { 1 };
 
     if dx < 0 {
         dx *= -1;
@@ -59,17 +180,23 @@ pub fn draw_line<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>, draw_first: bool
     let inc_e = -2 * (if run_on_x { dy } else { dx });
     let inc_ne = 2 * (dx - dy) * (if run_on_x { 1 } else { -1 });
 
-    let mut d = (dx - 2 * dy) * if run_on_x { 1 } else { -1 };
-    let mut x = a.0 as i32;
-    let mut y = a.1 as i32;
+    // the decision variable is the same for both axis, we only swap the roles of dx and dy
+    let mut d = if run_on_x { dx - 2 * dy } else { dy - 2 * dx };
+    // when d == 0 the line passes exactly between two pixels and both are valid. If we always pick
+    // the same one, the line from a to b and the one from b to a end up with different pixels.
+    // So we only step on a tie when moving forward on the main axis, that way the choice depends
+    // on the position of the pixels and not on the direction of the line
+    let step_on_tie = if run_on_x { x_inc > 0 } else { y_inc > 0 };
+    let mut x = a.0;
+    let mut y = a.1;
 
     if draw_first {
-        canvas.set_pixel(x, y, core.color);
+        plot(x, y);
     }
 
     if run_on_x {
-        while x > b.0 || x < b.0 {
-            if d <= 0 {
+        while x != b.0 {
+            if d < 0 || (d == 0 && step_on_tie) {
                 d += inc_ne;
                 y += y_inc;
             } else {
@@ -78,11 +205,11 @@ pub fn draw_line<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>, draw_first: bool
 
             x += x_inc;
 
-            canvas.set_pixel(x, y, core.color);
+            plot(x, y);
         }
     } else {
-        while y > b.1 || y < b.1 {
-            if d <= 0 {
+        while y != b.1 {
+            if d < 0 || (d == 0 && step_on_tie) {
                 d += inc_ne;
                 x += x_inc;
             } else {
@@ -91,29 +218,35 @@ pub fn draw_line<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>, draw_first: bool
 
             y += y_inc;
 
-            canvas.set_pixel(x, y, core.color);
+            plot(x, y);
         }
-
-        x += x_inc;
-        // edge case found. we want to draw a full line from a to b inclusive
-        // this ensures that b is drawn, when reaching this else condition it is not drawn
-        // This was tested to check if there are overlaps on the canvas
-        canvas.set_pixel(x, y, core.color);
     }
 }
 
-/// for the line hit test we just check if the given point is at certain distance from the line
-/// I use the vector to point formulation since it gives me the distance of a finite line
-/// (vector)
+/// for the line hit test we just check if the given point is at certain distance from the line.
+/// Wider lines can be clicked from further away
 pub fn line_hit_test(core: &ShapeCore, point: Point) -> bool {
     let p1 = core.points[0];
     let p2 = core.points[1];
+    let half_width = core.stroke_width / 2;
 
     if p1 == p2 {
         // if a line is a point then is impossible to select it.
         // What we do is create a box around this point of 10 pixels and check if the click is
         // within that box
-        return point.is_within_box(p1, p1, 10);
+        return point.is_within_box(p1, p1, 10 + half_width);
+    }
+
+    segment_hit_test(p1, p2, point, HIT_TEST_ERROR + half_width as u64)
+}
+
+/// checks if the point is at a distance lower than "error" from the segment p1 p2.
+/// I use the vector to point formulation since it gives me the distance of a finite line
+/// (vector)
+pub fn segment_hit_test(p1: Point, p2: Point, point: Point, error: u64) -> bool {
+    if p1 == p2 {
+        let d = point - p1;
+        return (d.dot(d) as i64) < (error * error) as i64;
     }
 
     let delta = p2 - p1;
@@ -126,11 +259,11 @@ pub fn line_hit_test(core: &ShapeCore, point: Point) -> bool {
     if n < 0 {
         // Closest point is p1
         let d = point - p1;
-        return (d.dot(d) as i64) < (HIT_TEST_ERROR * HIT_TEST_ERROR) as i64;
+        (d.dot(d) as i64) < (error * error) as i64
     } else if n > delta_sqr {
         // Closest point is p2
         let d = point - p2;
-        return (d.dot(d) as i64) < (HIT_TEST_ERROR * HIT_TEST_ERROR) as i64;
+        (d.dot(d) as i64) < (error * error) as i64
     } else {
         /* originally this should be:
         I changed this formula to use only integer arithmetic
@@ -158,6 +291,6 @@ pub fn line_hit_test(core: &ShapeCore, point: Point) -> bool {
 
         // final check: cross_sq / delta_sqr < error^2
         // multiply both sides by delta_sqr to keep it integer-based
-        return cross_sq < (HIT_TEST_ERROR * HIT_TEST_ERROR) as i64 * delta_sqr;
+        cross_sq < (error * error) as i64 * delta_sqr
     }
 }
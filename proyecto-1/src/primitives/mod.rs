@@ -2,27 +2,78 @@ use crate::core::{Shape, ShapeCore, ShapeImpl};
 
 // This is the primitives module. holds each shape
 
+mod antialias;
 mod bezier;
+mod dash;
 mod ellipse;
+mod ellipse_arc;
+mod fill;
+mod freehand;
+mod image;
 mod line;
+mod path;
+mod polygon;
+mod polyline;
 mod rectangle;
+mod rounded_rectangle;
+mod spline;
+mod stroke;
+mod text;
 mod triangle;
 
 // we only expose each shape directly for the module
 pub use bezier::Bezier;
 pub use ellipse::Ellipse;
+pub use fill::Fill;
+pub use freehand::Freehand;
+pub use image::Image;
 pub use line::Line;
+pub use path::Path;
+pub use polygon::Polygon;
+pub use polyline::Polyline;
 pub use rectangle::Rectangle;
+pub use spline::Spline;
+pub use text::Text;
 pub use triangle::Triangle;
 
+// the line algorithms are also exposed since other parts of the app (and the tests) use them
+pub use line::draw_line;
+pub use triangle::draw_line_for_triangle;
+
+// the freehand tool simplifies the cursor path before storing it
+pub use freehand::{simplify_path, FREEHAND_TOLERANCE};
+
+// the bucket tool finds the region to fill on the rendered drawing
+pub use fill::flood_fill;
+
+// the eraser cuts lines and curves where it goes over them
+pub use bezier::erased_range;
+
+// clicking a bezier curve moves its subdivision point to the closest point of the curve
+pub use bezier::closest_point;
+
+// the UI shows how much a bezier curve changed after reducing its degree
+pub use bezier::{max_deviation, reduce_degree};
+
+// the regular polygon tool builds the corners of the polygon it draws
+pub use polygon::regular_polygon;
+
 /// given a shape core allows you to create a dynamic ShapeImpl stored on the Heap
 pub fn new_shape_from_core(core: ShapeCore) -> Box<dyn ShapeImpl> {
     match core.shape_type {
-        Shape::NoSelect => Box::new(Line::new(core)),
+        Shape::NoSelect | Shape::Eraser => Box::new(Line::new(core)),
         Shape::Line => Box::new(Line::new(core)),
         Shape::Ellipse => Box::new(Ellipse::new(core)),
         Shape::Triangle => Box::new(Triangle::new(core)),
         Shape::Rectangle => Box::new(Rectangle::new(core)),
         Shape::Bezier => Box::new(Bezier::new(core)),
+        Shape::BSpline | Shape::CatmullRom => Box::new(Spline::new(core)),
+        Shape::Path => Box::new(Path::new(core)),
+        Shape::Polygon | Shape::RegularPolygon => Box::new(Polygon::new(core)),
+        Shape::Polyline => Box::new(Polyline::new(core)),
+        Shape::Freehand => Box::new(Freehand::new(core)),
+        Shape::Fill => Box::new(Fill::new(core)),
+        Shape::Text => Box::new(Text::new(core)),
+        Shape::Image => Box::new(Image::new(core)),
     }
 }
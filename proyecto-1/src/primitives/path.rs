@@ -0,0 +1,223 @@
+use super::bezier::CONTROL_POLYGON_DASH;
+use super::dash::DashPattern;
+use super::freehand::{draw_path, path_hit_test};
+use super::line::draw_dashed_polyline;
+use super::stroke::PointFloat;
+use crate::canvas::Canvas;
+use crate::core::{JointKind, Point, ShapeCore, ShapeImpl, UpdateOp, CONTROL_POINT_REACH, RGBA};
+
+/// chain of cubic bezier curves, like the paths of other vector editors. The points are stored
+/// as point, handle, handle, point, handle, handle, point... Each curve goes from a point to the
+/// next one and its handles are the two control points between them. The joint of each point
+/// (see JointKind) decides how its handles move together
+pub struct Path {
+    core: ShapeCore,
+    // points of the curve, generated each time the shape changes like the lines of the bezier
+    path: Vec<PointFloat>,
+}
+
+impl ShapeImpl for Path {
+    fn new(core: ShapeCore) -> Path {
+        let path = generate_path(&core);
+        Path { core, path }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn update(&mut self, op: &UpdateOp) {
+        // a point of the path takes its handles with it, we need how much it moved
+        let moved = match op {
+            UpdateOp::ControlPoint(i, p) if i % 3 == 0 && *i < self.core.points.len() => {
+                Some(*p - self.core.points[*i])
+            }
+            _ => None,
+        };
+
+        self.update_basic(op);
+
+        match op {
+            // the new point is the end of a new curve, its handles start on the point and are
+            // pulled out later
+            UpdateOp::AddControlPoint(p) => {
+                let core = &mut self.core;
+                let at = core.points.len() - 1;
+                core.points.splice(at..at, [*p, *p]);
+                if let Some(last) = core.joints.last().copied() {
+                    core.joints.push(last);
+                }
+            }
+            UpdateOp::ControlPoint(i, _) => match moved {
+                Some(delta) => self.move_handles(*i, delta),
+                None => self.keep_joint(*i),
+            },
+            UpdateOp::ChangeJoint(joint, _) => self.fit_joint(joint * 3),
+            _ => {}
+        }
+
+        self.path = generate_path(&self.core);
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        self.draw_with_color(self.core.color, canvas);
+    }
+
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        draw_path(&self.path, &self.core, color, canvas);
+    }
+
+    /// the handles are joined to their point with dashed lines, like the control polygon of the
+    /// bezier curve
+    fn draw_selection<'a>(&self, color1: RGBA, color2: RGBA, canvas: &mut Canvas<'a>) {
+        self.draw_selection_basic(color1, canvas);
+
+        let Some(dash) = DashPattern::from_lengths(&CONTROL_POLYGON_DASH, 0) else {
+            return;
+        };
+        let points = &self.core.points;
+        for anchor in (0..points.len()).step_by(3) {
+            let handles = &points[anchor.saturating_sub(1)..(anchor + 2).min(points.len())];
+            draw_dashed_polyline(handles, false, &dash, color2, canvas);
+        }
+    }
+
+    fn hit_test(&self, point: Point) -> bool {
+        path_hit_test(&self.path, &self.core, point)
+    }
+
+    /// the handles of a new point are on top of it, so the points are looked for before the
+    /// handles. Otherwise a point could never be moved until its handles are pulled out
+    fn control_point_at(&self, target: Point) -> Option<usize> {
+        let points = &self.core.points;
+        let (anchors, handles): (Vec<usize>, Vec<usize>) =
+            (0..points.len()).partition(|i| i % 3 == 0);
+        anchors
+            .into_iter()
+            .chain(handles)
+            .find(|i| points[*i].distance(target) <= CONTROL_POINT_REACH)
+    }
+}
+
+impl Path {
+    /// moves the handles of the point at index by delta, after the point was moved
+    fn move_handles(&mut self, index: usize, delta: Point) {
+        let points = &mut self.core.points;
+        for handle in [index.wrapping_sub(1), index + 1] {
+            if let Some(p) = points.get_mut(handle) {
+                *p = *p + delta;
+            }
+        }
+    }
+
+    /// after moving the handle at index, moves the handle on the other side of its point to keep
+    /// the joint of that point. The first and last points only have one handle
+    fn keep_joint(&mut self, handle: usize) {
+        let joints = self.core.path_joints();
+        let points = &mut self.core.points;
+        let n = points.len();
+        if handle >= n {
+            return;
+        }
+        let (anchor, other) = match handle % 3 {
+            1 => (handle - 1, handle.checked_sub(2)),
+            _ => (handle + 1, Some(handle + 2).filter(|i| *i < n)),
+        };
+        let Some(other) = other else {
+            return;
+        };
+
+        let center = points[anchor];
+        let pulled = points[handle];
+        points[other] = match joints[anchor / 3] {
+            JointKind::Corner => return,
+            JointKind::Smooth => {
+                // same direction as the moved handle but on the other side, with its own length
+                let (dx, dy) = ((center.0 - pulled.0) as f32, (center.1 - pulled.1) as f32);
+                let length = dx.hypot(dy);
+                if length == 0.0 {
+                    return;
+                }
+                let own = center.distance(points[other]) / length;
+                (center.0 as f32 + dx * own, center.1 as f32 + dy * own).into()
+            }
+            JointKind::Symmetric => Point(2 * center.0 - pulled.0, 2 * center.1 - pulled.1),
+        };
+    }
+
+    /// moves both handles of the point at index so they fit its joint. They are put on the same
+    /// line keeping the direction from one handle to the other. When both are on top of the point
+    /// the direction goes from the previous point to the next one, and the handles get a third of
+    /// the distance to them (the same tangent a Catmull-Rom spline would have there)
+    fn fit_joint(&mut self, anchor: usize) {
+        let joints = self.core.path_joints();
+        let points = &mut self.core.points;
+        if anchor == 0 || anchor + 1 >= points.len() {
+            return;
+        }
+
+        let joint = joints[anchor / 3];
+        let center: PointFloat = points[anchor].into();
+        let (before, after): (PointFloat, PointFloat) =
+            (points[anchor - 1].into(), points[anchor + 1].into());
+        let length = |p: PointFloat| (p.0 - center.0).hypot(p.1 - center.1);
+
+        let (mut direction, mut lengths) = (
+            (after.0 - before.0, after.1 - before.1),
+            (length(before), length(after)),
+        );
+        if lengths == (0.0, 0.0) {
+            let (previous, next): (PointFloat, PointFloat) =
+                (points[anchor - 3].into(), points[anchor + 3].into());
+            direction = (next.0 - previous.0, next.1 - previous.1);
+            lengths = (length(previous) / 3.0, length(next) / 3.0);
+        }
+
+        let norm = direction.0.hypot(direction.1);
+        if joint == JointKind::Corner || norm == 0.0 {
+            return;
+        }
+        if joint == JointKind::Symmetric {
+            let mean = (lengths.0 + lengths.1) / 2.0;
+            lengths = (mean, mean);
+        }
+
+        let unit = (direction.0 / norm, direction.1 / norm);
+        points[anchor - 1] = (center.0 - unit.0 * lengths.0, center.1 - unit.1 * lengths.0).into();
+        points[anchor + 1] = (center.0 + unit.0 * lengths.1, center.1 + unit.1 * lengths.1).into();
+    }
+}
+
+/// returns the points of the curve. Like the pieces of a b-spline, each curve is sampled every 2
+/// pixels or so of its own control points
+fn generate_path(core: &ShapeCore) -> Vec<PointFloat> {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    let mut path = vec![points[0]];
+
+    for (i, curve) in points.windows(4).step_by(3).enumerate() {
+        let length: f32 = core.points[i * 3..i * 3 + 4]
+            .windows(2)
+            .map(|w| w[0].distance(w[1]))
+            .sum();
+        let steps = ((length / 2.0).ceil() as usize).max(1);
+        for step in 1..=steps {
+            path.push(cubic(curve, step as f32 / steps as f32));
+        }
+    }
+
+    path
+}
+
+/// point of a cubic bezier curve at t
+fn cubic(p: &[PointFloat], t: f32) -> PointFloat {
+    let s = 1.0 - t;
+    let (a, b, c, d) = (s * s * s, 3.0 * s * s * t, 3.0 * s * t * t, t * t * t);
+    (
+        a * p[0].0 + b * p[1].0 + c * p[2].0 + d * p[3].0,
+        a * p[0].1 + b * p[1].1 + c * p[2].1 + d * p[3].1,
+    )
+}
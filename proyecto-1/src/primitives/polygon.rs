@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+
+use super::antialias::{draw_coverage, region_coverage, Coverage};
+use super::dash::DashPattern;
+use super::line::{antialiased_border, line_hit_test, polyline_pixels, segment_hit_test};
+use super::stroke::{draw_stroke, stroke_polyline, PointFloat};
+use crate::canvas::Canvas;
+use crate::core::{FillRule, Point, ShapeCore, ShapeImpl, RGBA};
+
+/// closed shape with any amount of points. Its created like the bezier curve, one click per point
+pub struct Polygon {
+    core: ShapeCore,
+}
+
+impl ShapeImpl for Polygon {
+    fn new(core: ShapeCore) -> Polygon {
+        Polygon { core }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        draw_polygon(&self.core, canvas);
+    }
+
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        draw_polygon(&self.core.copy_with_color(color), canvas);
+    }
+
+    /// hit test for polygon. If its filled it checks if the point is inside with the fill rule of
+    /// the polygon, if not it only checks if it touches the borders
+    fn hit_test(&self, p: Point) -> bool {
+        let points = &self.core.points;
+        let n = points.len();
+        let edges = (0..n).map(|i| (points[i], points[(i + 1) % n]));
+
+        if self.core.fill_color.is_transparent() {
+            return edges
+                .map(|(a, b)| self.core.copy_with_points(vec![a, b]))
+                .any(|edge| line_hit_test(&edge, p));
+        }
+
+        // half of a wide border is outside the polygon, clicking it also selects the polygon
+        let half_width = self.core.stroke_width as u64 / 2;
+        let on_border = half_width > 0
+            && edges
+                .clone()
+                .any(|(a, b)| segment_hit_test(a, b, p, half_width));
+
+        let points: Vec<PointFloat> = points.iter().map(|p| (*p).into()).collect();
+        let winding = winding_number(&points, p.into());
+        self.core.fill_rule.is_inside(winding) || on_border
+    }
+}
+
+/// draws a polygon. The border is drawn first and the inside is every pixel the scanline fill finds
+/// that is not part of the border, so no pixel is drawn twice
+fn draw_polygon(core: &ShapeCore, canvas: &mut Canvas) {
+    if core.antialias {
+        draw_antialiased_polygon(core, canvas);
+        return;
+    }
+
+    let border: HashSet<(i32, i32)> = if core.stroke_width > 1 {
+        let border = stroke_polyline(&core.points, core, true);
+        draw_stroke(&border, core.color, canvas);
+        border
+    } else {
+        let pixels = polyline_pixels(&core.points, true);
+        let drawn = match DashPattern::new(core) {
+            Some(dash) => dash.filter_pixels(&pixels),
+            None => pixels.clone(),
+        };
+        for (x, y) in drawn {
+            canvas.set_pixel(x, y, core.color);
+        }
+        // the whole border is used to know where not to fill, even the pixels on the gaps
+        pixels.into_iter().collect()
+    };
+
+    if core.fill_color.is_transparent() {
+        return;
+    }
+
+    fill_polygon(&core.points, core.fill_rule, |x, y| {
+        if !border.contains(&(x, y)) {
+            canvas.set_pixel(x, y, core.fill_color);
+        }
+    });
+}
+
+/// draws an anti-aliased polygon (see antialias.rs). The fill goes until the middle of the border,
+/// the border is drawn over it
+fn draw_antialiased_polygon(core: &ShapeCore, canvas: &mut Canvas) {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    let border = antialiased_border(&points, core, true);
+
+    let fill = if core.fill_color.is_transparent() {
+        Coverage::new()
+    } else {
+        let min_x = core.points.iter().map(|p| p.0).min().unwrap();
+        let max_x = core.points.iter().map(|p| p.0).max().unwrap();
+        let min_y = core.points.iter().map(|p| p.1).min().unwrap();
+        let max_y = core.points.iter().map(|p| p.1).max().unwrap();
+        region_coverage((min_x, min_y), (max_x, max_y), |p| {
+            core.fill_rule.is_inside(winding_number(&points, p))
+        })
+    };
+
+    draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+}
+
+/// an edge of the polygon on the edge table. We keep it going down: top has the smallest y
+struct Edge {
+    top: Point,
+    bottom: Point,
+    /// +1 if the original edge goes down, -1 if it goes up
+    winding: i32,
+}
+
+impl Edge {
+    /// x where the edge crosses the row y, as a fraction (numerator, denominator). The
+    /// denominator is always positive
+    fn x_at(&self, y: i32) -> (i64, i64) {
+        let dy = (self.bottom.1 - self.top.1) as i64;
+        let dx = (self.bottom.0 - self.top.0) as i64;
+        (self.top.0 as i64 * dy + (y - self.top.1) as i64 * dx, dy)
+    }
+}
+
+/// scanline fill with an active edge table. Calls "plot" once for every pixel whose center is
+/// inside the polygon given the fill rule, the polygon can cross itself.
+///
+/// Each edge covers the rows from its top (inclusive) to its bottom (exclusive), that way a vertex
+/// shared by two edges is only crossed once and horizontal edges cover no rows at all. The edges
+/// are sorted by their top row, when the scanline gets there they become active and they stop
+/// being active on their bottom row. On each row the active edges are sorted by the x where they
+/// cross it, going from left to right we add the winding of each crossing and fill the spans that
+/// the rule considers inside. The crossings are computed with integers, so a polygon and the same
+/// polygon with its points in the other direction cover the same pixels
+pub fn fill_polygon(points: &[Point], rule: FillRule, mut plot: impl FnMut(i32, i32)) {
+    let n = points.len();
+    let mut edge_table: Vec<Edge> = (0..n)
+        .map(|i| (points[i], points[(i + 1) % n]))
+        .filter(|(a, b)| a.1 != b.1)
+        .map(|(a, b)| {
+            if a.1 < b.1 {
+                Edge {
+                    top: a,
+                    bottom: b,
+                    winding: 1,
+                }
+            } else {
+                Edge {
+                    top: b,
+                    bottom: a,
+                    winding: -1,
+                }
+            }
+        })
+        .collect();
+    // sorted backwards, the next edge to become active is the last one
+    edge_table.sort_by_key(|e| std::cmp::Reverse(e.top.1));
+
+    let Some(mut y) = edge_table.last().map(|e| e.top.1) else {
+        return;
+    };
+    let mut active: Vec<Edge> = vec![];
+
+    while !edge_table.is_empty() || !active.is_empty() {
+        while edge_table.last().is_some_and(|e| e.top.1 == y) {
+            active.push(edge_table.pop().unwrap());
+        }
+        active.retain(|e| e.bottom.1 > y);
+
+        // sorted by the crossing, comparing the fractions without dividing
+        active.sort_by(|a, b| {
+            let ((na, da), (nb, db)) = (a.x_at(y), b.x_at(y));
+            (na * db).cmp(&(nb * da))
+        });
+
+        let mut winding = 0;
+        for pair in active.windows(2) {
+            winding += pair[0].winding;
+            if rule.is_inside(winding) {
+                // the pixels whose center is between both crossings
+                let start = ceil_div(pair[0].x_at(y));
+                let end = ceil_div(pair[1].x_at(y));
+                for x in start..end {
+                    plot(x, y);
+                }
+            }
+        }
+
+        y += 1;
+    }
+}
+
+/// rounds the fraction up
+fn ceil_div((numerator, denominator): (i64, i64)) -> i32 {
+    -((-numerator).div_euclid(denominator)) as i32
+}
+
+/// how many times the border goes around the point, counting the edges that cross the row of
+/// the point on its left or right on it. Uses the same rules as fill_polygon, so a pixel is
+/// inside if fill_polygon fills it
+pub fn winding_number(points: &[PointFloat], p: PointFloat) -> i32 {
+    let n = points.len();
+    let mut winding = 0;
+
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let (top, bottom, edge_winding) = if a.1 < b.1 { (a, b, 1) } else { (b, a, -1) };
+        if p.1 < top.1 || p.1 >= bottom.1 {
+            continue;
+        }
+
+        let x = top.0 + (p.1 - top.1) * (bottom.0 - top.0) / (bottom.1 - top.1);
+        if x <= p.0 {
+            winding += edge_winding;
+        }
+    }
+
+    winding
+}
+
+/// corners of a regular polygon with the given amount of sides around the center, the first one
+/// at the given angle (in radians, clockwise since y goes down). A star has a second corner
+/// between each pair of them, at the inner ratio of the radius. The corners are rounded to pixels
+/// since they are stored as a polygon that can be edited like any other
+pub fn regular_polygon(
+    center: Point,
+    radius: f32,
+    angle: f32,
+    sides: u32,
+    inner_ratio: Option<f32>,
+) -> Vec<Point> {
+    let corners = if inner_ratio.is_some() {
+        sides * 2
+    } else {
+        sides
+    };
+    let step = 2.0 * std::f32::consts::PI / corners as f32;
+
+    (0..corners)
+        .map(|i| {
+            let radius = match inner_ratio {
+                Some(ratio) if i % 2 == 1 => radius * ratio,
+                _ => radius,
+            };
+            let angle = angle + step * i as f32;
+            let p = (
+                center.0 as f32 + radius * angle.cos(),
+                center.1 as f32 + radius * angle.sin(),
+            );
+            Point::from(p)
+        })
+        .collect()
+}
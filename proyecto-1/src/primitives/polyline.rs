@@ -0,0 +1,53 @@
+use super::dash::DashPattern;
+use super::line::{draw_styled_line, line_hit_test, polyline_pixels};
+use crate::canvas::Canvas;
+use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
+
+/// open border with any amount of points. Its created like the bezier curve, one click per point
+pub struct Polyline {
+    core: ShapeCore,
+}
+
+impl ShapeImpl for Polyline {
+    fn new(core: ShapeCore) -> Polyline {
+        Polyline { core }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        draw_polyline(&self.core, canvas);
+    }
+
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        draw_polyline(&self.core.copy_with_color(color), canvas);
+    }
+
+    /// a polyline is hit if any of its lines is hit
+    fn hit_test(&self, p: Point) -> bool {
+        self.core
+            .points
+            .windows(2)
+            .map(|w| self.core.copy_with_points(w.to_vec()))
+            .any(|line| line_hit_test(&line, p))
+    }
+}
+
+/// draws the lines between each point without drawing the points they share twice
+fn draw_polyline(core: &ShapeCore, canvas: &mut Canvas) {
+    // wide, dashed and anti-aliased lines already go through every point of the core
+    if core.stroke_width > 1 || core.antialias || DashPattern::new(core).is_some() {
+        draw_styled_line(core, canvas);
+        return;
+    }
+
+    for (x, y) in polyline_pixels(&core.points, false) {
+        canvas.set_pixel(x, y, core.color);
+    }
+}
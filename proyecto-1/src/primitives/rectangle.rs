@@ -1,9 +1,13 @@
 use std::cmp::{max, min};
 
 use crate::canvas::Canvas;
-use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
+use crate::core::{Point, ShapeCore, ShapeImpl, UpdateOp, RGBA};
 
-use super::line::line_hit_test; // To draw lines for the rectangle
+use super::antialias::{draw_coverage, region_coverage, Coverage};
+use super::dash::DashPattern;
+use super::line::{antialiased_border, draw_dashed_polyline, line_hit_test}; // To draw lines for the rectangle
+use super::rounded_rectangle::{draw_rounded_rectangle, radius_from_handle, RoundedBox};
+use super::stroke::{draw_stroke, stroke_polyline};
 
 const HIT_TEST_THRESHOLD: u32 = 5;
 
@@ -34,6 +38,31 @@ impl ShapeImpl for Rectangle {
         draw_rectangle(&self.core.copy_with_color(color), canvas);
     }
 
+    /// the corners and the handle of the radius of the corners
+    fn control_points(&self) -> Vec<Point> {
+        let (min, max) = get_box(&self.core);
+        let rounded = RoundedBox::new(&self.core, min, max);
+        vec![self.core.points[0], self.core.points[1], rounded.handle()]
+    }
+
+    /// moving the handle of the radius changes every corner if they are all the same, or only
+    /// the top left one (where the handle is) if they are different
+    fn update(&mut self, op: &UpdateOp) {
+        match op {
+            UpdateOp::ControlPoint(2, p) => {
+                let (min, _) = get_box(&self.core);
+                let radius = radius_from_handle(min, *p);
+                let radii = &mut self.core.corner_radii;
+                if radii.iter().all(|r| *r == radii[0]) {
+                    *radii = [radius; 4];
+                } else {
+                    radii[0] = radius;
+                }
+            }
+            _ => self.update_basic(op),
+        }
+    }
+
     /// simple hit test for rectangle just gets the square and checks if the point is within that
     /// square if its filled. Or if its over the lines if its not filled
     fn hit_test(&self, point: Point) -> bool {
@@ -45,43 +74,89 @@ impl ShapeImpl for Rectangle {
             return line_hit_test(&self.core, point);
         }
 
+        // half of the border is outside the box
+        let half_width = self.core.stroke_width / 2;
+
+        let (min, max) = get_box(&self.core);
+        let rounded = RoundedBox::new(&self.core, min, max);
+        if rounded.is_round() {
+            let distance = rounded.distance(point.into());
+            let half_width = self.core.stroke_width as f32 / 2.0;
+            if !self.core.fill_color.is_transparent() {
+                return distance <= half_width;
+            }
+            return distance.abs() <= HIT_TEST_THRESHOLD as f32 + half_width;
+        }
+
         // case when its filled||
         if !self.core.fill_color.is_transparent() {
-            return point.is_within_box(p1, p2, 0);
+            return point.is_within_box(p1, p2, half_width);
         }
 
         // checking if the click is on the lines. we create a "box" surrounding each line
-        return point.is_within_box(p1, Point(p1.0, p2.1), HIT_TEST_THRESHOLD)
-            || point.is_within_box(p1, Point(p2.0, p1.1), HIT_TEST_THRESHOLD)
-            || point.is_within_box(p2, Point(p2.0, p1.1), HIT_TEST_THRESHOLD)
-            || point.is_within_box(p2, Point(p1.0, p2.1), HIT_TEST_THRESHOLD);
+        let threshold = HIT_TEST_THRESHOLD + half_width;
+        point.is_within_box(p1, Point(p1.0, p2.1), threshold)
+            || point.is_within_box(p1, Point(p2.0, p1.1), threshold)
+            || point.is_within_box(p2, Point(p2.0, p1.1), threshold)
+            || point.is_within_box(p2, Point(p1.0, p2.1), threshold)
     }
 }
 
-/// Draws a rectangle given a shape core
-fn draw_rectangle<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>) {
+/// returns the top left and bottom right corners of the rectangle
+pub(super) fn get_box(core: &ShapeCore) -> ((i32, i32), (i32, i32)) {
     let p1 = core.points[0];
     let p2 = core.points[1];
+    (
+        (min(p1.0, p2.0), min(p1.1, p2.1)),
+        (max(p1.0, p2.0), max(p1.1, p2.1)),
+    )
+}
 
-    let min_x = min(p1.0, p2.0);
-    let max_x = max(p1.0, p2.0);
+/// Draws a rectangle given a shape core. The ones with round corners are drawn by
+/// rounded_rectangle.rs
+fn draw_rectangle<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>) {
+    let ((min_x, min_y), (max_x, max_y)) = get_box(core);
 
-    let min_y = min(p1.1, p2.1);
-    let max_y = max(p1.1, p2.1);
+    let rounded = RoundedBox::new(core, (min_x, min_y), (max_x, max_y));
+    if rounded.is_round() {
+        draw_rounded_rectangle(core, &rounded, canvas);
+        return;
+    }
 
-    // we draw x inclusive
-    for x in min_x..(max_x + 1) {
-        canvas.set_pixel(x, max_y, core.color);
-        if min_y != max_y {
-            canvas.set_pixel(x, min_y, core.color);
-        }
+    if core.antialias {
+        draw_antialiased_rectangle(core, (min_x, min_y), (max_x, max_y), canvas);
+        return;
     }
 
-    // we draw y exclusive to avoid drawing the corners twice
-    for y in (min_y + 1)..max_y {
-        canvas.set_pixel(min_x, y, core.color);
-        if min_x != max_x {
-            canvas.set_pixel(max_x, y, core.color);
+    if core.stroke_width > 1 {
+        draw_thick_rectangle(core, (min_x, min_y), (max_x, max_y), canvas);
+        return;
+    }
+
+    if let Some(dash) = DashPattern::new(core) {
+        // the pattern goes around the rectangle starting on the first corner
+        let corners = [
+            Point(min_x, min_y),
+            Point(max_x, min_y),
+            Point(max_x, max_y),
+            Point(min_x, max_y),
+        ];
+        draw_dashed_polyline(&corners, true, &dash, core.color, canvas);
+    } else {
+        // we draw x inclusive
+        for x in min_x..(max_x + 1) {
+            canvas.set_pixel(x, max_y, core.color);
+            if min_y != max_y {
+                canvas.set_pixel(x, min_y, core.color);
+            }
+        }
+
+        // we draw y exclusive to avoid drawing the corners twice
+        for y in (min_y + 1)..max_y {
+            canvas.set_pixel(min_x, y, core.color);
+            if min_x != max_x {
+                canvas.set_pixel(max_x, y, core.color);
+            }
         }
     }
 
@@ -93,3 +168,63 @@ fn draw_rectangle<'a>(core: &ShapeCore, canvas: &mut Canvas<'a>) {
         }
     }
 }
+
+/// draws a rectangle with a border wider than 1 pixel. The border is a closed stroke going through
+/// the 4 corners (see stroke.rs), so the corners follow the line join. The inside is every pixel of
+/// the box that is not part of the border
+fn draw_thick_rectangle<'a>(
+    core: &ShapeCore,
+    min: (i32, i32),
+    max: (i32, i32),
+    canvas: &mut Canvas<'a>,
+) {
+    let corners = [
+        Point(min.0, min.1),
+        Point(max.0, min.1),
+        Point(max.0, max.1),
+        Point(min.0, max.1),
+    ];
+    let border = stroke_polyline(&corners, core, true);
+    draw_stroke(&border, core.color, canvas);
+
+    if core.fill_color.is_transparent() {
+        return;
+    }
+
+    for y in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            if !border.contains(&(x, y)) {
+                canvas.set_pixel(x, y, core.fill_color);
+            }
+        }
+    }
+}
+
+/// draws an anti-aliased rectangle (see antialias.rs). The fill goes until the middle of the
+/// border, the border is drawn over it
+fn draw_antialiased_rectangle<'a>(
+    core: &ShapeCore,
+    min: (i32, i32),
+    max: (i32, i32),
+    canvas: &mut Canvas<'a>,
+) {
+    let corners = [
+        (min.0 as f32, min.1 as f32),
+        (max.0 as f32, min.1 as f32),
+        (max.0 as f32, max.1 as f32),
+        (min.0 as f32, max.1 as f32),
+    ];
+    let border = antialiased_border(&corners, core, true);
+
+    let fill = if core.fill_color.is_transparent() {
+        Coverage::new()
+    } else {
+        let (left, top) = corners[0];
+        let (right, bottom) = corners[2];
+        region_coverage(min, max, |(x, y)| {
+            x >= left && x <= right && y >= top && y <= bottom
+        })
+    };
+
+    draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+}
@@ -0,0 +1,238 @@
+use std::collections::{HashMap, HashSet};
+use std::f32::consts::FRAC_PI_2;
+
+use crate::canvas::Canvas;
+use crate::core::{Point, ShapeCore};
+
+use super::antialias::{draw_coverage, region_coverage, Coverage};
+use super::dash::DashPattern;
+use super::ellipse::midpoint_ellipse;
+use super::line::{antialiased_border, polyline_pixels};
+use super::stroke::{draw_stroke, sample, stroke_path, PointFloat};
+
+/// the radius handle is never closer than this to the corner, so it can still be grabbed when
+/// the radius is 0 (it would be under the control point of the corner)
+const HANDLE_MIN_OFFSET: i32 = 8;
+
+/// for each corner (same order as the radii) the direction from the center of its circle towards
+/// the corner
+const CORNER_SIGNS: [(i32, i32); 4] = [(-1, -1), (1, -1), (1, 1), (-1, 1)];
+
+/// rectangle with round corners. Each corner is a quarter of a circle, the sides are the straight
+/// lines between them. The radii are limited to half the shortest side, so the circles of two
+/// corners never cross
+pub(super) struct RoundedBox {
+    min: (i32, i32),
+    max: (i32, i32),
+    /// top left, top right, bottom right and bottom left
+    radii: [i32; 4],
+}
+
+impl RoundedBox {
+    pub(super) fn new(core: &ShapeCore, min: (i32, i32), max: (i32, i32)) -> Self {
+        let limit = ((max.0 - min.0).min(max.1 - min.1) / 2) as u32;
+        RoundedBox {
+            min,
+            max,
+            radii: core.corner_radii.map(|r| r.min(limit) as i32),
+        }
+    }
+
+    /// checks if any corner is round, if not its drawn as a plain rectangle
+    pub(super) fn is_round(&self) -> bool {
+        self.radii.iter().any(|r| *r > 0)
+    }
+
+    /// the centers of the circles of each corner
+    fn centers(&self) -> [(i32, i32); 4] {
+        let [tl, tr, br, bl] = self.radii;
+        let (min, max) = (self.min, self.max);
+        [
+            (min.0 + tl, min.1 + tl),
+            (max.0 - tr, min.1 + tr),
+            (max.0 - br, max.1 - br),
+            (min.0 + bl, max.1 - bl),
+        ]
+    }
+
+    /// distance from p to the outline, negative inside. Its the distance to the circle of the
+    /// corner if p is past its center on both axis, and to the box otherwise
+    pub(super) fn distance(&self, p: PointFloat) -> f32 {
+        let middle = (
+            (self.min.0 + self.max.0) as f32 / 2.0,
+            (self.min.1 + self.max.1) as f32 / 2.0,
+        );
+        // the corner of the quarter of the box where p is
+        let corner = match (p.0 >= middle.0, p.1 >= middle.1) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (true, true) => 2,
+            (false, true) => 3,
+        };
+
+        let center = self.centers()[corner];
+        let (sx, sy) = CORNER_SIGNS[corner];
+        let dx = (p.0 - center.0 as f32) * sx as f32;
+        let dy = (p.1 - center.1 as f32) * sy as f32;
+        if dx > 0.0 && dy > 0.0 {
+            return (dx * dx + dy * dy).sqrt() - self.radii[corner] as f32;
+        }
+
+        let qx = (p.0 - middle.0).abs() - (self.max.0 - self.min.0) as f32 / 2.0;
+        let qy = (p.1 - middle.1).abs() - (self.max.1 - self.min.1) as f32 / 2.0;
+        let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+        outside + qx.max(qy).min(0.0)
+    }
+
+    fn is_inside(&self, p: PointFloat) -> bool {
+        self.distance(p) <= 0.0
+    }
+
+    /// the outline as a closed path going clockwise from the left end of the top left corner,
+    /// each corner flattened into a point every 2 pixels or so
+    fn outline(&self) -> Vec<PointFloat> {
+        let mut path = vec![];
+        for (i, (center, radius)) in self.centers().into_iter().zip(self.radii).enumerate() {
+            let center = (center.0 as f32, center.1 as f32);
+            let radius = radius as f32;
+            let steps = ((radius * FRAC_PI_2 / 2.0).ceil() as usize).max(1);
+            // the top left corner goes from the left (180 degrees) to the top (270 degrees)
+            let start = FRAC_PI_2 * (i + 2) as f32;
+            for step in 0..=steps {
+                let angle = start + FRAC_PI_2 * step as f32 / steps as f32;
+                path.push((
+                    center.0 + radius * angle.cos(),
+                    center.1 + radius * angle.sin(),
+                ));
+            }
+        }
+        path
+    }
+
+    /// the pixels of the 1 pixel border. Each corner is the quarter of the circle the integer
+    /// midpoint algorithm of the ellipse draws, and the sides are the rows and columns between
+    /// the corners. Its 8-connected and no pixel is repeated
+    fn ring(&self) -> Vec<(i32, i32)> {
+        let mut pixels = vec![];
+        let centers = self.centers();
+        for ((center, radius), (sx, sy)) in centers.iter().zip(self.radii).zip(CORNER_SIGNS) {
+            if radius == 0 {
+                pixels.push(*center);
+                continue;
+            }
+            let circle = midpoint_ellipse(
+                Point(center.0, center.1),
+                radius as i64,
+                radius as i64,
+                |_, _, _| {},
+            );
+            pixels.extend(
+                circle
+                    .into_iter()
+                    .filter(|(x, y)| (x - center.0) * sx >= 0 && (y - center.1) * sy >= 0),
+            );
+        }
+
+        let [tl, tr, br, bl] = centers;
+        pixels.extend((tl.0 + 1..tr.0).map(|x| (x, self.min.1)));
+        pixels.extend((tr.1 + 1..br.1).map(|y| (self.max.0, y)));
+        pixels.extend((bl.0 + 1..br.0).map(|x| (x, self.max.1)));
+        pixels.extend((tl.1 + 1..bl.1).map(|y| (self.min.0, y)));
+
+        // the corners of both circles are the same pixel when the radius is half the side
+        let mut seen = HashSet::new();
+        pixels.retain(|p| seen.insert(*p));
+        pixels
+    }
+
+    /// the point dragged to change the radius, on the diagonal of the top left corner at the
+    /// radius from both sides
+    pub(super) fn handle(&self) -> Point {
+        let limit = ((self.max.0 - self.min.0).min(self.max.1 - self.min.1) / 2).max(0);
+        let offset = self.radii[0].max(HANDLE_MIN_OFFSET).min(limit);
+        Point(self.min.0 + offset, self.min.1 + offset)
+    }
+}
+
+/// the radius of the top left corner for the handle at p: how far it is from both sides, the
+/// average if its not on the diagonal
+pub(super) fn radius_from_handle(min: (i32, i32), p: Point) -> u32 {
+    ((p.0 - min.0 + p.1 - min.1) / 2).max(0) as u32
+}
+
+/// draws a rectangle with round corners (see RoundedBox). Like the other shapes the 1 pixel border
+/// is drawn with the integer algorithms, and the fill is every pixel between the sides of the
+/// border on each row. Wide, dashed and anti-aliased borders flatten the outline into a path
+pub(super) fn draw_rounded_rectangle(core: &ShapeCore, rounded: &RoundedBox, canvas: &mut Canvas) {
+    let (min, max) = (rounded.min, rounded.max);
+
+    if core.antialias {
+        let border = antialiased_border(&rounded.outline(), core, true);
+        let fill = if core.fill_color.is_transparent() {
+            Coverage::new()
+        } else {
+            region_coverage(min, max, |p| rounded.is_inside(p))
+        };
+        draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+        return;
+    }
+
+    let dash = DashPattern::new(core);
+    if core.stroke_width > 1 || dash.is_some() {
+        let path = rounded.outline();
+        let border: HashSet<(i32, i32)> = if core.stroke_width > 1 {
+            let border = stroke_path(&path, core, true);
+            draw_stroke(&border, core.color, canvas);
+            border
+        } else {
+            // the whole border is used to know where not to fill
+            let points: Vec<Point> = path.into_iter().map(Point::from).collect();
+            let pixels = polyline_pixels(&points, true);
+            for (x, y) in dash.unwrap().filter_pixels(&pixels) {
+                canvas.set_pixel(x, y, core.color);
+            }
+            pixels.into_iter().collect()
+        };
+
+        if !core.fill_color.is_transparent() {
+            for y in min.1..=max.1 {
+                for x in min.0..=max.0 {
+                    if rounded.is_inside(sample(x, y)) && !border.contains(&(x, y)) {
+                        canvas.set_pixel(x, y, core.fill_color);
+                    }
+                }
+            }
+        }
+        return;
+    }
+
+    let border = rounded.ring();
+    for (x, y) in border.iter() {
+        canvas.set_pixel(*x, *y, core.color);
+    }
+
+    if core.fill_color.is_transparent() {
+        return;
+    }
+
+    // the shape is convex, so each row of the border is a run on the left and a run on the right
+    // (the same one on the top and bottom sides). The fill goes between them
+    let mut rows: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (x, y) in border {
+        rows.entry(y).or_default().push(x);
+    }
+    for (y, mut xs) in rows {
+        xs.sort();
+        let mut left = 0;
+        while left + 1 < xs.len() && xs[left + 1] == xs[left] + 1 {
+            left += 1;
+        }
+        let mut right = xs.len() - 1;
+        while right > left && xs[right - 1] == xs[right] - 1 {
+            right -= 1;
+        }
+        for x in xs[left] + 1..xs[right] {
+            canvas.set_pixel(x, y, core.fill_color);
+        }
+    }
+}
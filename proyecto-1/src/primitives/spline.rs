@@ -0,0 +1,227 @@
+use super::bezier::CONTROL_POLYGON_DASH;
+use super::dash::DashPattern;
+use super::freehand::{catmull_rom, draw_path, path_hit_test, smooth_path};
+use super::line::draw_dashed_polyline;
+use super::stroke::PointFloat;
+use crate::canvas::Canvas;
+use crate::core::{Point, Shape, ShapeCore, ShapeImpl, UpdateOp, RGBA};
+
+/// B-spline or Catmull-Rom spline. Both are chains of small curves where each control point only
+/// changes the pieces close to it, unlike the bezier curve where every point moves all of it.
+/// They are created and selected like the bezier curve, and instead of subdividing them a knot is
+/// inserted: a new control point that doesnt change the curve
+pub struct Spline {
+    core: ShapeCore,
+    subdivide_t: f32,
+    // points of the curve, generated each time the shape changes like the lines of the bezier
+    path: Vec<PointFloat>,
+}
+
+impl ShapeImpl for Spline {
+    fn new(core: ShapeCore) -> Spline {
+        let path = generate_path(&core);
+        Spline {
+            core,
+            subdivide_t: 0.5,
+            path,
+        }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn update(&mut self, op: &UpdateOp) {
+        self.update_basic(op);
+
+        match op {
+            UpdateOp::UpdateSubdivide(t) => self.subdivide_t = *t,
+            UpdateOp::InsertKnot => self.insert_knot(),
+            _ => {}
+        }
+
+        // the knots given one by one dont fit after adding or removing control points
+        let core = &mut self.core;
+        if !core.knots.is_empty()
+            && core.knots.len() != core.points.len() + core.spline_degree() + 1
+        {
+            core.knots.clear();
+        }
+        self.path = generate_path(&self.core);
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        self.draw_with_color(self.core.color, canvas);
+    }
+
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        draw_path(&self.path, &self.core, color, canvas);
+    }
+
+    /// like the bezier curve, the control polygon is dashed and the point where the knot is
+    /// inserted is shown
+    fn draw_selection<'a>(&self, color1: RGBA, color2: RGBA, canvas: &mut Canvas<'a>) {
+        self.draw_selection_basic(color1, canvas);
+
+        if let Some(dash) = DashPattern::from_lengths(&CONTROL_POLYGON_DASH, 0) {
+            draw_dashed_polyline(&self.core.points, false, &dash, color2, canvas);
+        }
+
+        let p = spline_point(&self.core, self.subdivide_t);
+        self.draw_control_point(Point::from(p), color1, canvas);
+    }
+
+    fn hit_test(&self, point: Point) -> bool {
+        path_hit_test(&self.path, &self.core, point)
+    }
+}
+
+impl Spline {
+    /// adds a control point at subdivide_t. On b-splines its Boehm's knot insertion: the new
+    /// knot splits the piece of the curve where it falls, and the control points of that piece
+    /// are replaced by points on the control polygon so the curve doesnt change (besides
+    /// rounding them to pixels). On Catmull-Rom splines the curve goes through every point, so
+    /// the point of the curve is added between the points of its piece
+    fn insert_knot(&mut self) {
+        let core = &mut self.core;
+        let n = core.points.len();
+
+        if core.shape_type == Shape::CatmullRom {
+            let p = Point::from(spline_point(core, self.subdivide_t));
+            let piece = catmull_rom_piece(n, self.subdivide_t).0;
+            if p != core.points[piece] && p != core.points[piece + 1] {
+                core.points.insert(piece + 1, p);
+            }
+            return;
+        }
+
+        let degree = core.spline_degree();
+        let mut knots = core.spline_knots();
+        let u = parameter(&knots, degree, n, self.subdivide_t);
+        // a knot repeated degree times already goes through a control point, another one would
+        // only repeat that point
+        if knots.iter().filter(|k| **k == u).count() >= degree {
+            return;
+        }
+
+        let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+        let k = knot_span(&knots, degree, n, u);
+        let mut new_points = Vec::with_capacity(n + 1);
+        for i in 0..=n {
+            if i + degree <= k {
+                new_points.push(points[i]);
+            } else if i <= k {
+                let alpha = (u - knots[i]) / (knots[i + degree] - knots[i]);
+                let (a, b) = (points[i - 1], points[i]);
+                new_points.push((a.0 + (b.0 - a.0) * alpha, a.1 + (b.1 - a.1) * alpha));
+            } else {
+                new_points.push(points[i - 1]);
+            }
+        }
+        knots.insert(k + 1, u);
+
+        core.points = new_points.into_iter().map(Point::from).collect();
+        core.knots = knots;
+        // a curve with less points than its degree needs was drawn with a lower one, it must stay
+        // the same now that it has one point more
+        core.degree = degree as u32;
+    }
+}
+
+/// returns the points of the curve. Each piece is sampled every 2 pixels or so of its own control
+/// points, so moving a control point doesnt change how the pieces far from it are drawn
+fn generate_path(core: &ShapeCore) -> Vec<PointFloat> {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    if core.shape_type == Shape::CatmullRom {
+        return smooth_path(&points);
+    }
+
+    let n = points.len();
+    let (degree, knots) = (core.spline_degree(), core.spline_knots());
+    let mut path = vec![de_boor(&points, &knots, degree, knots[degree])];
+    for k in degree..n {
+        let (start, end) = (knots[k], knots[k + 1]);
+        if start == end {
+            continue;
+        }
+
+        // the piece is never longer than the control polygon of its points
+        let piece = &core.points[k - degree..=k];
+        let length: f32 = piece.windows(2).map(|w| w[0].distance(w[1])).sum();
+        let steps = ((length / 2.0).ceil() as usize).max(1);
+        for step in 1..=steps {
+            let u = start + (end - start) * step as f32 / steps as f32;
+            path.push(de_boor(&points, &knots, degree, u));
+        }
+    }
+
+    path
+}
+
+/// point of the spline at t, from 0 (start of the curve) to 1 (end of the curve)
+fn spline_point(core: &ShapeCore, t: f32) -> PointFloat {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    let n = points.len();
+
+    if core.shape_type == Shape::CatmullRom {
+        let (i, t) = catmull_rom_piece(n, t);
+        // the ends use themselves as the missing neighbour, like smooth_path
+        let p0 = points[i.saturating_sub(1)];
+        let p3 = points[(i + 2).min(n - 1)];
+        return catmull_rom(p0, points[i], points[i + 1], p3, t);
+    }
+
+    let (degree, knots) = (core.spline_degree(), core.spline_knots());
+    de_boor(&points, &knots, degree, parameter(&knots, degree, n, t))
+}
+
+/// the piece of a Catmull-Rom spline with n points where t is, and the t inside that piece
+fn catmull_rom_piece(n: usize, t: f32) -> (usize, f32) {
+    let position = t.clamp(0.0, 1.0) * (n - 1) as f32;
+    let piece = (position as usize).min(n - 2);
+    (piece, position - piece as f32)
+}
+
+/// turns t (from 0 to 1) into the knot value. The curve goes from the knot number degree to the
+/// knot number n, the ones before and after only shape the first and last pieces
+fn parameter(knots: &[f32], degree: usize, n: usize, t: f32) -> f32 {
+    let (start, end) = (knots[degree], knots[n]);
+    start + (end - start) * t.clamp(0.0, 1.0)
+}
+
+/// the index k of the piece of the curve where u is, knots[k] <= u < knots[k + 1]. The end of
+/// the curve belongs to the last piece
+fn knot_span(knots: &[f32], degree: usize, n: usize, u: f32) -> usize {
+    (degree..n)
+        .rev()
+        .find(|k| knots[*k] < knots[k + 1] && knots[*k] <= u)
+        .unwrap_or(degree)
+}
+
+/// point of the b-spline at the knot value u with de Boor's algorithm. Only the degree + 1
+/// control points of the piece where u is are used, they are interpolated like de Casteljau does
+/// with the bezier curve but with weights that depend on the knots
+fn de_boor(points: &[PointFloat], knots: &[f32], degree: usize, u: f32) -> PointFloat {
+    let k = knot_span(knots, degree, points.len(), u);
+    let mut d: Vec<PointFloat> = points[k - degree..=k].to_vec();
+
+    for r in 1..=degree {
+        for j in (r..=degree).rev() {
+            let i = j + k - degree;
+            let span = knots[i + 1 + degree - r] - knots[i];
+            let alpha = if span == 0.0 {
+                0.0
+            } else {
+                (u - knots[i]) / span
+            };
+            let (a, b) = (d[j - 1], d[j]);
+            d[j] = (a.0 + (b.0 - a.0) * alpha, a.1 + (b.1 - a.1) * alpha);
+        }
+    }
+
+    d[degree]
+}
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+
+use crate::canvas::Canvas;
+use crate::core::{LineCap, LineJoin, Point, ShapeCore, RGBA};
+
+use super::dash::DashPattern;
+
+/// pixels covered by a thick border. We collect them on a set before drawing, that way a pixel
+/// covered by two segments (or a segment and a joint) is only drawn once
+pub type StrokePixels = HashSet<(i32, i32)>;
+
+/// a point with float coordinates, the borders of thick lines are not on integer positions
+pub type PointFloat = (f32, f32);
+
+// a pixel is covered when its center is inside the shape. Pixel centers are on integer positions,
+// so with even widths the border of a line falls exactly over the centers of a row of pixels and
+// its impossible to decide if that row is inside or not. We sample a tiny bit away from the center
+// (different on each axis, so diagonals dont tie either) and ties are always solved the same way
+const SAMPLE_OFFSET: PointFloat = (1e-3, 1.3e-3);
+
+/// returns the position where we sample the pixel (x, y)
+pub fn sample(x: i32, y: i32) -> PointFloat {
+    (x as f32 + SAMPLE_OFFSET.0, y as f32 + SAMPLE_OFFSET.1)
+}
+
+/// returns the pixels covered by a border going through every point, with the width, caps,
+/// joins and dashes of the shape core. Each segment is a rectangle of the width of the border, the
+/// ends get a cap and the points in the middle a join. If closed, the last point is also joined
+/// with the first one and there are no caps
+pub fn stroke_polyline(points: &[Point], core: &ShapeCore, closed: bool) -> StrokePixels {
+    let points: Vec<PointFloat> = points.iter().map(|p| (*p).into()).collect();
+    stroke_path(&points, core, closed)
+}
+
+/// same as stroke_polyline but with float points, for borders that dont go through integer
+/// positions (like an ellipse)
+pub fn stroke_path(points: &[PointFloat], core: &ShapeCore, closed: bool) -> StrokePixels {
+    let mut pixels = StrokePixels::new();
+    let points = remove_repeated(points, closed);
+
+    let Some(dash) = DashPattern::new(core) else {
+        add_path(&mut pixels, &points, core, closed);
+        return pixels;
+    };
+
+    // every dash is an open path with its own caps
+    for piece in dash.split(&points, closed) {
+        let piece = remove_repeated(&piece, false);
+        // a dash of length 0 with butt caps has no area, like on svg its not drawn
+        if piece.len() > 1 || core.line_cap != LineCap::Butt {
+            add_path(&mut pixels, &piece, core, false);
+        }
+    }
+
+    pixels
+}
+
+/// draws every pixel of a stroke with the given color
+pub fn draw_stroke(pixels: &StrokePixels, color: RGBA, canvas: &mut Canvas) {
+    for (x, y) in pixels.iter() {
+        canvas.set_pixel(*x, *y, color);
+    }
+}
+
+/// repeated points have no direction, we cant join or cap them. On a closed path the last point
+/// is also removed if its the first one
+fn remove_repeated(points: &[PointFloat], closed: bool) -> Vec<PointFloat> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+/// adds the pixels of a path without dashes, see stroke_polyline
+fn add_path(pixels: &mut StrokePixels, points: &[PointFloat], core: &ShapeCore, closed: bool) {
+    let half = core.stroke_width as f32 / 2.0;
+
+    // a line without length would be invisible, we draw it as a dot instead
+    if points.len() == 1 {
+        if core.line_cap == LineCap::Square {
+            let p = points[0];
+            add_convex(
+                pixels,
+                &[
+                    (p.0 - half, p.1 - half),
+                    (p.0 + half, p.1 - half),
+                    (p.0 + half, p.1 + half),
+                    (p.0 - half, p.1 + half),
+                ],
+            );
+        } else {
+            add_disc(pixels, points[0], half);
+        }
+        return;
+    }
+
+    for w in points.windows(2) {
+        add_segment(pixels, w[0], w[1], half);
+    }
+
+    let n = points.len();
+    if closed && n > 2 {
+        add_segment(pixels, points[n - 1], points[0], half);
+        for i in 0..n {
+            let prev = points[(i + n - 1) % n];
+            let next = points[(i + 1) % n];
+            add_join(pixels, prev, points[i], next, half, core);
+        }
+    } else if n > 1 {
+        for i in 1..n - 1 {
+            add_join(pixels, points[i - 1], points[i], points[i + 1], half, core);
+        }
+        add_cap(pixels, points[1], points[0], half, core.line_cap);
+        add_cap(pixels, points[n - 2], points[n - 1], half, core.line_cap);
+    }
+}
+
+/// adds the cap at the end of the segment that goes from "from" to "end"
+fn add_cap(pixels: &mut StrokePixels, from: PointFloat, end: PointFloat, half: f32, cap: LineCap) {
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Round => add_disc(pixels, end, half),
+        LineCap::Square => {
+            // the segment continues half the width after the end
+            let (dx, dy) = direction(from, end);
+            add_segment(pixels, end, (end.0 + dx * half, end.1 + dy * half), half);
+        }
+    }
+}
+
+/// adds the join between the segments prev -> p and p -> next. Only the outer side of the corner
+/// needs it, on the inner side both segments already overlap
+fn add_join(
+    pixels: &mut StrokePixels,
+    prev: PointFloat,
+    p: PointFloat,
+    next: PointFloat,
+    half: f32,
+    core: &ShapeCore,
+) {
+    if core.line_join == LineJoin::Round {
+        add_disc(pixels, p, half);
+        return;
+    }
+
+    let d0 = direction(prev, p);
+    let d1 = direction(p, next);
+    let cross = d0.0 * d1.1 - d0.1 * d1.0;
+    let dot = d0.0 * d1.0 + d0.1 * d1.1;
+    if cross == 0.0 && dot > 0.0 {
+        // both segments go in the same direction, there is no corner
+        return;
+    }
+    if cross == 0.0 {
+        // the path goes exactly backwards. There is no outer side and the miter would be
+        // infinitely long, so the turn gets the cap of the shape like if the path ended there
+        add_cap(pixels, prev, p, half, core.line_cap);
+        return;
+    }
+
+    // the normals of each segment pointing to the outer side of the corner
+    let side = if cross > 0.0 { -1.0 } else { 1.0 };
+    let n0 = (-d0.1 * side, d0.0 * side);
+    let n1 = (-d1.1 * side, d1.0 * side);
+    let corner0 = (p.0 + n0.0 * half, p.1 + n0.1 * half);
+    let corner1 = (p.0 + n1.0 * half, p.1 + n1.1 * half);
+
+    // the tip of the miter is on the bisector of both normals, at half / cos(angle / 2) from p
+    // where angle is the angle between the normals. Like svg, the limit is compared with the
+    // length of the miter relative to the width: 1 / cos(angle / 2). With cos(angle) = n0 . n1
+    // and cos(angle / 2)^2 = (1 + cos(angle)) / 2 we avoid any trigonometry
+    let cos = n0.0 * n1.0 + n0.1 * n1.1;
+    let miter_ratio = 1.0 / ((1.0 + cos) / 2.0).sqrt();
+    if core.line_join == LineJoin::Miter && miter_ratio <= core.miter_limit {
+        let scale = half / (1.0 + cos);
+        let tip = (p.0 + (n0.0 + n1.0) * scale, p.1 + (n0.1 + n1.1) * scale);
+        add_convex(pixels, &[p, corner0, tip, corner1]);
+    } else {
+        add_convex(pixels, &[p, corner0, corner1]);
+    }
+}
+
+/// returns the direction from a to b with length 1. a and b must be different
+fn direction(a: PointFloat, b: PointFloat) -> PointFloat {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    (dx / length, dy / length)
+}
+
+/// adds the pixels of the rectangle that goes from a to b with "half" width to each side
+fn add_segment(pixels: &mut StrokePixels, a: PointFloat, b: PointFloat, half: f32) {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length == 0.0 {
+        return;
+    }
+
+    // normal of the segment with the length of half the width
+    let (nx, ny) = (-dy / length * half, dx / length * half);
+    let corners = [
+        (a.0 + nx, a.1 + ny),
+        (b.0 + nx, b.1 + ny),
+        (b.0 - nx, b.1 - ny),
+        (a.0 - nx, a.1 - ny),
+    ];
+
+    add_convex(pixels, &corners);
+}
+
+/// adds the pixels inside a convex polygon. The corners can go in any direction
+fn add_convex(pixels: &mut StrokePixels, corners: &[PointFloat]) {
+    let (min, max) = bounding_box(corners);
+
+    for y in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            let p = sample(x, y);
+            let mut positive = false;
+            let mut negative = false;
+
+            // the point is inside if its on the same side of every edge
+            for i in 0..corners.len() {
+                let a = corners[i];
+                let b = corners[(i + 1) % corners.len()];
+                let side = (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+                positive |= side > 0.0;
+                negative |= side < 0.0;
+            }
+
+            if !(positive && negative) {
+                pixels.insert((x, y));
+            }
+        }
+    }
+}
+
+/// adds the pixels inside a circle
+fn add_disc(pixels: &mut StrokePixels, center: PointFloat, radius: f32) {
+    let corners = [
+        (center.0 - radius, center.1 - radius),
+        (center.0 + radius, center.1 + radius),
+    ];
+    let (min, max) = bounding_box(&corners);
+
+    for y in min.1..=max.1 {
+        for x in min.0..=max.0 {
+            let p = sample(x, y);
+            let (dx, dy) = (p.0 - center.0, p.1 - center.1);
+            if dx * dx + dy * dy <= radius * radius {
+                pixels.insert((x, y));
+            }
+        }
+    }
+}
+
+/// returns the smallest and biggest pixel that can be covered by the given points
+fn bounding_box(points: &[PointFloat]) -> ((i32, i32), (i32, i32)) {
+    let min_x = points.iter().map(|p| p.0).fold(f32::MAX, f32::min);
+    let min_y = points.iter().map(|p| p.1).fold(f32::MAX, f32::min);
+    let max_x = points.iter().map(|p| p.0).fold(f32::MIN, f32::max);
+    let max_y = points.iter().map(|p| p.1).fold(f32::MIN, f32::max);
+
+    (
+        (min_x.floor() as i32, min_y.floor() as i32),
+        (max_x.ceil() as i32, max_y.ceil() as i32),
+    )
+}
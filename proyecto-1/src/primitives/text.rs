@@ -0,0 +1,148 @@
+use std::sync::OnceLock;
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+
+use super::antialias::{add_coverage, draw_coverage, Coverage};
+use crate::canvas::Canvas;
+use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
+
+/// the font of every text. Its inside the executable so a drawing looks the same on any machine,
+/// and the headless render doesnt need anything installed
+const FONT_DATA: &[u8] = include_bytes!("../../assets/fonts/Ubuntu-Light.ttf");
+
+/// how far from the box of the letters a click still selects the text, in pixels
+const HIT_TEST_MARGIN: f32 = 3.0;
+
+/// how much of a pixel the letters must cover to draw it when the text isnt anti-aliased. Half a
+/// pixel loses the thin strokes of the font on small texts
+const ALIASED_COVERAGE: f32 = 0.3;
+
+/// loads the bundled font the first time its needed
+fn font() -> &'static FontRef<'static> {
+    static FONT: OnceLock<FontRef<'static>> = OnceLock::new();
+    FONT.get_or_init(|| FontRef::try_from_slice(FONT_DATA).expect("the bundled font is valid"))
+}
+
+/// text written with the bundled font. The only point of the core is the top left corner of the
+/// first line, the letters are drawn with the border color
+pub struct Text {
+    core: ShapeCore,
+}
+
+impl ShapeImpl for Text {
+    fn new(core: ShapeCore) -> Text {
+        Text { core }
+    }
+
+    fn get_core_mut(&mut self) -> &mut ShapeCore {
+        &mut self.core
+    }
+
+    fn get_core(&self) -> ShapeCore {
+        self.core.clone()
+    }
+
+    fn draw<'a>(&self, canvas: &mut Canvas<'a>) {
+        self.draw_with_color(self.core.color, canvas);
+    }
+
+    /// the letters are rasterized into how much of each pixel they cover. Anti-aliased texts use
+    /// that coverage as the alpha, like the other shapes. The pixel exact ones draw the pixels
+    /// covered enough (see ALIASED_COVERAGE)
+    fn draw_with_color<'a>(&self, color: RGBA, canvas: &mut Canvas<'a>) {
+        let coverage = text_coverage(&self.core);
+        if self.core.antialias {
+            draw_coverage(&coverage, &Coverage::new(), color, color, canvas);
+        } else {
+            for (&(x, y), &c) in coverage.iter() {
+                if c >= ALIASED_COVERAGE {
+                    canvas.set_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    /// a text is hit anywhere on the box of its lines, not only over the letters. Clicking
+    /// between the strokes of a letter would be frustrating
+    fn hit_test(&self, point: Point) -> bool {
+        let anchor = self.core.points[0];
+        let (width, height) = text_size(&self.core.text, self.core.font_size);
+        let (dx, dy) = ((point.0 - anchor.0) as f32, (point.1 - anchor.1) as f32);
+
+        width > 0.0
+            && dx >= -HIT_TEST_MARGIN
+            && dy >= -HIT_TEST_MARGIN
+            && dx <= width + HIT_TEST_MARGIN
+            && dy <= height + HIT_TEST_MARGIN
+    }
+}
+
+/// calls place with each letter of the text and the position of its origin (left end of the
+/// baseline), relative to the top left corner of the first line. Each line starts below the
+/// previous one, and the letters of a line are separated by their advance and kerning
+fn layout(text: &str, font_size: f32, mut place: impl FnMut(char, f32, f32)) {
+    let font = font().as_scaled(PxScale::from(font_size));
+    let line_height = font.height() + font.line_gap();
+
+    for (i, line) in text.lines().enumerate() {
+        let baseline = font.ascent() + line_height * i as f32;
+        let mut caret = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += font.kern(previous, id);
+            }
+            place(c, caret, baseline);
+            caret += font.h_advance(id);
+            previous = Some(id);
+        }
+    }
+}
+
+/// width of the longest line and height of all the lines of a text
+fn text_size(text: &str, font_size: f32) -> (f32, f32) {
+    let font = font().as_scaled(PxScale::from(font_size));
+    let lines = text.lines().count();
+    if lines == 0 {
+        return (0.0, 0.0);
+    }
+
+    let mut width: f32 = 0.0;
+    for line in text.lines() {
+        let mut line_width = 0.0;
+        layout(line, font_size, |c, x, _| {
+            line_width = x + font.h_advance(font.glyph_id(c));
+        });
+        width = width.max(line_width);
+    }
+
+    let height = font.height() * lines as f32 + font.line_gap() * (lines - 1) as f32;
+    (width, height)
+}
+
+/// how much of each pixel the letters of the text cover. The outlines of the font are rasterized
+/// by ab_glyph, which gives the coverage of the square from (x, y) to (x + 1, y + 1). Our pixels
+/// have their center on (x, y), so the letters are moved half a pixel to match them
+fn text_coverage(core: &ShapeCore) -> Coverage {
+    let font = font();
+    let scale = PxScale::from(core.font_size);
+    let anchor = core.points[0];
+    let mut coverage = Coverage::new();
+
+    layout(&core.text, core.font_size, |c, x, baseline| {
+        let position = point(anchor.0 as f32 + x + 0.5, anchor.1 as f32 + baseline + 0.5);
+        let glyph = font.glyph_id(c).with_scale_and_position(scale, position);
+        let Some(outline) = font.outline_glyph(glyph) else {
+            // spaces have no outline
+            return;
+        };
+
+        let bounds = outline.px_bounds();
+        let (left, top) = (bounds.min.x as i32, bounds.min.y as i32);
+        // letters that overlap (like with kerning) keep the biggest coverage of each pixel
+        outline.draw(|x, y, c| add_coverage(&mut coverage, left + x as i32, top + y as i32, c));
+    });
+
+    coverage
+}
@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use super::line::{draw_line, line_hit_test};
+use super::antialias::{draw_coverage, region_coverage, Coverage};
+use super::dash::DashPattern;
+use super::line::{
+    antialiased_border, draw_line, draw_styled_line, line_hit_test, polyline_pixels,
+    segment_hit_test,
+};
+use super::stroke::{draw_stroke, sample, stroke_polyline};
 use crate::canvas::Canvas;
 use crate::core::{Point, ShapeCore, ShapeImpl, RGBA};
 
@@ -55,15 +61,43 @@ impl ShapeImpl for Triangle {
         let cp2 = edge_side_check(b, c, p);
         let cp3 = edge_side_check(c, a, p);
 
-        (cp1 >= 0 && cp2 >= 0 && cp3 >= 0) || (cp1 <= 0 && cp2 <= 0 && cp3 <= 0)
+        // half of a wide border is outside the triangle, clicking it also selects the triangle
+        let half_width = self.core.stroke_width as u64 / 2;
+        let on_border = half_width > 0
+            && (segment_hit_test(a, b, p, half_width)
+                || segment_hit_test(b, c, p, half_width)
+                || segment_hit_test(c, a, p, half_width));
+
+        (cp1 >= 0 && cp2 >= 0 && cp3 >= 0) || (cp1 <= 0 && cp2 <= 0 && cp3 <= 0) || on_border
     }
 }
 
 /// draws a triangle. if we dont have enough points means we are only drawing the first line
 /// if we have 3 points we draw 3 lines without overlapping
 fn draw_triangle(core: &ShapeCore, canvas: &mut Canvas) {
+    let dash = DashPattern::new(core);
+
     if core.points.len() <= 2 {
-        draw_line(&core, canvas, false);
+        if core.stroke_width > 1 || dash.is_some() || core.antialias {
+            draw_styled_line(core, canvas);
+        } else {
+            draw_line(core, canvas, false);
+        }
+    } else if core.antialias {
+        draw_antialiased_triangle(core, canvas);
+    } else if core.stroke_width > 1 {
+        draw_thick_triangle(core, canvas);
+    } else if let Some(dash) = dash {
+        // the whole border is used to know where not to fill, even the pixels on the gaps
+        let border = polyline_pixels(&core.points, true);
+        for (x, y) in dash.filter_pixels(&border) {
+            canvas.set_pixel(x, y, core.color);
+        }
+
+        if !core.fill_color.is_transparent() {
+            let pixel_cache = border.into_iter().map(|p| (p, true)).collect();
+            fill_triangle(core, canvas, &pixel_cache);
+        }
     } else {
         // we dont store the pixel cache on the triangle because its data depends on the triangle
         // control points and if we modify them we end up needing another pixel cache
@@ -86,13 +120,69 @@ fn draw_triangle(core: &ShapeCore, canvas: &mut Canvas) {
     }
 }
 
+/// draws a triangle with a border wider than 1 pixel. The border is a closed stroke (see
+/// stroke.rs) and the inside is every pixel inside the triangle that is not part of the border
+fn draw_thick_triangle(core: &ShapeCore, canvas: &mut Canvas) {
+    let border = stroke_polyline(&core.points, core, true);
+    draw_stroke(&border, core.color, canvas);
+
+    if core.fill_color.is_transparent() {
+        return;
+    }
+
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    let min_x = core.points.iter().map(|p| p.0).min().unwrap();
+    let max_x = core.points.iter().map(|p| p.0).max().unwrap();
+    let min_y = core.points.iter().map(|p| p.1).min().unwrap();
+    let max_y = core.points.iter().map(|p| p.1).max().unwrap();
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            if !border.contains(&(x, y)) && is_within_triangle(&points, sample(x, y)) {
+                canvas.set_pixel(x, y, core.fill_color);
+            }
+        }
+    }
+}
+
+/// draws an anti-aliased triangle (see antialias.rs). The fill goes until the middle of the border,
+/// the border is drawn over it
+fn draw_antialiased_triangle(core: &ShapeCore, canvas: &mut Canvas) {
+    let points: Vec<PointFloat> = core.points.iter().map(|p| (*p).into()).collect();
+    let border = antialiased_border(&points, core, true);
+
+    let fill = if core.fill_color.is_transparent() {
+        Coverage::new()
+    } else {
+        let min_x = core.points.iter().map(|p| p.0).min().unwrap();
+        let max_x = core.points.iter().map(|p| p.0).max().unwrap();
+        let min_y = core.points.iter().map(|p| p.1).min().unwrap();
+        let max_y = core.points.iter().map(|p| p.1).max().unwrap();
+        region_coverage((min_x, min_y), (max_x, max_y), |p| {
+            is_within_triangle(&points, p)
+        })
+    };
+
+    draw_coverage(&border, &fill, core.color, core.fill_color, canvas);
+}
+
+/// checks if the point is inside the triangle, the same way as the hit test but with floats
+fn is_within_triangle(points: &[PointFloat], p: PointFloat) -> bool {
+    let side = |a: PointFloat, b: PointFloat| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+    let s1 = side(points[0], points[1]);
+    let s2 = side(points[1], points[2]);
+    let s3 = side(points[2], points[0]);
+
+    (s1 >= 0.0 && s2 >= 0.0 && s3 >= 0.0) || (s1 <= 0.0 && s2 <= 0.0 && s3 <= 0.0)
+}
+
 /// this function fills the triangle given the shape core.
 /// to fill it we build the top triangle from the horizontal line on the corner. Then do the same
 /// on the bottom triangle
 fn fill_triangle(core: &ShapeCore, canvas: &mut Canvas, drawn: &HashMap<(i32, i32), bool>) {
     let mut points = core.points.clone();
     // we sort the points by "y" to get the lowest one first
-    points.sort_by(|a, b| a.1.cmp(&b.1));
+    points.sort_by_key(|p| p.1);
 
     let p1: (f32, f32) = points[0].into();
     let p2: (f32, f32) = points[1].into();
@@ -104,8 +194,8 @@ fn fill_triangle(core: &ShapeCore, canvas: &mut Canvas, drawn: &HashMap<(i32, i3
 
     // the 4th point from the horizontal line intersection with the triangle side
     let p4 = (
-        (p1.0 + ((p2.1 - p1.1) as f32 / (p3.1 - p1.1) as f32 * (p3.0 - p1.0) as f32)),
-        p2.1 as f32,
+        (p1.0 + ((p2.1 - p1.1) / (p3.1 - p1.1) * (p3.0 - p1.0))),
+        p2.1,
     );
     // first fills the top triangle then the bottom one
     fill_top_triangle(p1, p2, p4, core.fill_color, canvas, drawn);
@@ -232,10 +322,10 @@ pub fn draw_line_for_triangle<'a>(
     let a = points[0];
     let b = points[1];
 
-    let mut dx = (b.0 - a.0) as i32;
-    let mut dy = (b.1 - a.1) as i32;
-    let x_inc = if dx < 0 { -1 as i32 } else { 1 };
-    let y_inc = if dy < 0 { -1 as i32 } else { 1 };
+    let mut dx = b.0 - a.0;
+    let mut dy = b.1 - a.1;
+    let x_inc = if dx < 0 { -1 } else { 1 };
+    let y_inc = if dy < 0 { -1 } else { 1 };
 
     if dx < 0 {
         dx *= -1;
@@ -248,9 +338,15 @@ pub fn draw_line_for_triangle<'a>(
     let inc_e = -2 * (if run_on_x { dy } else { dx });
     let inc_ne = 2 * (dx - dy) * (if run_on_x { 1 } else { -1 });
 
-    let mut d = (dx - 2 * dy) * if run_on_x { 1 } else { -1 };
-    let mut x = a.0 as i32;
-    let mut y = a.1 as i32;
+    // the decision variable is the same for both axis, we only swap the roles of dx and dy
+    let mut d = if run_on_x { dx - 2 * dy } else { dy - 2 * dx };
+    // when d == 0 the line passes exactly between two pixels and both are valid. If we always pick
+    // the same one, the line from a to b and the one from b to a end up with different pixels.
+    // So we only step on a tie when moving forward on the main axis, that way the choice depends
+    // on the position of the pixels and not on the direction of the line
+    let step_on_tie = if run_on_x { x_inc > 0 } else { y_inc > 0 };
+    let mut x = a.0;
+    let mut y = a.1;
 
     let mut draw_if_available = |x, y| {
         if drawn.get(&(x, y)).is_none() {
@@ -262,8 +358,8 @@ pub fn draw_line_for_triangle<'a>(
     draw_if_available(x, y);
 
     if run_on_x {
-        while x > b.0 || x < b.0 {
-            if d <= 0 {
+        while x != b.0 {
+            if d < 0 || (d == 0 && step_on_tie) {
                 d += inc_ne;
                 y += y_inc;
             } else {
@@ -275,8 +371,8 @@ pub fn draw_line_for_triangle<'a>(
             draw_if_available(x, y);
         }
     } else {
-        while y > b.1 || y < b.1 {
-            if d <= 0 {
+        while y != b.1 {
+            if d < 0 || (d == 0 && step_on_tie) {
                 d += inc_ne;
                 x += x_inc;
             } else {
@@ -287,11 +383,5 @@ pub fn draw_line_for_triangle<'a>(
 
             draw_if_available(x, y);
         }
-
-        x += x_inc;
-        // edge case found. we want to draw a full line from a to b inclusive
-        // this ensures that b is drawn, when reaching this else condition it is not drawn
-        // This was tested to check if there are overlaps on the canvas
-        draw_if_available(x, y);
     }
 }
@@ -0,0 +1,112 @@
+// Closest point checks. The closest point of a bezier curve is found with its t and distance, the
+// curve is hit by that distance, and control points are added under a click one undo at a time.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    primitives::{closest_point, new_shape_from_core},
+};
+
+fn bezier(points: &[(i32, i32)]) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Bezier,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn closest_point_gives_t_and_distance() {
+    // the middle of this curve is (100, 100), and the curve is horizontal there
+    let arch = bezier(&[(0, 0), (100, 200), (200, 0)]);
+    let (t, distance) = closest_point(&arch, Point(100, 90));
+    assert!((t - 0.5).abs() < 1e-3, "{t}");
+    assert!((distance - 10.0).abs() < 1e-2, "{distance}");
+
+    // past the end of the curve the closest point is the end
+    let (t, distance) = closest_point(&arch, Point(220, 0));
+    assert!(t > 0.999, "{t}");
+    assert!((distance - 20.0).abs() < 1e-2, "{distance}");
+
+    // on a loop the closest of both sides is found
+    let looped = bezier(&[(0, 100), (200, 0), (0, 0), (200, 100)]);
+    let (_, distance) = closest_point(&looped, Point(100, 30));
+    assert!(distance < 5.0, "{distance}");
+
+    // the arc of a weighted curve is a circle of radius 100 around (10, 10)
+    let circle = ShapeCore {
+        weights: vec![1.0, FRAC_1_SQRT_2, 1.0],
+        ..bezier(&[(110, 10), (110, 110), (10, 110)])
+    };
+    let (_, distance) = closest_point(&circle, Point(10, 10));
+    assert!((distance - 100.0).abs() < 1e-2, "{distance}");
+}
+
+#[test]
+fn the_curve_is_hit_by_its_distance() {
+    let arch = bezier(&[(0, 0), (100, 200), (200, 0)]);
+    let shape = new_shape_from_core(arch.clone());
+    assert!(shape.hit_test(Point(100, 80)));
+    // the control point is far from the curve
+    assert!(!shape.hit_test(Point(100, 200)));
+    assert!(!shape.hit_test(Point(100, 40)));
+
+    // wide curves are hit further away
+    let wide = new_shape_from_core(ShapeCore {
+        stroke_width: 40,
+        ..arch
+    });
+    assert!(wide.hit_test(Point(100, 55)));
+}
+
+#[test]
+fn control_points_are_added_under_the_click() {
+    let arch = bezier(&[(0, 0), (100, 200), (200, 0)]);
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(arch.clone()));
+
+    // the first half of the curve is between the first and the second control point
+    state.update_shape(0, UpdateOp::InsertControlPoint(Point(40, 60)));
+    let once = state.get_object(0).get_core();
+    assert_eq!(
+        once.points,
+        vec![Point(0, 0), Point(40, 60), Point(100, 200), Point(200, 0)]
+    );
+    state.update_shape(0, UpdateOp::InsertControlPoint(Point(170, 50)));
+    assert_eq!(state.get_object(0).get_core().points[3], Point(170, 50));
+
+    // each one is undone on its own
+    state.undo();
+    assert!(state.get_object(0).get_core() == once);
+    state.undo();
+    assert!(state.get_object(0).get_core() == arch);
+
+    // weighted points get a weight of 1
+    let mut shape = new_shape_from_core(ShapeCore {
+        weights: vec![1.0, 2.0, 1.0],
+        ..arch
+    });
+    shape.update(&UpdateOp::InsertControlPoint(Point(150, 90)));
+    let core = shape.get_core();
+    assert_eq!(core.weights, vec![1.0, 2.0, 1.0, 1.0]);
+    assert!(core.validate().is_ok());
+}
+
+#[test]
+fn moving_the_subdivision_point_is_not_an_undo_step() {
+    let arch = bezier(&[(0, 0), (100, 200), (200, 0)]);
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(arch.clone()));
+    state.update_shape(0, UpdateOp::ControlPoint(1, Point(100, 150)));
+
+    // like the clicks on the curve, these only move where the curve is cut
+    state.update_shape(0, UpdateOp::UpdateSubdivide(0.2));
+    state.update_shape(0, UpdateOp::UpdateSubdivide(0.7));
+
+    // the first undo already brings the control point back
+    state.undo();
+    assert!(state.get_object(0).get_core() == arch);
+}
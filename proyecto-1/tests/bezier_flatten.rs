@@ -0,0 +1,107 @@
+// Bezier flattening checks. The lines a curve is drawn with stay close to the exact curve, even
+// with many control points, and they reach both ends of it.
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, RGBA},
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+fn bezier(points: &[(i32, i32)]) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Bezier,
+        ..Default::default()
+    }
+}
+
+/// the pixels the curve draws
+fn pixels(core: &ShapeCore) -> Vec<(f32, f32)> {
+    let mut frame = Frame::new(220, 220);
+    let mut canvas = frame.recording_canvas();
+    new_shape_from_core(core.clone()).draw(&mut canvas);
+    canvas
+        .written_pixels()
+        .into_iter()
+        .map(|(x, y, _)| (x as f32, y as f32))
+        .collect()
+}
+
+/// points of the exact curve, from the bernstein polynomials
+fn exact(core: &ShapeCore, samples: usize) -> Vec<(f32, f32)> {
+    let n = core.points.len() - 1;
+    (0..=samples)
+        .map(|s| {
+            let t = s as f32 / samples as f32;
+            let mut binomial = 1.0;
+            let mut point = (0.0, 0.0);
+            for (i, p) in core.points.iter().enumerate() {
+                let b = binomial * t.powi(i as i32) * (1.0 - t).powi((n - i) as i32);
+                point.0 += b * p.0 as f32;
+                point.1 += b * p.1 as f32;
+                binomial = binomial * (n - i) as f32 / (i + 1) as f32;
+            }
+            point
+        })
+        .collect()
+}
+
+fn closest(p: (f32, f32), others: &[(f32, f32)]) -> f32 {
+    others
+        .iter()
+        .map(|o| (p.0 - o.0).hypot(p.1 - o.1))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// every pixel is close to the curve and every point of the curve is close to a pixel, so there
+/// are no gaps either
+fn assert_follows_the_curve(core: &ShapeCore) {
+    let drawn = pixels(core);
+    let curve = exact(core, 2000);
+    for p in &drawn {
+        assert!(closest(*p, &curve) <= 1.0, "{p:?} is not on the curve");
+    }
+    for p in &curve {
+        assert!(closest(*p, &drawn) <= 1.0, "{p:?} is not drawn");
+    }
+}
+
+#[test]
+fn lines_stay_on_the_curve() {
+    // a tight turn
+    assert_follows_the_curve(&bezier(&[(10, 200), (200, 10), (10, 10), (200, 200)]));
+    // a long gentle curve
+    assert_follows_the_curve(&bezier(&[(5, 100), (100, 90), (215, 100)]));
+    // a lot of control points, nothing is rounded until the end
+    assert_follows_the_curve(&bezier(&[
+        (10, 10),
+        (200, 30),
+        (20, 60),
+        (210, 80),
+        (10, 110),
+        (200, 130),
+        (20, 160),
+        (210, 180),
+        (10, 200),
+        (200, 210),
+        (110, 110),
+    ]));
+}
+
+#[test]
+fn both_ends_are_drawn() {
+    for core in [
+        bezier(&[(10, 10), (100, 200), (200, 15)]),
+        bezier(&[(10, 10), (10, 10)]),
+        bezier(&[(30, 40), (31, 40), (30, 41)]),
+    ] {
+        let drawn = pixels(&core);
+        for end in [core.points[0], core.points[core.points.len() - 1]] {
+            assert!(
+                drawn.contains(&(end.0 as f32, end.1 as f32)),
+                "{end:?} is not drawn"
+            );
+        }
+    }
+}
@@ -0,0 +1,66 @@
+// Helpers shared by the tests that draw on a recording canvas. Each test file is its own crate and
+// uses only some of them, so the unused ones are allowed.
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use paint_app::{
+    canvas::Canvas,
+    core::{ShapeCore, RGBA},
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+/// width and height of the canvas the shapes are drawn on
+pub const SIZE: u32 = 200;
+pub const TRANSLUCENT: RGBA = RGBA::new(200, 100, 50, 128);
+pub const NO_FILL: RGBA = RGBA::new(0, 0, 0, 0);
+
+/// pixels set while drawing, with the times each one was set
+pub type Writes = HashMap<(i32, i32), u32>;
+
+/// draws with the given function on a recording canvas and returns what was written
+pub fn record(draw: impl FnOnce(&mut Canvas)) -> Writes {
+    let mut frame = Frame::new(SIZE, SIZE);
+    let mut canvas = frame.recording_canvas();
+    draw(&mut canvas);
+    canvas
+        .written_pixels()
+        .into_iter()
+        .map(|(x, y, count)| ((x, y), count))
+        .collect()
+}
+
+/// draws the shape on a recording canvas and returns what was written
+pub fn record_shape(core: ShapeCore) -> Writes {
+    record(|canvas| new_shape_from_core(core).draw(canvas))
+}
+
+pub fn assert_no_overdraw(writes: &Writes, what: &str) {
+    let twice: Vec<_> = writes.iter().filter(|(_, count)| **count > 1).collect();
+    assert!(
+        twice.is_empty(),
+        "{what} draws pixels more than once: {twice:?}"
+    );
+}
+
+/// checks that every pixel can be reached from any other moving to one of its 8 neighbours
+pub fn assert_connected(pixels: &HashSet<(i32, i32)>, what: &str) {
+    let Some(start) = pixels.iter().next() else {
+        return;
+    };
+
+    let mut visited = HashSet::from([*start]);
+    let mut pending = vec![*start];
+    while let Some((x, y)) = pending.pop() {
+        for nx in (x - 1)..=(x + 1) {
+            for ny in (y - 1)..=(y + 1) {
+                if pixels.contains(&(nx, ny)) && visited.insert((nx, ny)) {
+                    pending.push((nx, ny));
+                }
+            }
+        }
+    }
+
+    assert_eq!(visited.len(), pixels.len(), "{what} has gaps");
+}
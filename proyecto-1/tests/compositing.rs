@@ -0,0 +1,222 @@
+// Compositing tests. color1 + color2 paints color1 over color2 ("source over") taking in account
+// the alpha of both colors, so transparent backgrounds stay transparent and translucent shapes
+// can be layered.
+//
+// Shapes can also use a blend mode, which changes their color based on what is below before it is
+// painted over.
+
+use paint_app::{
+    core::{BlendMode, Point, Shape, ShapeCore, RGBA},
+    draw_state::DrawState,
+    headless::{render, Frame},
+    primitives::new_shape_from_core,
+};
+
+const TRANSPARENT: RGBA = RGBA::new(0, 0, 0, 0);
+
+/// the same operation with floats, used as reference for the integer version
+fn source_over(src: RGBA, dst: RGBA) -> [f32; 4] {
+    let sa = src[3] as f32 / 255.0;
+    let da = dst[3] as f32 / 255.0;
+    let alpha = sa + da * (1.0 - sa);
+    let channel = |i: usize| {
+        if alpha == 0.0 {
+            0.0
+        } else {
+            (src[i] as f32 * sa + dst[i] as f32 * da * (1.0 - sa)) / alpha
+        }
+    };
+
+    [channel(0), channel(1), channel(2), alpha * 255.0]
+}
+
+#[test]
+fn opaque_source_replaces_destination() {
+    let src = RGBA::new(10, 20, 30, 255);
+    assert_eq!(src + RGBA::new(200, 100, 50, 255), src);
+    assert_eq!(src + RGBA::new(200, 100, 50, 90), src);
+    assert_eq!(src + TRANSPARENT, src);
+}
+
+#[test]
+fn transparent_source_keeps_destination() {
+    let dst = RGBA::new(200, 100, 50, 90);
+    assert_eq!(RGBA::new(10, 20, 30, 0) + dst, dst);
+    assert_eq!(TRANSPARENT + TRANSPARENT, TRANSPARENT);
+}
+
+#[test]
+fn over_transparent_destination_keeps_source() {
+    // before, a transparent background was treated as opaque black
+    let src = RGBA::new(255, 0, 0, 128);
+    assert_eq!(src + TRANSPARENT, src);
+    assert_eq!(src + RGBA::new(0, 255, 0, 0), src);
+}
+
+#[test]
+fn half_alpha_over_half_alpha() {
+    // 0.5 + 0.5 * 0.5 = 0.75 of alpha, and the source weights twice the destination
+    let result = RGBA::new(255, 0, 0, 128) + RGBA::new(0, 0, 255, 128);
+    assert_eq!(result, RGBA::new(170, 0, 85, 192));
+}
+
+#[test]
+fn matches_float_reference_rounded() {
+    let values = [0, 1, 63, 127, 128, 200, 254, 255];
+    for sa in values {
+        for da in values {
+            for (sc, dc) in [(0, 255), (255, 0), (17, 230), (128, 128)] {
+                let src = RGBA::new(sc, sc, 255 - sc, sa);
+                let dst = RGBA::new(dc, 255 - dc, dc, da);
+                let expected = source_over(src, dst);
+                let result = src + dst;
+
+                for i in 0..4 {
+                    let error = (result[i] as f32 - expected[i]).abs();
+                    assert!(
+                        error <= 0.5 + 1e-3,
+                        "{src} over {dst} gives {result}, expected {expected:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn translucent_shapes_on_transparent_background() {
+    let rect = |p1: (i32, i32), p2: (i32, i32), fill: RGBA| ShapeCore {
+        points: vec![Point::from(p1), Point::from(p2)],
+        color: fill,
+        fill_color: fill,
+        shape_type: Shape::Rectangle,
+        ..Default::default()
+    };
+    let red = RGBA::new(255, 0, 0, 128);
+    let blue = RGBA::new(0, 0, 255, 128);
+
+    let mut frame = Frame::new(20, 20);
+    {
+        let mut canvas = frame.canvas();
+        canvas.clear(TRANSPARENT);
+        new_shape_from_core(rect((0, 0), (12, 12), red)).draw(&mut canvas);
+        new_shape_from_core(rect((6, 6), (18, 18), blue)).draw(&mut canvas);
+    }
+
+    // only red, only blue, both and none
+    assert_eq!(frame.get_pixel(2, 2), red);
+    assert_eq!(frame.get_pixel(16, 16), blue);
+    assert_eq!(frame.get_pixel(9, 9), blue + red);
+    assert_eq!(frame.get_pixel(19, 0), TRANSPARENT);
+}
+
+/// paints src over dst on a single pixel with the given blend mode
+fn paint(mode: BlendMode, src: RGBA, dst: RGBA) -> RGBA {
+    let mut frame = Frame::new(1, 1);
+    {
+        let mut canvas = frame.canvas();
+        canvas.clear(dst);
+        canvas.set_blend_mode(mode);
+        canvas.set_pixel(0, 0, src);
+    }
+    frame.get_pixel(0, 0)
+}
+
+#[test]
+fn blend_modes_over_opaque_destination() {
+    let src = RGBA::new(200, 100, 50, 255);
+    let dst = RGBA::new(100, 150, 200, 255);
+    let expected = [
+        (BlendMode::Normal, RGBA::new(200, 100, 50, 255)),
+        (BlendMode::Multiply, RGBA::new(78, 59, 39, 255)),
+        (BlendMode::Screen, RGBA::new(222, 191, 211, 255)),
+        // r is dark below so it multiplies, g and b are light so they screen
+        (BlendMode::Overlay, RGBA::new(157, 127, 167, 255)),
+        (BlendMode::Add, RGBA::new(255, 250, 250, 255)),
+        (BlendMode::Difference, RGBA::new(100, 50, 150, 255)),
+        (BlendMode::Darken, RGBA::new(100, 100, 50, 255)),
+        (BlendMode::Lighten, RGBA::new(200, 150, 200, 255)),
+    ];
+
+    for (mode, color) in expected {
+        assert_eq!(paint(mode, src, dst), color, "{mode}");
+    }
+}
+
+#[test]
+fn blend_modes_over_transparent_destination_keep_source() {
+    // there is nothing below to blend with
+    let src = RGBA::new(200, 100, 50, 255);
+    for mode in BlendMode::ALL {
+        assert_eq!(paint(mode, src, TRANSPARENT), src, "{mode}");
+        assert_eq!(mode.blend(src, RGBA::new(90, 10, 30, 0)), src, "{mode}");
+    }
+}
+
+#[test]
+fn neutral_colors_do_not_change_destination() {
+    let white = RGBA::new(255, 255, 255, 255);
+    let black = RGBA::new(0, 0, 0, 255);
+    for value in [0, 1, 63, 127, 128, 200, 254, 255] {
+        let dst = RGBA::new(value, 255 - value, value / 2, 255);
+        assert_eq!(paint(BlendMode::Multiply, white, dst), dst);
+        assert_eq!(paint(BlendMode::Screen, black, dst), dst);
+        assert_eq!(paint(BlendMode::Add, black, dst), dst);
+        assert_eq!(paint(BlendMode::Difference, black, dst), dst);
+        assert_eq!(paint(BlendMode::Darken, white, dst), dst);
+        assert_eq!(paint(BlendMode::Lighten, black, dst), dst);
+    }
+}
+
+#[test]
+fn translucent_blend_is_painted_over() {
+    // the blended color keeps the alpha of the source, then is painted over as usual
+    let src = RGBA::new(0, 0, 0, 128);
+    let dst = RGBA::new(200, 100, 50, 255);
+    let blended = BlendMode::Screen.blend(src, dst);
+    assert_eq!(blended, RGBA::new(200, 100, 50, 128));
+    assert_eq!(paint(BlendMode::Screen, src, dst), dst);
+    assert_eq!(paint(BlendMode::Multiply, src, dst), src + dst);
+}
+
+#[test]
+fn drawings_use_the_blend_mode_of_each_shape() {
+    let rect = |p1: (i32, i32), p2: (i32, i32), fill: RGBA, blend_mode: BlendMode| {
+        new_shape_from_core(ShapeCore {
+            points: vec![Point::from(p1), Point::from(p2)],
+            color: fill,
+            fill_color: fill,
+            shape_type: Shape::Rectangle,
+            blend_mode,
+            ..Default::default()
+        })
+    };
+    let yellow = RGBA::new(255, 255, 0, 255);
+    let cyan = RGBA::new(0, 255, 255, 255);
+
+    let mut state = DrawState::new();
+    state.change_background_color(RGBA::new(255, 255, 255, 255));
+    state.add_shape(rect((0, 0), (12, 12), yellow, BlendMode::Normal));
+    state.add_shape(rect((6, 6), (18, 18), cyan, BlendMode::Multiply));
+    let frame = render(&state, 20, 20);
+
+    // multiply over white is the same color, over yellow only green is left
+    assert_eq!(frame.get_pixel(2, 2), yellow);
+    assert_eq!(frame.get_pixel(16, 16), cyan);
+    assert_eq!(frame.get_pixel(9, 9), RGBA::new(0, 255, 0, 255));
+}
+
+#[test]
+fn blend_mode_is_optional_on_saved_shapes() {
+    // drawings saved before blend modes existed must still load
+    let old = r#"{"points":[[0,0],[5,5]],"color":[255,0,0,255],"fill_color":[0,0,0,0],"shape_type":"Line"}"#;
+    let core: ShapeCore = serde_json::from_str(old).unwrap();
+    assert_eq!(core.blend_mode, BlendMode::Normal);
+
+    let core = ShapeCore {
+        blend_mode: BlendMode::Overlay,
+        ..core
+    };
+    let saved: ShapeCore = serde_json::from_str(&serde_json::to_string(&core).unwrap()).unwrap();
+    assert_eq!(saved.blend_mode, BlendMode::Overlay);
+}
@@ -0,0 +1,544 @@
+// Pixel coverage tests. The rasterizers claim that no pixel is drawn twice (which would make
+// translucent colors darker on that pixel), that lines go from a to b inclusive and that borders have
+// no gaps. Here we draw random shapes on a recording canvas, which counts the times each pixel is
+// set, and check those claims. The random generator uses a fixed seed, so a failure can always be
+// reproduced.
+
+use std::collections::{HashMap, HashSet};
+
+use paint_app::{
+    core::{FillRule, LineCap, LineJoin, Point, Shape, ShapeCore, RGBA},
+    headless::Frame,
+    primitives::{draw_line, draw_line_for_triangle, new_shape_from_core},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod common;
+use common::{assert_connected, assert_no_overdraw, record, Writes, NO_FILL, SIZE, TRANSLUCENT};
+
+const CASES: usize = 500;
+
+fn core(shape_type: Shape, points: Vec<Point>, fill_color: RGBA) -> ShapeCore {
+    ShapeCore {
+        points,
+        color: TRANSLUCENT,
+        fill_color,
+        shape_type,
+        ..Default::default()
+    }
+}
+
+/// random point that leaves some margin on the canvas, so nothing is clipped
+fn random_point(rng: &mut StdRng) -> Point {
+    Point(rng.gen_range(10..190), rng.gen_range(10..190))
+}
+
+/// checks every property of a line from a to b. "first_drawn" is false when the first pixel is
+/// skipped on purpose
+fn assert_line(writes: &Writes, a: Point, b: Point, first_drawn: bool, what: &str) {
+    assert_no_overdraw(writes, what);
+
+    let pixels: HashSet<_> = writes.keys().copied().collect();
+    assert_eq!(
+        pixels.contains(&(a.0, a.1)),
+        first_drawn || a == b,
+        "{what}"
+    );
+    assert!(pixels.contains(&(b.0, b.1)), "{what} does not draw its end");
+
+    // one pixel for each step on the main axis, so there are no extra pixels
+    let steps = (b.0 - a.0).abs().max((b.1 - a.1).abs()) as usize;
+    let expected = if first_drawn || a == b {
+        steps + 1
+    } else {
+        steps
+    };
+    assert_eq!(
+        pixels.len(),
+        expected,
+        "{what} has the wrong amount of pixels"
+    );
+
+    let mut full = pixels;
+    full.insert((a.0, a.1));
+    assert_connected(&full, what);
+}
+
+#[test]
+fn pixels_far_below_the_canvas_are_not_set() {
+    // y times the width is past the u32 limit, it must not wrap back onto the canvas
+    let y = (u32::MAX / SIZE + 1) as i32;
+    let writes = record(|c| {
+        c.set_pixel(0, y, TRANSLUCENT);
+        c.set_pixel(SIZE as i32 - 1, i32::MAX, TRANSLUCENT);
+    });
+    assert!(writes.is_empty(), "{writes:?}");
+}
+
+#[test]
+fn line_covers_a_to_b_once() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..CASES {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let line = core(Shape::Line, vec![a, b], NO_FILL);
+        let what = format!("line {a:?} -> {b:?}");
+
+        let writes = record(|c| draw_line(&line, c, true));
+        assert_line(&writes, a, b, true, &what);
+
+        let writes = record(|c| draw_line(&line, c, false));
+        assert_line(&writes, a, b, false, &what);
+    }
+}
+
+#[test]
+fn line_is_symmetric() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..CASES {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let forward = record(|c| draw_line(&core(Shape::Line, vec![a, b], NO_FILL), c, true));
+        let backward = record(|c| draw_line(&core(Shape::Line, vec![b, a], NO_FILL), c, true));
+
+        assert_eq!(
+            forward, backward,
+            "line {a:?} -> {b:?} depends on its direction"
+        );
+    }
+}
+
+#[test]
+fn line_on_every_octant() {
+    // lines from the center to a circle of points, so we always hit the 8 octants and the
+    // horizontal, vertical and diagonal cases
+    let center = Point(100, 100);
+    for end in (-60..=60).flat_map(|x| [Point(100 + x, 40), Point(100 + x, 160)]) {
+        for (a, b) in [(center, end), (end, center)] {
+            let writes = record(|c| draw_line(&core(Shape::Line, vec![a, b], NO_FILL), c, true));
+            assert_line(&writes, a, b, true, &format!("line {a:?} -> {b:?}"));
+        }
+    }
+}
+
+#[test]
+fn triangle_line_covers_a_to_b_once() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..CASES {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let line = core(Shape::Triangle, vec![a, b], NO_FILL);
+        let writes = record(|c| draw_line_for_triangle(&line, c, &mut HashMap::new()));
+
+        assert_line(
+            &writes,
+            a,
+            b,
+            true,
+            &format!("triangle line {a:?} -> {b:?}"),
+        );
+    }
+}
+
+#[test]
+fn rectangle_border_has_no_overdraw() {
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..CASES {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let what = format!("rectangle {a:?} {b:?}");
+        let writes =
+            record(|c| new_shape_from_core(core(Shape::Rectangle, vec![a, b], NO_FILL)).draw(c));
+        assert_no_overdraw(&writes, &what);
+
+        // the border is exactly the pixels on the edges of the box
+        let (min_x, max_x) = (a.0.min(b.0), a.0.max(b.0));
+        let (min_y, max_y) = (a.1.min(b.1), a.1.max(b.1));
+        let border: HashSet<_> = (min_x..=max_x)
+            .flat_map(|x| (min_y..=max_y).map(move |y| (x, y)))
+            .filter(|(x, y)| *x == min_x || *x == max_x || *y == min_y || *y == max_y)
+            .collect();
+        let pixels: HashSet<_> = writes.keys().copied().collect();
+        assert_eq!(pixels, border, "{what}");
+
+        let writes = record(|c| {
+            new_shape_from_core(core(Shape::Rectangle, vec![a, b], TRANSLUCENT)).draw(c)
+        });
+        assert_no_overdraw(&writes, &format!("filled {what}"));
+    }
+}
+
+#[test]
+fn ellipse_has_no_overdraw_or_gaps() {
+    let mut rng = StdRng::seed_from_u64(5);
+    for _ in 0..CASES {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let what = format!("ellipse {a:?} {b:?}");
+        let writes =
+            record(|c| new_shape_from_core(core(Shape::Ellipse, vec![a, b], NO_FILL)).draw(c));
+        assert_no_overdraw(&writes, &what);
+        assert_connected(&writes.keys().copied().collect(), &what);
+
+        // the border is symmetric on both axis
+        let center = Point((a.0 + b.0) >> 1, (a.1 + b.1) >> 1);
+        for (x, y) in writes.keys() {
+            let mirrored = (2 * center.0 - x, 2 * center.1 - y);
+            assert!(writes.contains_key(&mirrored), "{what} is not symmetric");
+        }
+
+        let writes =
+            record(|c| new_shape_from_core(core(Shape::Ellipse, vec![a, b], TRANSLUCENT)).draw(c));
+        assert_no_overdraw(&writes, &format!("filled {what}"));
+    }
+}
+
+#[test]
+fn triangle_has_no_overdraw_or_gaps() {
+    let mut rng = StdRng::seed_from_u64(6);
+    for _ in 0..CASES {
+        let points = vec![
+            random_point(&mut rng),
+            random_point(&mut rng),
+            random_point(&mut rng),
+        ];
+        let what = format!("triangle {points:?}");
+
+        let writes =
+            record(|c| new_shape_from_core(core(Shape::Triangle, points.clone(), NO_FILL)).draw(c));
+        assert_no_overdraw(&writes, &what);
+        let pixels: HashSet<_> = writes.keys().copied().collect();
+        assert_connected(&pixels, &what);
+        for p in points.iter() {
+            assert!(
+                pixels.contains(&(p.0, p.1)),
+                "{what} does not draw its corners"
+            );
+        }
+
+        let writes = record(|c| {
+            new_shape_from_core(core(Shape::Triangle, points.clone(), TRANSLUCENT)).draw(c)
+        });
+        assert_no_overdraw(&writes, &format!("filled {what}"));
+    }
+}
+
+#[test]
+fn thick_borders_have_no_overdraw() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let shapes = [
+        Shape::Line,
+        Shape::Ellipse,
+        Shape::Triangle,
+        Shape::Rectangle,
+        Shape::Bezier,
+    ];
+
+    for _ in 0..CASES / 5 {
+        for shape_type in shapes {
+            let n = match shape_type {
+                Shape::Triangle => 3,
+                Shape::Bezier => rng.gen_range(2..6),
+                _ => 2,
+            };
+            let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+            let mut core = core(shape_type, points.clone(), TRANSLUCENT);
+            core.stroke_width = rng.gen_range(2..12);
+            core.line_cap = LineCap::ALL[rng.gen_range(0..3)];
+            core.line_join = LineJoin::ALL[rng.gen_range(0..3)];
+            core.miter_limit = rng.gen_range(1.0..10.0);
+            let what = format!(
+                "{shape_type} {points:?} of width {} with {} caps and {} joins (limit {})",
+                core.stroke_width, core.line_cap, core.line_join, core.miter_limit
+            );
+
+            let join = core.line_join;
+            let writes = record(|c| new_shape_from_core(core).draw(c));
+            assert_no_overdraw(&writes, &what);
+
+            // the tip of a sharp miter can be thinner than a pixel, there we may only catch a lone
+            // pixel. Like any other sliver, thats expected
+            if join != LineJoin::Miter {
+                assert_connected(&writes.keys().copied().collect(), &what);
+            }
+        }
+    }
+}
+
+#[test]
+fn thick_lines_have_the_given_width() {
+    for width in 1..12 {
+        for (a, b) in [
+            (Point(20, 100), Point(180, 100)),
+            (Point(100, 20), Point(100, 180)),
+        ] {
+            let mut line = core(Shape::Line, vec![a, b], NO_FILL);
+            line.stroke_width = width;
+            let writes = record(|c| new_shape_from_core(line).draw(c));
+            assert_no_overdraw(&writes, "line");
+
+            // every pixel of the line has "width" pixels across it
+            let across = writes
+                .keys()
+                .filter(|(x, y)| if a.1 == b.1 { *x == 100 } else { *y == 100 })
+                .count();
+            assert_eq!(across, width as usize, "line {a:?} -> {b:?}");
+        }
+    }
+}
+
+#[test]
+fn paths_that_turn_back_get_a_cap() {
+    // goes right until 150 and comes back to 100, the turn is exactly backwards
+    let points = vec![Point(50, 100), Point(150, 100), Point(100, 100)];
+    for cap in LineCap::ALL {
+        for join in LineJoin::ALL {
+            let mut polyline = core(Shape::Polyline, points.clone(), NO_FILL);
+            polyline.stroke_width = 10;
+            polyline.line_cap = cap;
+            polyline.line_join = join;
+            let writes = record(|c| new_shape_from_core(polyline).draw(c));
+            let what = format!("{cap} caps and {join} joins");
+            assert_no_overdraw(&writes, &what);
+
+            // the turn looks like the end of the line, round joins are always round
+            let past_the_turn = writes.contains_key(&(153, 100));
+            let expected = cap != LineCap::Butt || join == LineJoin::Round;
+            assert_eq!(past_the_turn, expected, "{what}");
+        }
+    }
+}
+
+#[test]
+fn dashed_borders_have_no_overdraw() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let shapes = [
+        Shape::Line,
+        Shape::Ellipse,
+        Shape::Triangle,
+        Shape::Rectangle,
+        Shape::Bezier,
+    ];
+
+    for _ in 0..CASES / 5 {
+        for shape_type in shapes {
+            let n = match shape_type {
+                Shape::Triangle => 3,
+                Shape::Bezier => rng.gen_range(2..6),
+                _ => 2,
+            };
+            let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+            let mut core = core(shape_type, points.clone(), TRANSLUCENT);
+            core.stroke_width = rng.gen_range(1..8);
+            core.line_cap = LineCap::ALL[rng.gen_range(0..3)];
+            core.dash_pattern = (0..rng.gen_range(1..5))
+                .map(|_| rng.gen_range(0..12))
+                .collect();
+            core.dash_offset = rng.gen_range(0..20);
+            let what = format!(
+                "{shape_type} {points:?} of width {} with dashes {:?} (offset {})",
+                core.stroke_width, core.dash_pattern, core.dash_offset
+            );
+
+            let writes = record(|c| new_shape_from_core(core).draw(c));
+            assert_no_overdraw(&writes, &what);
+        }
+    }
+}
+
+#[test]
+fn dashes_continue_across_corners() {
+    // sides of 17 and 13 pixels, if the pattern restarted on each corner the dashes would not
+    // line up with a single pattern going around the whole border
+    let (min, max) = ((10, 10), (27, 23));
+    let mut core = core(
+        Shape::Rectangle,
+        vec![Point::from(min), Point::from(max)],
+        NO_FILL,
+    );
+    core.dash_pattern = vec![3, 2];
+    let writes = record(|c| new_shape_from_core(core).draw(c));
+
+    // every pixel of the border in order, starting on the first corner and going right. Every
+    // step is straight so the distance along the border is the index
+    let mut border = vec![];
+    border.extend((min.0..max.0).map(|x| (x, min.1)));
+    border.extend((min.1..max.1).map(|y| (max.0, y)));
+    border.extend((min.0 + 1..=max.0).rev().map(|x| (x, max.1)));
+    border.extend((min.1 + 1..=max.1).rev().map(|y| (min.0, y)));
+
+    let expected: HashSet<(i32, i32)> = border
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 5 < 3)
+        .map(|(_, p)| *p)
+        .collect();
+    let drawn: HashSet<(i32, i32)> = writes.keys().copied().collect();
+    assert_eq!(drawn, expected);
+}
+
+#[test]
+fn antialiased_shapes_have_no_overdraw() {
+    let mut rng = StdRng::seed_from_u64(10);
+    let shapes = [
+        Shape::Line,
+        Shape::Ellipse,
+        Shape::Triangle,
+        Shape::Rectangle,
+        Shape::Bezier,
+    ];
+
+    for _ in 0..CASES / 10 {
+        for shape_type in shapes {
+            let n = match shape_type {
+                Shape::Triangle => 3,
+                Shape::Bezier => rng.gen_range(2..6),
+                _ => 2,
+            };
+            let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+            let mut core = core(shape_type, points.clone(), TRANSLUCENT);
+            core.antialias = true;
+            core.stroke_width = rng.gen_range(1..6);
+            if rng.gen_bool(0.3) {
+                core.dash_pattern = vec![rng.gen_range(1..8), rng.gen_range(1..8)];
+            }
+            let what = format!(
+                "anti-aliased {shape_type} {points:?} of width {} with dashes {:?}",
+                core.stroke_width, core.dash_pattern
+            );
+
+            let writes = record(|c| new_shape_from_core(core).draw(c));
+            assert_no_overdraw(&writes, &what);
+        }
+    }
+}
+
+#[test]
+fn antialiased_lines_split_each_step_between_two_pixels() {
+    let mut rng = StdRng::seed_from_u64(11);
+
+    for _ in 0..CASES / 10 {
+        let (a, b) = (random_point(&mut rng), random_point(&mut rng));
+        let mut line = core(Shape::Line, vec![a, b], NO_FILL);
+        line.color = RGBA::new(255, 255, 255, 255);
+        line.antialias = true;
+
+        let mut frame = Frame::new(SIZE, SIZE);
+        new_shape_from_core(line).draw(&mut frame.canvas());
+
+        // on each step along the main axis the alphas of the pixels across it add up to a full
+        // pixel, with some room for rounding
+        let steep = (b.1 - a.1).abs() > (b.0 - a.0).abs();
+        let (from, to) = if steep { (a.1, b.1) } else { (a.0, b.0) };
+        for i in from.min(to)..=from.max(to) {
+            let total: u32 = (0..SIZE as i32)
+                .map(|j| if steep { (j, i) } else { (i, j) })
+                .map(|(x, y)| frame.get_pixel(x as u32, y as u32)[3] as u32)
+                .sum();
+            assert!(
+                (254..=256).contains(&total),
+                "line {a:?} -> {b:?} has a total alpha of {total} on step {i}"
+            );
+        }
+
+        // the ends are exactly on a pixel, they are fully covered
+        for p in [a, b] {
+            assert_eq!(frame.get_pixel(p.0 as u32, p.1 as u32)[3], 255);
+        }
+    }
+}
+
+/// winding number of the pixel center (x, y), counting the edges that cross its row on its left
+/// (or on it). Each edge covers the rows from its top to its bottom, without the bottom one
+fn winding(points: &[Point], x: i32, y: i32) -> i32 {
+    let n = points.len();
+    let mut winding = 0;
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let (top, bottom, w) = if a.1 < b.1 { (a, b, 1) } else { (b, a, -1) };
+        if y < top.1 || y >= bottom.1 {
+            continue;
+        }
+
+        // the crossing is at top.x + (y - top.y) * dx / dy, compared without dividing
+        let (dx, dy) = ((bottom.0 - top.0) as i64, (bottom.1 - top.1) as i64);
+        if top.0 as i64 * dy + (y - top.1) as i64 * dx <= x as i64 * dy {
+            winding += w;
+        }
+    }
+    winding
+}
+
+#[test]
+fn polygon_fill_follows_the_fill_rule() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let border = RGBA::new(255, 255, 255, 255);
+    let fill = RGBA::new(0, 0, 255, 255);
+
+    for _ in 0..CASES / 5 {
+        // random points, the polygons cross themselves most of the time
+        let n = rng.gen_range(3..9);
+        let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+
+        for rule in FillRule::ALL {
+            let mut polygon = core(Shape::Polygon, points.clone(), fill);
+            polygon.color = border;
+            polygon.fill_rule = rule;
+            let what = format!("{rule} polygon {points:?}");
+
+            let writes = record(|c| new_shape_from_core(polygon.clone()).draw(c));
+            assert_no_overdraw(&writes, &what);
+
+            let mut frame = Frame::new(SIZE, SIZE);
+            new_shape_from_core(polygon).draw(&mut frame.canvas());
+
+            for y in 0..SIZE as i32 {
+                for x in 0..SIZE as i32 {
+                    let color = frame.get_pixel(x as u32, y as u32);
+                    if color == border {
+                        continue;
+                    }
+
+                    let w = winding(&points, x, y);
+                    let inside = match rule {
+                        FillRule::NonZero => w != 0,
+                        FillRule::EvenOdd => w % 2 != 0,
+                    };
+                    assert_eq!(color == fill, inside, "{what} on ({x}, {y})");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn polygon_fill_does_not_depend_on_the_direction() {
+    let mut rng = StdRng::seed_from_u64(13);
+
+    for _ in 0..CASES / 5 {
+        let n = rng.gen_range(3..9);
+        let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+        let mut reversed = points.clone();
+        reversed.reverse();
+
+        let forward =
+            record(|c| new_shape_from_core(core(Shape::Polygon, points, TRANSLUCENT)).draw(c));
+        let backward =
+            record(|c| new_shape_from_core(core(Shape::Polygon, reversed, TRANSLUCENT)).draw(c));
+        assert_eq!(forward, backward);
+    }
+}
+
+#[test]
+fn polyline_has_no_overdraw_or_gaps() {
+    let mut rng = StdRng::seed_from_u64(14);
+
+    for _ in 0..CASES / 5 {
+        let n = rng.gen_range(2..9);
+        let points: Vec<Point> = (0..n).map(|_| random_point(&mut rng)).collect();
+        let what = format!("polyline {points:?}");
+
+        let writes =
+            record(|c| new_shape_from_core(core(Shape::Polyline, points.clone(), NO_FILL)).draw(c));
+        assert_no_overdraw(&writes, &what);
+        assert_connected(&writes.keys().copied().collect(), &what);
+        for p in points {
+            assert!(writes.contains_key(&(p.0, p.1)), "{what} misses {p:?}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// Degree reduction checks. Reducing an elevated curve gives back the original one, a curve that
+// cant be drawn with one point less changes as little as possible, and each reduction is undone
+// on its own.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    primitives::{max_deviation, new_shape_from_core, reduce_degree},
+};
+
+fn bezier(points: &[(i32, i32)]) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Bezier,
+        ..Default::default()
+    }
+}
+
+/// the core after applying the operations in order
+fn apply(core: &ShapeCore, ops: &[UpdateOp]) -> ShapeCore {
+    let mut shape = new_shape_from_core(core.clone());
+    for op in ops {
+        shape.update(op);
+    }
+    shape.get_core()
+}
+
+#[test]
+fn reducing_undoes_elevating() {
+    let quadratic = bezier(&[(10, 100), (60, 0), (150, 90)]);
+    let elevated = apply(
+        &quadratic,
+        &[UpdateOp::DegreeElevate, UpdateOp::DegreeElevate],
+    );
+    assert_eq!(elevated.points.len(), 5);
+
+    let reduced = apply(&elevated, &[UpdateOp::DegreeReduce, UpdateOp::DegreeReduce]);
+    assert_eq!(reduced.points.len(), 3);
+    // the elevated points are rounded to pixels, so the reduced ones can be a pixel away
+    for (a, b) in reduced.points.iter().zip(&quadratic.points) {
+        assert!(a.distance(*b) <= 1.5, "{a:?} is not {b:?}");
+    }
+    assert!(max_deviation(&quadratic, &reduced) < 1.0);
+}
+
+#[test]
+fn reducing_keeps_the_ends_and_changes_the_curve_little() {
+    // an s curve, a quadratic curve cant bend both ways
+    let cubic = bezier(&[(10, 50), (60, 0), (100, 100), (150, 50)]);
+    let reduced = apply(&cubic, &[UpdateOp::DegreeReduce]);
+    assert_eq!(reduced.points.len(), 3);
+    assert_eq!(reduced.points[0], cubic.points[0]);
+    assert_eq!(reduced.points[2], cubic.points[3]);
+
+    // the least squares curve is closer than the one with the middle points averaged
+    let deviation = max_deviation(&cubic, &reduced);
+    let averaged = bezier(&[(10, 50), (80, 50), (150, 50)]);
+    assert!(deviation > 0.0);
+    assert!(deviation <= max_deviation(&cubic, &averaged));
+
+    // a line has no degree to lose
+    let line = bezier(&[(10, 10), (100, 40)]);
+    assert!(apply(&line, &[UpdateOp::DegreeReduce]) == line);
+}
+
+#[test]
+fn rational_curves_are_reduced_with_their_weights() {
+    let circle = ShapeCore {
+        weights: vec![1.0, FRAC_1_SQRT_2, 1.0],
+        ..bezier(&[(110, 10), (110, 110), (10, 110)])
+    };
+    let reduced = apply(&circle, &[UpdateOp::DegreeElevate, UpdateOp::DegreeReduce]);
+    assert_eq!(reduced.points.len(), 3);
+    assert_eq!(reduced.weights.len(), 3);
+    assert!(reduced.validate().is_ok());
+    assert!(max_deviation(&circle, &reduced) < 1.5);
+}
+
+#[test]
+fn each_reduction_is_undone_on_its_own() {
+    let quartic = bezier(&[(10, 100), (40, 0), (80, 120), (120, 0), (150, 100)]);
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(quartic.clone()));
+
+    state.update_shape(0, UpdateOp::DegreeReduce);
+    let once = state.get_object(0).get_core();
+    state.update_shape(0, UpdateOp::DegreeReduce);
+    assert_eq!(state.get_object(0).get_core().points.len(), 3);
+
+    state.undo();
+    assert!(state.get_object(0).get_core() == once);
+    state.undo();
+    assert!(state.get_object(0).get_core() == quartic);
+    state.redo();
+    assert!(state.get_object(0).get_core() == once);
+}
+
+#[test]
+fn curves_with_many_points_are_reduced() {
+    // a zig-zag of 14 points elevated to 15 and back, and one of 24 elevated to 25
+    for n in [14, 24] {
+        let points: Vec<(i32, i32)> = (0..n)
+            .map(|i| (10 + i * 20, if i % 2 == 0 { 10 } else { 200 }))
+            .collect();
+        let zig_zag = bezier(&points);
+        let elevated = apply(&zig_zag, &[UpdateOp::DegreeElevate]);
+        assert!(reduce_degree(&elevated).is_some());
+
+        let reduced = apply(&elevated, &[UpdateOp::DegreeReduce]);
+        assert_eq!(reduced.points.len(), n as usize);
+        assert!(max_deviation(&zig_zag, &reduced) < 1.0);
+    }
+
+    // a line cant be reduced
+    assert!(reduce_degree(&bezier(&[(10, 10), (100, 40)])).is_none());
+}
+
+#[test]
+fn reductions_that_cant_be_done_are_reported_and_not_recorded() {
+    let line = bezier(&[(10, 10), (100, 40)]);
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(line.clone()));
+    assert!(state.update_shape(0, UpdateOp::Move(Point(5, 5))));
+
+    // a line has no degree to lose, there is nothing to undo but the move
+    assert!(!state.update_shape(0, UpdateOp::DegreeReduce));
+    state.undo();
+    assert!(state.get_object(0).get_core() == line);
+}
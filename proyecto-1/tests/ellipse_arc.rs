@@ -0,0 +1,123 @@
+// Rotated ellipse and arc, pie and chord checks. Like the other rasterizers they must not draw a
+// pixel twice and the borders must have no gaps, and dragging the handles must move the part of
+// the ellipse that was dragged.
+
+use paint_app::{
+    core::{ArcKind, Point, Shape, ShapeCore, UpdateOp, RGBA},
+    primitives::new_shape_from_core,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod common;
+use common::{assert_connected, record_shape, NO_FILL, TRANSLUCENT};
+
+fn ellipse(points: [(i32, i32); 2], rotation: f32, arc: ArcKind, fill_color: RGBA) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: TRANSLUCENT,
+        fill_color,
+        shape_type: Shape::Ellipse,
+        rotation,
+        arc,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rotated_ellipses_and_arcs_have_no_overdraw_or_gaps() {
+    let mut rng = StdRng::seed_from_u64(15);
+    for _ in 0..300 {
+        let a = (rng.gen_range(20..100), rng.gen_range(20..100));
+        let b = (rng.gen_range(100..180), rng.gen_range(100..180));
+        let rotation = rng.gen_range(-180.0..180.0);
+        let arc = ArcKind::ALL[rng.gen_range(0..ArcKind::ALL.len())];
+        let mut core = ellipse([a, b], rotation, arc, NO_FILL);
+        core.start_angle = rng.gen_range(0.0..360.0);
+        core.end_angle = rng.gen_range(0.0..360.0);
+        let what = format!("{arc} {a:?} {b:?} rotated {rotation}");
+
+        let writes = record_shape(core.clone());
+        assert!(
+            writes.values().all(|n| *n == 1),
+            "{what} draws pixels twice"
+        );
+        assert_connected(&writes.keys().copied().collect(), &what);
+
+        core.fill_color = TRANSLUCENT;
+        let writes = record_shape(core);
+        assert!(
+            writes.values().all(|n| *n == 1),
+            "filled {what} draws pixels twice"
+        );
+    }
+}
+
+#[test]
+fn pies_are_filled_between_their_lines() {
+    // a quarter of a circle, from the right (0) to the bottom (90) since y goes down
+    let mut core = ellipse([(40, 40), (160, 160)], 0.0, ArcKind::Pie, TRANSLUCENT);
+    core.start_angle = 0.0;
+    core.end_angle = 90.0;
+    let writes = record_shape(core.clone());
+    assert!(writes.keys().all(|(x, y)| *x >= 100 && *y >= 100));
+    assert!(writes.contains_key(&(120, 120)));
+
+    // rotated half a turn its the top left quarter
+    core.rotation = 180.0;
+    let writes = record_shape(core);
+    assert!(writes.keys().all(|(x, y)| *x <= 100 && *y <= 100));
+    assert!(writes.contains_key(&(80, 80)));
+}
+
+#[test]
+fn huge_ellipses_are_filled_only_on_the_canvas() {
+    // much bigger than the 200x200 canvas, the fill covers the whole canvas once
+    let core = ellipse(
+        [(-3000, -3000), (3000, 3000)],
+        30.0,
+        ArcKind::Full,
+        TRANSLUCENT,
+    );
+    let writes = record_shape(core);
+    assert_eq!(writes.len(), 200 * 200);
+    assert!(writes.values().all(|n| *n == 1));
+}
+
+#[test]
+fn rotated_ellipses_are_hit_on_their_border() {
+    let core = ellipse([(40, 80), (160, 120)], 90.0, ArcKind::Full, NO_FILL);
+    let shape = new_shape_from_core(core);
+    // the wide ellipse is now tall
+    assert!(shape.hit_test(Point(100, 40)));
+    assert!(shape.hit_test(Point(80, 100)));
+    assert!(!shape.hit_test(Point(40, 100)));
+    assert!(!shape.hit_test(Point(100, 100)));
+}
+
+#[test]
+fn handles_move_the_arc_ends_and_the_corners() {
+    let mut shape =
+        new_shape_from_core(ellipse([(40, 80), (160, 120)], 0.0, ArcKind::Arc, NO_FILL));
+    // both corners and both ends of the arc, that starts at 0 and ends at 270 by default
+    let handles = shape.control_points();
+    assert_eq!(handles.len(), 4);
+    assert_eq!(handles[2], Point(160, 100));
+    assert_eq!(handles[3], Point(100, 80));
+
+    shape.update(&UpdateOp::ControlPoint(2, Point(100, 150)));
+    assert_eq!(shape.get_core().start_angle, 90.0);
+    assert_eq!(shape.control_points()[2], Point(100, 120));
+
+    // dragging a corner of a rotated ellipse keeps the other corner in place
+    let mut shape = new_shape_from_core(ellipse(
+        [(40, 80), (160, 120)],
+        90.0,
+        ArcKind::Full,
+        NO_FILL,
+    ));
+    let handles = shape.control_points();
+    shape.update(&UpdateOp::ControlPoint(0, Point(130, 20)));
+    let moved = shape.control_points();
+    assert_eq!(moved[0], Point(130, 20));
+    assert_eq!(moved[1], handles[1]);
+}
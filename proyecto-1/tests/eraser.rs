@@ -0,0 +1,149 @@
+// Eraser checks. The split eraser cuts lines and curves with subdivide, and everything erased on
+// a drag is grouped so a single undo brings it back.
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, RGBA},
+    draw_state::DrawState,
+    headless::render,
+    primitives::{erased_range, new_shape_from_core},
+};
+
+const RADIUS: f32 = 6.0;
+
+fn core(shape_type: Shape, points: &[(i32, i32)]) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: RGBA::new(255, 255, 255, 255),
+        shape_type,
+        ..Default::default()
+    }
+}
+
+/// a drawing with a line and a curve that both cross x = 50
+fn drawing() -> DrawState {
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(core(
+        Shape::Line,
+        &[(0, 20), (100, 20)],
+    )));
+    state.add_shape(new_shape_from_core(core(
+        Shape::Bezier,
+        &[(0, 80), (50, 40), (100, 80)],
+    )));
+    state
+}
+
+#[test]
+fn erased_range_is_the_part_under_the_eraser() {
+    let line = core(Shape::Line, &[(0, 20), (100, 20)]);
+
+    let (t0, t1) = erased_range(&line, Point(50, 0), Point(50, 100), RADIUS).unwrap();
+    assert!((0.44..0.46).contains(&t0) && (0.54..0.56).contains(&t1));
+    // only touching an end erases nothing
+    assert_eq!(
+        erased_range(&line, Point(-5, 0), Point(-5, 40), RADIUS),
+        None
+    );
+    assert_eq!(
+        erased_range(&line, Point(0, 20), Point(100, 20), RADIUS),
+        Some((0.0, 1.0))
+    );
+    assert_eq!(
+        erased_range(&line, Point(50, 30), Point(90, 30), RADIUS),
+        None
+    );
+}
+
+#[test]
+fn cutting_leaves_the_pieces_outside_the_eraser() {
+    let mut state = drawing();
+    state.begin_group();
+    for i in (0..2).rev() {
+        let core = state.get_object(i).get_core();
+        let (t0, t1) = erased_range(&core, Point(50, 0), Point(50, 100), RADIUS).unwrap();
+        state.cut_shape(i, t0, t1);
+    }
+    state.end_group();
+
+    // two pieces for each shape, none of them under the eraser
+    assert_eq!(state.get_objects().len(), 4);
+    for shape in state.get_objects() {
+        let core = shape.get_core();
+        assert!(erased_range(&core, Point(50, 0), Point(50, 100), RADIUS - 1.0).is_none());
+    }
+    let lines = state
+        .get_objects()
+        .iter()
+        .filter(|s| s.get_type() == Shape::Line)
+        .count();
+    assert_eq!(lines, 2);
+
+    // the end of a curve can be erased too, leaving a single piece
+    let mut state = drawing();
+    state.cut_shape(1, 0.0, 0.3);
+    assert_eq!(state.get_objects().len(), 2);
+    assert_eq!(state.get_object(1).get_core().points[2], Point(100, 80));
+
+    // an empty part leaves the shapes where they were, without history
+    let mut state = drawing();
+    state.cut_shape(0, 0.0, 0.0);
+    state.cut_shape(1, 0.5, 0.5);
+    assert_eq!(state.get_objects().len(), 2);
+    assert_eq!(
+        state.get_object(0).get_core().points,
+        vec![Point(0, 20), Point(100, 20)]
+    );
+    state.undo();
+    assert_eq!(state.get_objects().len(), 1);
+}
+
+#[test]
+fn a_drag_is_undone_at_once() {
+    let mut state = drawing();
+    let before = render(&state, 100, 100);
+
+    state.begin_group();
+    state.cut_shape(0, 0.2, 0.4);
+    state.cut_shape(0, 0.0, 0.1);
+    state.delete_shape(1);
+    state.end_group();
+    let after = render(&state, 100, 100);
+    assert!(after.as_bytes() != before.as_bytes());
+
+    state.undo();
+    assert!(render(&state, 100, 100).as_bytes() == before.as_bytes());
+    assert_eq!(state.get_objects().len(), 2);
+    state.redo();
+    assert!(render(&state, 100, 100).as_bytes() == after.as_bytes());
+
+    // the shapes created before the drag are a different step
+    state.undo();
+    state.undo();
+    assert_eq!(state.get_objects().len(), 1);
+
+    // a group without changes is not a step
+    let mut state = drawing();
+    state.begin_group();
+    state.end_group();
+    state.undo();
+    assert_eq!(state.get_objects().len(), 1);
+}
+
+#[test]
+fn undo_ends_an_open_group() {
+    // the release of the drag never arrived, the group is still open
+    let mut state = drawing();
+    state.begin_group();
+    state.cut_shape(0, 0.2, 0.4);
+    state.undo();
+    assert_eq!(state.get_objects().len(), 2);
+
+    // what is done after that is undone on its own, not kept on the group
+    state.delete_shape(1);
+    state.delete_shape(0);
+    assert_eq!(state.get_objects().len(), 0);
+    state.undo();
+    assert_eq!(state.get_objects().len(), 1);
+    state.undo();
+    assert_eq!(state.get_objects().len(), 2);
+}
@@ -0,0 +1,161 @@
+// Bucket fill checks. The flood fill must find exactly the connected region of similar colors,
+// and the fill made with it must be a shape like the others: drawn once per pixel, undone,
+// saved and loaded.
+
+use std::{collections::VecDeque, path::PathBuf};
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, RGBA},
+    draw_state::DrawState,
+    headless::{render, Frame},
+    primitives::{flood_fill, new_shape_from_core},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const BACKGROUND: RGBA = RGBA::new(255, 255, 255, 255);
+const BORDER: RGBA = RGBA::new(0, 0, 0, 255);
+const FILL: RGBA = RGBA::new(200, 40, 40, 255);
+
+/// the region of the seed found one pixel at a time, to compare against the scanline fill
+fn reference_fill(frame: &Frame, seed: Point, tolerance: u8) -> Vec<bool> {
+    let (width, height) = (frame.width() as i32, frame.height() as i32);
+    let target = frame.get_pixel(seed.0 as u32, seed.1 as u32);
+    let mut region = vec![false; (width * height) as usize];
+    let mut queue = VecDeque::from([seed]);
+
+    while let Some(Point(x, y)) = queue.pop_front() {
+        if x < 0 || y < 0 || x >= width || y >= height || region[(y * width + x) as usize] {
+            continue;
+        }
+        let color = frame.get_pixel(x as u32, y as u32);
+        if (0..4).any(|i| color[i].abs_diff(target[i]) > tolerance) {
+            continue;
+        }
+        region[(y * width + x) as usize] = true;
+        queue.extend([
+            Point(x - 1, y),
+            Point(x + 1, y),
+            Point(x, y - 1),
+            Point(x, y + 1),
+        ]);
+    }
+
+    region
+}
+
+#[test]
+fn flood_fill_finds_the_connected_region() {
+    let mut rng = StdRng::seed_from_u64(13);
+
+    for _ in 0..100 {
+        // noise of 2 colors with small variations, so the tolerance matters
+        let (width, height) = (rng.gen_range(1..40), rng.gen_range(1..40));
+        let mut frame = Frame::new(width, height);
+        let mut canvas = frame.canvas();
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                let base = if rng.gen_bool(0.6) { 200 } else { 40 };
+                let shade = base + rng.gen_range(0..12);
+                canvas.set_pixel(x, y, RGBA::new(shade, shade, shade, 255));
+            }
+        }
+
+        let seed = Point(
+            rng.gen_range(0..width as i32),
+            rng.gen_range(0..height as i32),
+        );
+        let tolerance = rng.gen_range(0..20);
+        let spans = flood_fill(&frame, seed, tolerance);
+
+        let mut filled = vec![false; (width * height) as usize];
+        for span in spans.chunks_exact(2) {
+            assert_eq!(span[0].1, span[1].1, "span {span:?} is not on a row");
+            for x in span[0].0..=span[1].0 {
+                let i = (span[0].1 * width as i32 + x) as usize;
+                assert!(!filled[i], "({x}, {}) is on 2 spans", span[0].1);
+                filled[i] = true;
+            }
+        }
+        assert!(filled == reference_fill(&frame, seed, tolerance));
+    }
+}
+
+#[test]
+fn bucket_fills_regions_bounded_by_several_shapes() {
+    // a closed region made of 3 lines and a polyline, none of them can be filled on its own
+    let border = |shape_type, points: &[(i32, i32)]| ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: BORDER,
+        shape_type,
+        ..Default::default()
+    };
+    let mut state = DrawState::new();
+    state.change_background_color(BACKGROUND);
+    for core in [
+        border(Shape::Line, &[(10, 10), (90, 10)]),
+        border(Shape::Line, &[(90, 10), (90, 90)]),
+        border(Shape::Line, &[(90, 90), (10, 90)]),
+        border(Shape::Polyline, &[(10, 90), (3, 50), (10, 10)]),
+    ] {
+        state.add_shape(new_shape_from_core(core));
+    }
+
+    let before = render(&state, 100, 100);
+    let spans = flood_fill(&before, Point(50, 50), 0);
+    state.add_shape(new_shape_from_core(ShapeCore {
+        points: spans,
+        fill_color: FILL,
+        shape_type: Shape::Fill,
+        ..Default::default()
+    }));
+
+    let check = |state: &DrawState| {
+        let after = render(state, 100, 100);
+        for (x, y) in [(50, 50), (89, 89), (11, 11), (5, 50)] {
+            assert_eq!(after.get_pixel(x, y), FILL, "({x}, {y}) is not filled");
+        }
+        for (x, y) in [(50, 5), (95, 50), (50, 10), (90, 50), (0, 50)] {
+            assert_eq!(after.get_pixel(x, y), before.get_pixel(x, y));
+        }
+    };
+    check(&state);
+
+    // the fill is a single step of the history
+    state.undo();
+    assert!(render(&state, 100, 100).as_bytes() == before.as_bytes());
+    state.redo();
+    check(&state);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bucket_fill.json");
+    state.save_to_file(path.clone()).unwrap();
+    let mut loaded = DrawState::new();
+    loaded.load_from_file(path).unwrap();
+    check(&loaded);
+}
+
+#[test]
+fn fills_are_drawn_once_and_hit_on_their_pixels() {
+    let mut frame = Frame::new(60, 60);
+    let mut canvas = frame.canvas();
+    for y in 20..40 {
+        canvas.set_pixel(30, y, BORDER);
+    }
+    let spans = flood_fill(&frame, Point(0, 0), 0);
+    let fill = new_shape_from_core(ShapeCore {
+        points: spans,
+        fill_color: FILL,
+        shape_type: Shape::Fill,
+        ..Default::default()
+    });
+
+    let mut frame = Frame::new(60, 60);
+    let mut canvas = frame.recording_canvas();
+    fill.draw(&mut canvas);
+    assert_eq!(canvas.written_pixels().len(), 60 * 60 - 20);
+    assert!(canvas.written_pixels().iter().all(|(_, _, n)| *n == 1));
+
+    assert!(fill.hit_test(Point(29, 30)));
+    assert!(!fill.hit_test(Point(30, 30)));
+    assert!(!fill.hit_test(Point(60, 30)));
+    assert!(fill.control_points().is_empty());
+}
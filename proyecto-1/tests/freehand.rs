@@ -0,0 +1,118 @@
+// Freehand checks. The cursor path is simplified before storing it, the simplified path must stay
+// close to what the user drew while keeping only a few points.
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, RGBA},
+    headless::Frame,
+    primitives::{new_shape_from_core, simplify_path, FREEHAND_TOLERANCE},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// distance from p to the segment ab
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let (p, a, b): ((f32, f32), (f32, f32), (f32, f32)) = (p.into(), a.into(), b.into());
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sqr = dx * dx + dy * dy;
+    let t = if length_sqr == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sqr).clamp(0.0, 1.0)
+    };
+    ((p.0 - a.0 - dx * t).powi(2) + (p.1 - a.1 - dy * t).powi(2)).sqrt()
+}
+
+/// a random walk, like a cursor moving while dragging
+fn cursor_path(rng: &mut StdRng) -> Vec<Point> {
+    let mut p = Point(100, 100);
+    let mut path = vec![p];
+    for _ in 0..rng.gen_range(2..300) {
+        p = Point(p.0 + rng.gen_range(-3..=3), p.1 + rng.gen_range(-3..=3));
+        path.push(p);
+    }
+    path
+}
+
+#[test]
+fn simplified_paths_stay_close_to_the_cursor() {
+    let mut rng = StdRng::seed_from_u64(20);
+
+    for _ in 0..200 {
+        let path = cursor_path(&mut rng);
+        let simple = simplify_path(&path, FREEHAND_TOLERANCE);
+
+        assert_eq!(simple.first(), path.first());
+        assert_eq!(simple.last(), path.last());
+        assert!(simple.len() <= path.len());
+
+        // every point of the cursor path is close to the simplified one
+        for p in &path {
+            let distance = simple
+                .windows(2)
+                .map(|w| distance_to_segment(*p, w[0], w[1]))
+                .fold(f32::MAX, f32::min);
+            assert!(
+                simple.len() < 2 || distance <= FREEHAND_TOLERANCE,
+                "{p:?} is {distance} away from the simplified path"
+            );
+        }
+    }
+}
+
+#[test]
+fn straight_strokes_keep_their_ends_only() {
+    let path: Vec<Point> = (0..100).map(|i| Point(10 + i, 20 + i / 2)).collect();
+    assert_eq!(
+        simplify_path(&path, FREEHAND_TOLERANCE),
+        vec![Point(10, 20), Point(109, 69)]
+    );
+
+    // going back to the start is not a straight line
+    let mut back = path.clone();
+    back.extend(path.iter().rev());
+    assert_eq!(simplify_path(&back, FREEHAND_TOLERANCE).len(), 3);
+}
+
+#[test]
+fn smooth_freehand_goes_through_its_points() {
+    let color = RGBA::new(255, 255, 255, 255);
+    let points = vec![
+        Point(20, 100),
+        Point(60, 40),
+        Point(120, 150),
+        Point(180, 90),
+    ];
+
+    for smooth in [false, true] {
+        let core = ShapeCore {
+            points: points.clone(),
+            color,
+            shape_type: Shape::Freehand,
+            smooth,
+            ..Default::default()
+        };
+
+        let mut frame = Frame::new(200, 200);
+        let mut canvas = frame.recording_canvas();
+        new_shape_from_core(core).draw(&mut canvas);
+        assert!(canvas.written_pixels().iter().all(|(_, _, n)| *n == 1));
+        for p in &points {
+            assert_eq!(frame.get_pixel(p.0 as u32, p.1 as u32), color);
+        }
+    }
+}
+
+#[test]
+fn freehand_moves_and_is_hit_along_the_path() {
+    let core = ShapeCore {
+        points: vec![Point(100, 100), Point(200, 100), Point(200, 200)],
+        shape_type: Shape::Freehand,
+        ..Default::default()
+    };
+    let mut shape = new_shape_from_core(core);
+    assert!(shape.hit_test(Point(150, 100)));
+    assert!(!shape.hit_test(Point(120, 180)));
+
+    shape.update(&UpdateOp::Move(Point(0, 50)));
+    assert!(shape.hit_test(Point(150, 150)));
+    assert!(!shape.hit_test(Point(150, 100)));
+}
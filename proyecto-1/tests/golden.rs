@@ -0,0 +1,723 @@
+// Golden image tests. Each fixture is a shape core that we draw into a small frame and compare
+// pixel by pixel against a reference png checked in at tests/golden.
+//
+// When a rasterizer changes ON PURPOSE the references must be regenerated with:
+//     UPDATE_GOLDEN=1 cargo test --test golden
+// and the new pngs reviewed before committing them.
+//
+// When a fixture fails we write the rendered frame and a diff image (different pixels in red over
+// a dimmed copy of the reference) to the cargo test temporary folder, the path is in the message.
+
+use std::{env, f32::consts::FRAC_1_SQRT_2, fs, path::PathBuf};
+
+use paint_app::{
+    core::{
+        ArcKind, FillRule, ImageSampling, ImageSource, JointKind, KnotVector, LineCap, LineJoin,
+        Point, Shape, ShapeCore, RGBA,
+    },
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+const SIZE: u32 = 64;
+const BACKGROUND: RGBA = RGBA::new(0, 0, 0, 255);
+const BORDER: RGBA = RGBA::new(255, 255, 255, 255);
+const FILL: RGBA = RGBA::new(40, 120, 220, 255);
+const NO_FILL: RGBA = RGBA::new(0, 0, 0, 0);
+
+/// builds a fixture
+fn fixture(
+    name: &'static str,
+    shape_type: Shape,
+    points: &[(i32, i32)],
+    fill_color: RGBA,
+) -> (&'static str, ShapeCore) {
+    let core = ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: BORDER,
+        fill_color,
+        shape_type,
+        ..Default::default()
+    };
+    (name, core)
+}
+
+/// builds a fixture with a border wider than 1 pixel
+fn thick(
+    name: &'static str,
+    shape_type: Shape,
+    points: &[(i32, i32)],
+    fill_color: RGBA,
+    stroke_width: u32,
+) -> (&'static str, ShapeCore) {
+    let (name, mut core) = fixture(name, shape_type, points, fill_color);
+    core.stroke_width = stroke_width;
+    (name, core)
+}
+
+/// builds a fixture with a wide border and the given caps and joins
+fn styled(
+    name: &'static str,
+    shape_type: Shape,
+    points: &[(i32, i32)],
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+) -> (&'static str, ShapeCore) {
+    let (name, mut core) = thick(name, shape_type, points, NO_FILL, 9);
+    core.line_cap = line_cap;
+    core.line_join = line_join;
+    core.miter_limit = miter_limit;
+    (name, core)
+}
+
+/// builds a fixture with a dashed border
+fn dashed(
+    name: &'static str,
+    shape_type: Shape,
+    points: &[(i32, i32)],
+    stroke_width: u32,
+    dash_pattern: &[u32],
+    dash_offset: u32,
+) -> (&'static str, ShapeCore) {
+    let (name, mut core) = thick(name, shape_type, points, NO_FILL, stroke_width);
+    core.dash_pattern = dash_pattern.to_vec();
+    core.dash_offset = dash_offset;
+    (name, core)
+}
+
+/// builds an anti-aliased fixture
+fn antialiased(
+    name: &'static str,
+    shape_type: Shape,
+    points: &[(i32, i32)],
+    fill_color: RGBA,
+    stroke_width: u32,
+) -> (&'static str, ShapeCore) {
+    let (name, mut core) = thick(name, shape_type, points, fill_color, stroke_width);
+    core.antialias = true;
+    (name, core)
+}
+
+/// draws the shape on a frame with the background used by every fixture
+fn render(core: &ShapeCore) -> Frame {
+    let mut frame = Frame::new(SIZE, SIZE);
+    let mut canvas = frame.canvas();
+    canvas.clear(BACKGROUND);
+    new_shape_from_core(core.clone()).draw(&mut canvas);
+    frame
+}
+
+/// compares every fixture against its reference and panics with the list of failures
+fn check(fixtures: Vec<(&'static str, ShapeCore)>) {
+    let golden_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let diff_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    let update = env::var("UPDATE_GOLDEN").is_ok();
+    let mut failures = vec![];
+
+    for (name, core) in fixtures {
+        let actual = render(&core);
+        let golden_path = golden_dir.join(format!("{name}.png"));
+
+        if update {
+            fs::create_dir_all(&golden_dir).unwrap();
+            actual.save_png(&golden_path).unwrap();
+            continue;
+        }
+
+        let expected = match Frame::load_png(&golden_path) {
+            Ok(frame) => frame,
+            Err(err) => {
+                failures.push(format!(
+                    "{name}: cant read {}: {err}",
+                    golden_path.display()
+                ));
+                continue;
+            }
+        };
+
+        if let Some((diff, count)) = diff(&expected, &actual) {
+            fs::create_dir_all(&diff_dir).unwrap();
+            let actual_path = diff_dir.join(format!("{name}.actual.png"));
+            let diff_path = diff_dir.join(format!("{name}.diff.png"));
+            actual.save_png(&actual_path).unwrap();
+            diff.save_png(&diff_path).unwrap();
+            failures.push(format!(
+                "{name}: {count} pixels are different, see {}",
+                diff_path.display()
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n"));
+}
+
+/// returns the diff image and the amount of different pixels, or None if both frames are equal
+fn diff(expected: &Frame, actual: &Frame) -> Option<(Frame, usize)> {
+    if expected.width() != actual.width() || expected.height() != actual.height() {
+        return Some((Frame::new(actual.width(), actual.height()), usize::MAX));
+    }
+
+    let mut result = Frame::new(expected.width(), expected.height());
+    let mut count = 0;
+    {
+        let mut canvas = result.canvas();
+        for y in 0..expected.height() {
+            for x in 0..expected.width() {
+                let e = expected.get_pixel(x, y);
+                let color = if e == actual.get_pixel(x, y) {
+                    let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 12) as u8;
+                    RGBA::new(gray, gray, gray, 255)
+                } else {
+                    count += 1;
+                    RGBA::new(255, 0, 0, 255)
+                };
+                canvas.set_pixel(x as i32, y as i32, color);
+            }
+        }
+    }
+
+    if count == 0 {
+        None
+    } else {
+        Some((result, count))
+    }
+}
+
+#[test]
+fn lines() {
+    let l = Shape::Line;
+    check(vec![
+        fixture("line_point", l, &[(32, 32), (32, 32)], NO_FILL),
+        fixture("line_horizontal", l, &[(4, 32), (59, 32)], NO_FILL),
+        fixture("line_vertical", l, &[(32, 4), (32, 59)], NO_FILL),
+        fixture("line_diagonal", l, &[(4, 4), (59, 59)], NO_FILL),
+        // one line on each octant, all of them start at the center
+        fixture("line_octant_1", l, &[(32, 32), (60, 44)], NO_FILL),
+        fixture("line_octant_2", l, &[(32, 32), (44, 60)], NO_FILL),
+        fixture("line_octant_3", l, &[(32, 32), (20, 60)], NO_FILL),
+        fixture("line_octant_4", l, &[(32, 32), (4, 44)], NO_FILL),
+        fixture("line_octant_5", l, &[(32, 32), (4, 20)], NO_FILL),
+        fixture("line_octant_6", l, &[(32, 32), (20, 4)], NO_FILL),
+        fixture("line_octant_7", l, &[(32, 32), (44, 4)], NO_FILL),
+        fixture("line_octant_8", l, &[(32, 32), (60, 20)], NO_FILL),
+        fixture("line_outside", l, &[(-10, 40), (80, 20)], NO_FILL),
+    ]);
+}
+
+#[test]
+fn ellipses() {
+    let e = Shape::Ellipse;
+    check(vec![
+        fixture("ellipse_circle", e, &[(8, 8), (56, 56)], NO_FILL),
+        fixture("ellipse_wide", e, &[(2, 20), (62, 44)], NO_FILL),
+        fixture("ellipse_tall", e, &[(20, 2), (44, 62)], NO_FILL),
+        fixture("ellipse_small", e, &[(28, 28), (33, 31)], NO_FILL),
+        fixture("ellipse_flat_x", e, &[(8, 32), (56, 32)], NO_FILL),
+        fixture("ellipse_flat_y", e, &[(32, 8), (32, 56)], NO_FILL),
+        fixture("ellipse_filled", e, &[(6, 12), (58, 52)], FILL),
+        fixture("ellipse_filled_small", e, &[(28, 28), (35, 33)], FILL),
+    ]);
+}
+
+#[test]
+fn rectangles() {
+    let r = Shape::Rectangle;
+    check(vec![
+        fixture("rectangle", r, &[(8, 12), (56, 52)], NO_FILL),
+        fixture("rectangle_reversed", r, &[(56, 52), (8, 12)], NO_FILL),
+        fixture("rectangle_filled", r, &[(8, 12), (56, 52)], FILL),
+        fixture("rectangle_flat", r, &[(8, 32), (56, 32)], NO_FILL),
+        fixture("rectangle_point", r, &[(32, 32), (32, 32)], NO_FILL),
+    ]);
+}
+
+#[test]
+fn triangles() {
+    let t = Shape::Triangle;
+    check(vec![
+        fixture("triangle_building", t, &[(8, 8), (56, 40)], NO_FILL),
+        fixture("triangle", t, &[(32, 4), (60, 58), (4, 50)], NO_FILL),
+        fixture("triangle_filled", t, &[(32, 4), (60, 58), (4, 50)], FILL),
+        fixture("triangle_narrow", t, &[(4, 4), (60, 12), (8, 10)], FILL),
+        fixture("triangle_flat_top", t, &[(4, 8), (60, 8), (32, 56)], FILL),
+        fixture(
+            "triangle_flat_bottom",
+            t,
+            &[(32, 8), (60, 56), (4, 56)],
+            FILL,
+        ),
+        fixture("triangle_flat", t, &[(4, 32), (32, 32), (60, 32)], FILL),
+        fixture(
+            "triangle_translucent",
+            t,
+            &[(32, 4), (60, 58), (4, 50)],
+            RGBA::new(220, 60, 40, 128),
+        ),
+    ]);
+}
+
+#[test]
+fn beziers() {
+    let b = Shape::Bezier;
+    check(vec![
+        fixture("bezier_linear", b, &[(4, 60), (60, 4)], NO_FILL),
+        fixture(
+            "bezier_quadratic",
+            b,
+            &[(4, 60), (32, 0), (60, 60)],
+            NO_FILL,
+        ),
+        fixture(
+            "bezier_cubic",
+            b,
+            &[(4, 32), (20, 0), (44, 64), (60, 32)],
+            NO_FILL,
+        ),
+        fixture(
+            "bezier_loop",
+            b,
+            &[(8, 56), (64, 0), (0, 0), (56, 56)],
+            NO_FILL,
+        ),
+        fixture(
+            "bezier_high_degree",
+            b,
+            &[
+                (2, 60),
+                (10, 2),
+                (22, 62),
+                (32, 2),
+                (42, 62),
+                (54, 2),
+                (62, 60),
+            ],
+            NO_FILL,
+        ),
+    ]);
+}
+
+#[test]
+fn thick_borders() {
+    check(vec![
+        thick("thick_line", Shape::Line, &[(6, 10), (58, 50)], NO_FILL, 5),
+        thick(
+            "thick_line_even",
+            Shape::Line,
+            &[(6, 32), (58, 32)],
+            NO_FILL,
+            4,
+        ),
+        thick(
+            "thick_line_point",
+            Shape::Line,
+            &[(32, 32), (32, 32)],
+            NO_FILL,
+            9,
+        ),
+        thick(
+            "thick_rectangle",
+            Shape::Rectangle,
+            &[(8, 12), (56, 52)],
+            FILL,
+            6,
+        ),
+        thick(
+            "thick_ellipse",
+            Shape::Ellipse,
+            &[(6, 12), (58, 52)],
+            FILL,
+            5,
+        ),
+        thick(
+            "thick_ellipse_flat",
+            Shape::Ellipse,
+            &[(8, 32), (56, 32)],
+            NO_FILL,
+            4,
+        ),
+        thick(
+            "thick_triangle",
+            Shape::Triangle,
+            &[(32, 6), (58, 56), (6, 50)],
+            FILL,
+            5,
+        ),
+        thick(
+            "thick_bezier",
+            Shape::Bezier,
+            &[(4, 32), (20, 0), (44, 64), (60, 32)],
+            NO_FILL,
+            6,
+        ),
+    ]);
+}
+
+#[test]
+fn caps_and_joins() {
+    let line = [(14, 20), (50, 44)];
+    let corner = [(8, 56), (32, 10), (56, 56)];
+    let sharp = [(4, 40), (36, 32), (4, 24)];
+    let rect = [(14, 14), (50, 50)];
+    let (l, t, r) = (Shape::Line, Shape::Triangle, Shape::Rectangle);
+    let (butt, miter) = (LineCap::Butt, LineJoin::Miter);
+
+    check(vec![
+        styled("cap_butt", l, &line, butt, miter, 4.0),
+        styled("cap_round", l, &line, LineCap::Round, miter, 4.0),
+        styled("cap_square", l, &line, LineCap::Square, miter, 4.0),
+        styled("join_miter", t, &corner, butt, miter, 4.0),
+        styled("join_round", t, &corner, butt, LineJoin::Round, 4.0),
+        styled("join_bevel", t, &corner, butt, LineJoin::Bevel, 4.0),
+        // the sharp corner is longer than the limit of 4, its cut like a bevel
+        styled("join_miter_limit", t, &sharp, butt, miter, 4.0),
+        styled("join_miter_no_limit", t, &sharp, butt, miter, 20.0),
+        styled("join_round_rectangle", r, &rect, butt, LineJoin::Round, 4.0),
+        styled("join_bevel_rectangle", r, &rect, butt, LineJoin::Bevel, 4.0),
+    ]);
+}
+
+#[test]
+fn dashes() {
+    let (l, e, t, r, b) = (
+        Shape::Line,
+        Shape::Ellipse,
+        Shape::Triangle,
+        Shape::Rectangle,
+        Shape::Bezier,
+    );
+    let curve = [(4, 32), (20, 0), (44, 64), (60, 32)];
+    let mut dots = dashed("dash_dots", l, &[(8, 32), (56, 32)], 6, &[0, 10], 0);
+    dots.1.line_cap = LineCap::Round;
+    let mut filled = dashed(
+        "dash_ellipse_filled",
+        e,
+        &[(6, 12), (58, 52)],
+        5,
+        &[8, 6],
+        0,
+    );
+    filled.1.fill_color = FILL;
+
+    check(vec![
+        dashed("dash_line", l, &[(6, 10), (58, 50)], 1, &[6, 3], 0),
+        // an odd pattern is repeated, [5] is a dash of 5 and a gap of 5
+        dashed("dash_line_odd", l, &[(6, 32), (58, 32)], 1, &[5], 0),
+        dashed("dash_line_offset", l, &[(6, 32), (58, 32)], 1, &[5], 3),
+        // the pattern continues around the corners instead of restarting on each side
+        dashed("dash_rectangle", r, &[(8, 12), (56, 52)], 1, &[7, 4], 0),
+        dashed(
+            "dash_triangle",
+            t,
+            &[(32, 6), (58, 56), (6, 50)],
+            1,
+            &[7, 4],
+            0,
+        ),
+        dashed("dash_ellipse", e, &[(6, 12), (58, 52)], 1, &[6, 4], 0),
+        dashed("dash_bezier", b, &curve, 1, &[6, 4], 0),
+        dashed("dash_thick_line", l, &[(6, 10), (58, 50)], 5, &[10, 6], 0),
+        dashed(
+            "dash_thick_rectangle",
+            r,
+            &[(8, 12), (56, 52)],
+            4,
+            &[12, 6],
+            0,
+        ),
+        dashed("dash_thick_bezier", b, &curve, 4, &[10, 6], 0),
+        filled,
+        dots,
+    ]);
+}
+
+#[test]
+fn antialiasing() {
+    let (l, e, t, r, b) = (
+        Shape::Line,
+        Shape::Ellipse,
+        Shape::Triangle,
+        Shape::Rectangle,
+        Shape::Bezier,
+    );
+    let curve = [(4, 32), (20, 0), (44, 64), (60, 32)];
+    let triangle = [(32, 6), (58, 56), (6, 50)];
+    let mut dashed = antialiased("aa_dashed_line", l, &[(6, 10), (58, 50)], NO_FILL, 1);
+    dashed.1.dash_pattern = vec![6, 3];
+
+    check(vec![
+        antialiased("aa_line", l, &[(6, 10), (58, 50)], NO_FILL, 1),
+        antialiased("aa_line_steep", l, &[(20, 4), (40, 60)], NO_FILL, 1),
+        // straight lines have nothing to smooth, they are the same as the pixel exact ones
+        antialiased("aa_line_horizontal", l, &[(6, 32), (58, 32)], NO_FILL, 1),
+        antialiased("aa_thick_line", l, &[(6, 10), (58, 50)], NO_FILL, 5),
+        dashed,
+        antialiased("aa_ellipse", e, &[(6, 12), (58, 52)], NO_FILL, 1),
+        antialiased("aa_ellipse_filled", e, &[(6, 12), (58, 52)], FILL, 1),
+        antialiased("aa_thick_ellipse", e, &[(6, 12), (58, 52)], FILL, 5),
+        antialiased("aa_triangle", t, &triangle, NO_FILL, 1),
+        antialiased("aa_triangle_filled", t, &triangle, FILL, 1),
+        antialiased("aa_rectangle_filled", r, &[(8, 12), (56, 52)], FILL, 1),
+        antialiased("aa_bezier", b, &curve, NO_FILL, 1),
+        antialiased("aa_thick_bezier", b, &curve, NO_FILL, 4),
+    ]);
+}
+
+#[test]
+fn polygons() {
+    let (pg, pl) = (Shape::Polygon, Shape::Polyline);
+    // a star drawn in one stroke, its center is inside with non-zero and a hole with even-odd
+    let star = [(32, 4), (49, 58), (4, 24), (60, 24), (15, 58)];
+    let concave = [(6, 6), (58, 6), (58, 58), (32, 30), (6, 58)];
+    let zigzag = [(4, 52), (18, 12), (32, 52), (46, 12), (60, 52)];
+    let mut even_odd = fixture("polygon_star_even_odd", pg, &star, FILL);
+    even_odd.1.fill_rule = FillRule::EvenOdd;
+    let mut thick_star = thick("polygon_thick_star", pg, &star, FILL, 4);
+    thick_star.1.line_join = LineJoin::Round;
+
+    check(vec![
+        fixture("polygon_star", pg, &star, FILL),
+        even_odd,
+        fixture("polygon_concave", pg, &concave, FILL),
+        fixture("polygon_outline", pg, &concave, NO_FILL),
+        thick_star,
+        antialiased("polygon_antialiased", pg, &star, FILL, 1),
+        fixture("polyline", pl, &zigzag, NO_FILL),
+        thick("polyline_thick", pl, &zigzag, NO_FILL, 5),
+    ]);
+}
+
+#[test]
+fn freehand() {
+    let fh = Shape::Freehand;
+    // what is left of a wavy stroke after simplifying it
+    let wave = [(4, 40), (14, 16), (26, 44), (38, 14), (50, 46), (60, 22)];
+    let mut smooth = fixture("freehand_smooth", fh, &wave, NO_FILL);
+    smooth.1.smooth = true;
+    let mut brush = thick("freehand_brush", fh, &wave, NO_FILL, 5);
+    brush.1.smooth = true;
+    brush.1.line_cap = LineCap::Round;
+    brush.1.line_join = LineJoin::Round;
+
+    check(vec![
+        fixture("freehand", fh, &wave, NO_FILL),
+        smooth,
+        brush,
+        antialiased("freehand_antialiased", fh, &wave, NO_FILL, 1),
+    ]);
+}
+
+#[test]
+fn ellipse_arcs() {
+    let e = Shape::Ellipse;
+    let wide = [(4, 18), (60, 46)];
+    let part = |name, arc, fill_color| {
+        let mut f = fixture(name, e, &wide, fill_color);
+        f.1.arc = arc;
+        f.1.start_angle = 30.0;
+        f.1.end_angle = 300.0;
+        f
+    };
+    let mut rotated = fixture("ellipse_rotated", e, &wide, NO_FILL);
+    rotated.1.rotation = 30.0;
+    let mut rotated_filled = fixture("ellipse_rotated_filled", e, &wide, FILL);
+    rotated_filled.1.rotation = -45.0;
+    let mut rotated_thick = thick("ellipse_rotated_thick", e, &wide, FILL, 5);
+    rotated_thick.1.rotation = 60.0;
+    let mut rotated_pie = part("ellipse_rotated_pie", ArcKind::Pie, FILL);
+    rotated_pie.1.rotation = 20.0;
+    let mut aa_pie = part("aa_ellipse_pie", ArcKind::Pie, FILL);
+    aa_pie.1.antialias = true;
+    aa_pie.1.rotation = 20.0;
+
+    check(vec![
+        rotated,
+        rotated_filled,
+        rotated_thick,
+        part("ellipse_arc", ArcKind::Arc, NO_FILL),
+        part("ellipse_pie", ArcKind::Pie, FILL),
+        part("ellipse_chord", ArcKind::Chord, FILL),
+        rotated_pie,
+        aa_pie,
+    ]);
+}
+
+#[test]
+fn rounded_rectangles() {
+    let r = Shape::Rectangle;
+    let square = [(6, 6), (57, 57)];
+    let rounded = |(name, mut core): (&'static str, ShapeCore), radii: [u32; 4]| {
+        core.corner_radii = radii;
+        (name, core)
+    };
+
+    check(vec![
+        rounded(fixture("rounded", r, &square, NO_FILL), [12; 4]),
+        rounded(fixture("rounded_filled", r, &square, FILL), [12; 4]),
+        rounded(fixture("rounded_corners", r, &square, FILL), [0, 8, 25, 40]),
+        rounded(
+            fixture("rounded_pill", r, &[(4, 22), (59, 41)], FILL),
+            [99; 4],
+        ),
+        rounded(thick("rounded_thick", r, &square, FILL, 5), [16; 4]),
+        rounded(dashed("rounded_dashed", r, &square, 1, &[6, 3], 0), [16; 4]),
+        rounded(antialiased("aa_rounded", r, &square, FILL, 1), [16; 4]),
+    ]);
+}
+
+#[test]
+fn texts() {
+    let text = |(name, mut core): (&'static str, ShapeCore), text: &str, font_size: f32| {
+        core.text = text.to_string();
+        core.font_size = font_size;
+        (name, core)
+    };
+
+    check(vec![
+        text(fixture("text", Shape::Text, &[(4, 4)], NO_FILL), "Ag", 32.0),
+        text(
+            fixture("text_lines", Shape::Text, &[(2, 2)], NO_FILL),
+            "paint\nWAVE",
+            16.0,
+        ),
+        text(
+            antialiased("aa_text", Shape::Text, &[(4, 4)], NO_FILL, 1),
+            "Ag",
+            32.0,
+        ),
+    ]);
+}
+
+#[test]
+fn images() {
+    // a 4x4 png with a color on each corner, white in the middle and a transparent pixel
+    let mut png = Frame::new(4, 4);
+    let mut canvas = png.canvas();
+    for (x, y) in (0..4).flat_map(|x| (0..4).map(move |y| (x, y))) {
+        let color = match (x, y) {
+            (0, 0) => RGBA::new(255, 0, 0, 255),
+            (3, 0) => RGBA::new(0, 255, 0, 255),
+            (0, 3) => FILL,
+            (3, 3) => RGBA::new(255, 200, 0, 255),
+            // left transparent
+            (2, 1) => continue,
+            _ => BORDER,
+        };
+        canvas.set_pixel(x, y, color);
+    }
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden_image.png");
+    png.save_png(&path).unwrap();
+
+    let image = |name, points: &[(i32, i32)], sampling| {
+        let (name, mut core) = fixture(name, Shape::Image, points, NO_FILL);
+        core.image = Some(ImageSource::embed(&path).unwrap());
+        core.sampling = sampling;
+        (name, core)
+    };
+
+    check(vec![
+        image("image_nearest", &[(4, 4), (59, 59)], ImageSampling::Nearest),
+        image(
+            "image_bilinear",
+            &[(4, 4), (59, 59)],
+            ImageSampling::Bilinear,
+        ),
+        image(
+            "image_flipped",
+            &[(59, 10), (4, 53)],
+            ImageSampling::Nearest,
+        ),
+    ]);
+}
+
+#[test]
+fn splines() {
+    let zigzag = [
+        (4, 50),
+        (14, 10),
+        (24, 54),
+        (34, 10),
+        (44, 54),
+        (54, 10),
+        (59, 50),
+    ];
+    let knots = |(name, mut core): (&'static str, ShapeCore), degree, knot_vector| {
+        core.degree = degree;
+        core.knot_vector = knot_vector;
+        (name, core)
+    };
+
+    check(vec![
+        fixture("bspline", Shape::BSpline, &zigzag, NO_FILL),
+        knots(
+            fixture("bspline_uniform", Shape::BSpline, &zigzag, NO_FILL),
+            3,
+            KnotVector::Uniform,
+        ),
+        knots(
+            fixture("bspline_quadratic", Shape::BSpline, &zigzag, NO_FILL),
+            2,
+            KnotVector::Clamped,
+        ),
+        thick("bspline_thick", Shape::BSpline, &zigzag, NO_FILL, 4),
+        fixture("catmull_rom", Shape::CatmullRom, &zigzag, NO_FILL),
+        antialiased("aa_catmull_rom", Shape::CatmullRom, &zigzag, NO_FILL, 1),
+    ]);
+}
+
+#[test]
+fn rational_beziers() {
+    let corner = [(60, 4), (60, 60), (4, 60)];
+    let weighted = |(name, mut core): (&'static str, ShapeCore), weight: f32| {
+        core.weights = vec![1.0, weight, 1.0];
+        (name, core)
+    };
+
+    check(vec![
+        // a quarter of the circle of radius 56 around (4, 4)
+        weighted(
+            fixture("rational_circle", Shape::Bezier, &corner, NO_FILL),
+            FRAC_1_SQRT_2,
+        ),
+        weighted(
+            fixture("rational_heavy", Shape::Bezier, &corner, NO_FILL),
+            4.0,
+        ),
+        weighted(
+            thick("rational_thick", Shape::Bezier, &corner, NO_FILL, 4),
+            FRAC_1_SQRT_2,
+        ),
+    ]);
+}
+
+#[test]
+fn paths() {
+    // a smooth joint on the left and a corner on the right
+    let points = [
+        (4, 56),
+        (4, 20),
+        (16, 8),
+        (28, 8),
+        (40, 8),
+        (44, 24),
+        (44, 36),
+        (60, 40),
+        (60, 56),
+        (60, 60),
+    ];
+    let path = |(name, mut core): (&'static str, ShapeCore)| {
+        core.joints = vec![
+            JointKind::Symmetric,
+            JointKind::Symmetric,
+            JointKind::Corner,
+            JointKind::Symmetric,
+        ];
+        (name, core)
+    };
+
+    check(vec![
+        path(fixture("path", Shape::Path, &points, NO_FILL)),
+        path(thick("path_thick", Shape::Path, &points, NO_FILL, 4)),
+        path(antialiased("aa_path", Shape::Path, &points, NO_FILL, 1)),
+    ]);
+}
@@ -0,0 +1,104 @@
+// Hit test checks. A shape must be selectable by clicking anywhere on what is drawn, so wide
+// borders can be clicked further away from the control points than thin ones.
+
+use paint_app::{
+    core::{FillRule, Point, Shape, ShapeCore, RGBA},
+    primitives::new_shape_from_core,
+};
+
+const BORDER: RGBA = RGBA::new(255, 255, 255, 255);
+const FILL: RGBA = RGBA::new(40, 120, 220, 255);
+const NO_FILL: RGBA = RGBA::new(0, 0, 0, 0);
+
+fn hits(shape_type: Shape, points: &[(i32, i32)], fill_color: RGBA, width: u32, p: Point) -> bool {
+    let core = ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: BORDER,
+        fill_color,
+        shape_type,
+        stroke_width: width,
+        ..Default::default()
+    };
+    new_shape_from_core(core).hit_test(p)
+}
+
+#[test]
+fn wide_lines_are_hit_further_away() {
+    let line = [(100, 100), (300, 100)];
+    assert!(!hits(Shape::Line, &line, NO_FILL, 1, Point(200, 140)));
+    assert!(hits(Shape::Line, &line, NO_FILL, 40, Point(200, 140)));
+    assert!(!hits(Shape::Line, &line, NO_FILL, 40, Point(200, 160)));
+}
+
+#[test]
+fn wide_rectangle_borders_are_hit() {
+    let rect = [(100, 100), (300, 200)];
+    assert!(!hits(Shape::Rectangle, &rect, NO_FILL, 1, Point(88, 150)));
+    assert!(hits(Shape::Rectangle, &rect, NO_FILL, 20, Point(88, 150)));
+    // the inside is still empty
+    assert!(!hits(Shape::Rectangle, &rect, NO_FILL, 20, Point(200, 150)));
+
+    // half of the border is outside a filled rectangle
+    assert!(!hits(Shape::Rectangle, &rect, FILL, 1, Point(95, 150)));
+    assert!(hits(Shape::Rectangle, &rect, FILL, 20, Point(95, 150)));
+}
+
+#[test]
+fn wide_ellipse_borders_are_hit() {
+    let ellipse = [(100, 100), (300, 200)];
+    assert!(!hits(Shape::Ellipse, &ellipse, NO_FILL, 1, Point(88, 150)));
+    assert!(hits(Shape::Ellipse, &ellipse, NO_FILL, 20, Point(88, 150)));
+    assert!(!hits(
+        Shape::Ellipse,
+        &ellipse,
+        NO_FILL,
+        20,
+        Point(200, 150)
+    ));
+
+    assert!(!hits(Shape::Ellipse, &ellipse, FILL, 1, Point(95, 150)));
+    assert!(hits(Shape::Ellipse, &ellipse, FILL, 20, Point(95, 150)));
+}
+
+#[test]
+fn wide_filled_triangle_borders_are_hit() {
+    let triangle = [(100, 100), (300, 100), (200, 250)];
+    assert!(hits(Shape::Triangle, &triangle, FILL, 1, Point(200, 150)));
+    assert!(!hits(Shape::Triangle, &triangle, FILL, 1, Point(200, 95)));
+    assert!(hits(Shape::Triangle, &triangle, FILL, 20, Point(200, 95)));
+}
+
+#[test]
+fn polygons_are_hit_with_their_fill_rule() {
+    let star = [(200, 40), (306, 360), (40, 160), (360, 160), (94, 360)];
+    let center = Point(200, 200);
+    let tip = Point(200, 60);
+
+    assert!(hits(Shape::Polygon, &star, FILL, 1, center));
+    assert!(hits(Shape::Polygon, &star, FILL, 1, tip));
+    assert!(!hits(Shape::Polygon, &star, FILL, 1, Point(40, 40)));
+    // without fill only the border is hit
+    assert!(!hits(Shape::Polygon, &star, NO_FILL, 1, center));
+
+    let mut core = ShapeCore {
+        points: star.iter().map(|p| Point::from(*p)).collect(),
+        fill_color: FILL,
+        shape_type: Shape::Polygon,
+        fill_rule: FillRule::EvenOdd,
+        ..Default::default()
+    };
+    // the center of the star is a hole with even-odd
+    assert!(!new_shape_from_core(core.clone()).hit_test(center));
+    assert!(new_shape_from_core(core.clone()).hit_test(tip));
+    core.fill_rule = FillRule::NonZero;
+    assert!(new_shape_from_core(core).hit_test(center));
+}
+
+#[test]
+fn polylines_are_hit_on_every_line() {
+    let zigzag = [(100, 300), (200, 100), (300, 300)];
+    assert!(hits(Shape::Polyline, &zigzag, NO_FILL, 1, Point(150, 200)));
+    assert!(hits(Shape::Polyline, &zigzag, NO_FILL, 1, Point(250, 200)));
+    // a polyline is open, the missing side is not hit
+    assert!(!hits(Shape::Polyline, &zigzag, NO_FILL, 1, Point(200, 300)));
+}
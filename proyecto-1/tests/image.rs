@@ -0,0 +1,162 @@
+// Image checks. A png is stretched over the box between both points with each sampling, painted
+// over what is below with its alpha, and embedded pngs travel inside the saved drawing.
+
+use std::{fs, path::PathBuf};
+
+use paint_app::{
+    core::{ImageSampling, ImageSource, Point, Shape, ShapeCore, RGBA},
+    draw_state::DrawState,
+    headless::{render, Frame},
+    primitives::new_shape_from_core,
+};
+
+const RED: RGBA = RGBA::new(255, 0, 0, 255);
+const GREEN: RGBA = RGBA::new(0, 255, 0, 255);
+const BLUE: RGBA = RGBA::new(0, 0, 255, 255);
+const TRANSPARENT: RGBA = RGBA::new(0, 0, 0, 0);
+const WHITE: RGBA = RGBA::new(255, 255, 255, 255);
+
+/// writes a 2x2 png (red, green on top, blue and transparent below) and returns its path
+fn quadrants_png(name: &str) -> PathBuf {
+    let mut frame = Frame::new(2, 2);
+    let mut canvas = frame.canvas();
+    canvas.set_pixel(0, 0, RED);
+    canvas.set_pixel(1, 0, GREEN);
+    canvas.set_pixel(0, 1, BLUE);
+
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    frame.save_png(&path).unwrap();
+    path
+}
+
+fn image(a: (i32, i32), b: (i32, i32), image: ImageSource, sampling: ImageSampling) -> ShapeCore {
+    ShapeCore {
+        points: vec![Point::from(a), Point::from(b)],
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Image,
+        image: Some(image),
+        sampling,
+        ..Default::default()
+    }
+}
+
+/// draws the image over a white frame
+fn draw(core: ShapeCore) -> Frame {
+    let mut frame = Frame::new(30, 30);
+    let mut canvas = frame.canvas();
+    canvas.clear(WHITE);
+    new_shape_from_core(core).draw(&mut canvas);
+    frame
+}
+
+#[test]
+fn nearest_sampling_enlarges_each_pixel_into_a_block() {
+    let png = ImageSource::embed(&quadrants_png("nearest.png")).unwrap();
+    let frame = draw(image((10, 10), (19, 19), png, ImageSampling::Nearest));
+
+    for y in 10..20 {
+        for x in 10..20 {
+            let expected = match (x < 15, y < 15) {
+                (true, true) => RED,
+                (false, true) => GREEN,
+                (true, false) => BLUE,
+                // the transparent pixel leaves the background
+                (false, false) => WHITE,
+            };
+            assert_eq!(frame.get_pixel(x, y), expected, "pixel {x} {y}");
+        }
+    }
+    // nothing is drawn out of the box
+    assert_eq!(frame.get_pixel(9, 10), WHITE);
+    assert_eq!(frame.get_pixel(20, 19), WHITE);
+}
+
+#[test]
+fn bilinear_sampling_blends_neighbours() {
+    let png = ImageSource::embed(&quadrants_png("bilinear.png")).unwrap();
+    let frame = draw(image((0, 0), (19, 19), png, ImageSampling::Bilinear));
+
+    // the corners are the colors of the image
+    assert_eq!(frame.get_pixel(0, 0), RED);
+    assert_eq!(frame.get_pixel(19, 0), GREEN);
+    assert_eq!(frame.get_pixel(0, 19), BLUE);
+    assert_eq!(frame.get_pixel(19, 19), WHITE);
+
+    // between red and green both are mixed
+    let middle = frame.get_pixel(9, 0);
+    assert!(middle[0] > 100 && middle[1] > 100 && middle[2] == 0);
+
+    // going towards the transparent pixel green fades over the white background instead of
+    // getting darker
+    let fading = frame.get_pixel(19, 10);
+    assert!(fading[1] == 255 && fading[0] > 0 && fading[0] == fading[2]);
+}
+
+#[test]
+fn swapped_points_flip_the_image() {
+    let png = ImageSource::embed(&quadrants_png("flip.png")).unwrap();
+    let frame = draw(image((19, 10), (10, 19), png, ImageSampling::Nearest));
+    assert_eq!(frame.get_pixel(10, 10), GREEN);
+    assert_eq!(frame.get_pixel(19, 10), RED);
+    assert_eq!(frame.get_pixel(19, 19), BLUE);
+}
+
+#[test]
+fn embedded_images_dont_need_the_file() {
+    let path = quadrants_png("saved.png");
+    let mut state = DrawState::new();
+    let embedded = image(
+        (0, 0),
+        (9, 9),
+        ImageSource::embed(&path).unwrap(),
+        ImageSampling::Nearest,
+    );
+    let referenced = image(
+        (10, 0),
+        (19, 9),
+        ImageSource::File(path.clone()),
+        ImageSampling::Nearest,
+    );
+    state.add_shape(new_shape_from_core(embedded));
+    state.add_shape(new_shape_from_core(referenced));
+    let drawing = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("image_drawing.json");
+    state.save_to_file(drawing.clone()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut loaded = DrawState::new();
+    loaded.load_from_file(drawing).unwrap();
+    let frame = render(&loaded, 30, 30);
+    assert_eq!(frame.get_pixel(2, 2), RED);
+    assert_eq!(frame.get_pixel(7, 2), GREEN);
+
+    // without its file the image is a crossed box
+    assert_eq!(frame.get_pixel(16, 2), TRANSPARENT);
+    assert_eq!(frame.get_pixel(10, 0), RGBA::new(0, 0, 0, 255));
+    assert_eq!(frame.get_pixel(14, 4), RGBA::new(0, 0, 0, 255));
+}
+
+#[test]
+fn only_images_save_a_png() {
+    let line = ShapeCore {
+        points: vec![Point(0, 0), Point(10, 10)],
+        shape_type: Shape::Line,
+        ..Default::default()
+    };
+    let json = serde_json::to_string(&line).unwrap();
+    assert!(!json.contains("\"image\""));
+
+    // copies of an embedded png share its text
+    let path = quadrants_png("shared.png");
+    let core = image(
+        (0, 0),
+        (9, 9),
+        ImageSource::embed(&path).unwrap(),
+        ImageSampling::Nearest,
+    );
+    let copy = new_shape_from_core(core.clone()).get_core();
+    let (Some(ImageSource::Embedded(a)), Some(ImageSource::Embedded(b))) = (core.image, copy.image)
+    else {
+        panic!("the png is not embedded");
+    };
+    assert!(std::sync::Arc::ptr_eq(&a, &b));
+}
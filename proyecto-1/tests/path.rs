@@ -0,0 +1,145 @@
+// Path checks. Moving a handle keeps the joint of its point (corner, smooth or symmetric), moving
+// a point takes its handles with it, and the joints are saved and undone with the path.
+
+use paint_app::{
+    core::{JointKind, Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+/// two curves that meet at (60, 40). The handles of that point go left and right of it
+fn two_curves(joint: JointKind) -> ShapeCore {
+    let points = [
+        (10, 60),
+        (20, 20),
+        (40, 40),
+        (60, 40),
+        (80, 40),
+        (100, 20),
+        (110, 60),
+    ];
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        joints: vec![joint; 3],
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Path,
+        ..Default::default()
+    }
+}
+
+/// the points after pulling the handle that leaves the middle point to (80, 60)
+fn pulled(joint: JointKind) -> Vec<Point> {
+    let mut shape = new_shape_from_core(two_curves(joint));
+    shape.update(&UpdateOp::ControlPoint(4, Point(80, 60)));
+    shape.get_core().points
+}
+
+#[test]
+fn moving_a_handle_keeps_the_joint() {
+    // a corner leaves the other handle where it was
+    assert_eq!(pulled(JointKind::Corner)[2], Point(40, 40));
+
+    // symmetric handles are mirrored around the point
+    assert_eq!(pulled(JointKind::Symmetric)[2], Point(40, 20));
+
+    // smooth handles are on the same line, but the other one keeps its length of 20
+    let smooth = pulled(JointKind::Smooth)[2];
+    let (dx, dy) = (smooth.0 - 60, smooth.1 - 40);
+    assert_eq!(dx, dy);
+    assert!((smooth.distance(Point(60, 40)) - 20.0).abs() < 1.0);
+
+    // the ends only have one handle, there is nothing to keep
+    let mut shape = new_shape_from_core(two_curves(JointKind::Symmetric));
+    shape.update(&UpdateOp::ControlPoint(1, Point(0, 0)));
+    shape.update(&UpdateOp::ControlPoint(5, Point(120, 0)));
+    assert_eq!(
+        shape.get_core().points[2..5],
+        two_curves(JointKind::Symmetric).points[2..5]
+    );
+}
+
+#[test]
+fn moving_a_point_takes_its_handles() {
+    let mut shape = new_shape_from_core(two_curves(JointKind::Corner));
+    shape.update(&UpdateOp::ControlPoint(3, Point(65, 50)));
+    let points = shape.get_core().points;
+    assert_eq!(points[2..5], [Point(45, 50), Point(65, 50), Point(85, 50)]);
+    assert_eq!(points[1], Point(20, 20));
+}
+
+#[test]
+fn changing_the_joint_fits_the_handles() {
+    // a corner with handles of different lengths and directions
+    let mut core = two_curves(JointKind::Corner);
+    core.points[2] = Point(60, 20);
+    core.points[4] = Point(90, 40);
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(core.clone()));
+
+    state.update_shape(0, UpdateOp::ChangeJoint(1, JointKind::Symmetric));
+    let fitted = state.get_object(0).get_core();
+    let (before, center, after) = (fitted.points[2], fitted.points[3], fitted.points[4]);
+    assert_eq!(before.0 + after.0, 2 * center.0);
+    assert_eq!(before.1 + after.1, 2 * center.1);
+    assert_eq!(fitted.joints[1], JointKind::Symmetric);
+
+    // undo brings back the corner and its handles
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().points, core.points);
+    assert_eq!(state.get_object(0).get_core().joints[1], JointKind::Corner);
+
+    // handles on top of their point are pulled out towards the points around
+    let mut core = two_curves(JointKind::Corner);
+    core.points[2] = core.points[3];
+    core.points[4] = core.points[3];
+    let mut shape = new_shape_from_core(core);
+    shape.update(&UpdateOp::ChangeJoint(1, JointKind::Smooth));
+    let points = shape.get_core().points;
+    assert!(points[2].0 < 60 && points[4].0 > 60);
+    assert!(points[2].distance(Point(60, 40)) > 10.0);
+}
+
+#[test]
+fn the_curve_goes_through_its_points() {
+    let core = two_curves(JointKind::Symmetric);
+    let mut frame = Frame::new(120, 80);
+    let mut canvas = frame.recording_canvas();
+    new_shape_from_core(core.clone()).draw(&mut canvas);
+    let pixels: Vec<(i32, i32)> = canvas
+        .written_pixels()
+        .into_iter()
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    for anchor in core.points.iter().step_by(3) {
+        assert!(
+            pixels.contains(&(anchor.0, anchor.1)),
+            "{anchor:?} is not drawn"
+        );
+    }
+    // the handles are not on the curve
+    assert!(!pixels.contains(&(20, 20)));
+
+    // points are grabbed before the handles on top of them
+    let mut core = two_curves(JointKind::Symmetric);
+    core.points[2] = core.points[3];
+    assert_eq!(
+        new_shape_from_core(core).control_point_at(Point(61, 41)),
+        Some(3)
+    );
+}
+
+#[test]
+fn joints_are_saved_and_checked() {
+    let json = serde_json::to_string(&two_curves(JointKind::Smooth)).unwrap();
+    let loaded: ShapeCore = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.joints, vec![JointKind::Smooth; 3]);
+    assert!(loaded.validate().is_ok());
+
+    let mut core = two_curves(JointKind::Smooth);
+    core.joints.pop();
+    assert!(core.validate().is_err());
+    core = two_curves(JointKind::Smooth);
+    core.points.pop();
+    assert!(core.validate().is_err());
+}
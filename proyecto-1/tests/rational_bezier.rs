@@ -0,0 +1,150 @@
+// Rational bezier checks. With weights a quadratic bezier is an exact arc of a circle, and the arc
+// stays the same after subdividing it or elevating its degree. The weights are saved with the
+// drawing and undone like any other change.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+const CENTER: (f32, f32) = (10.0, 10.0);
+const RADIUS: f32 = 100.0;
+
+/// a quarter of the circle of radius 100 around (10, 10), from the right to the bottom
+fn quarter_circle() -> ShapeCore {
+    ShapeCore {
+        points: vec![Point(110, 10), Point(110, 110), Point(10, 110)],
+        weights: vec![1.0, FRAC_1_SQRT_2, 1.0],
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type: Shape::Bezier,
+        ..Default::default()
+    }
+}
+
+/// the pixels the shapes draw
+fn pixels(cores: &[ShapeCore]) -> Vec<(i32, i32)> {
+    let mut frame = Frame::new(130, 130);
+    let mut canvas = frame.recording_canvas();
+    for core in cores {
+        new_shape_from_core(core.clone()).draw(&mut canvas);
+    }
+    canvas
+        .written_pixels()
+        .into_iter()
+        .map(|(x, y, _)| (x, y))
+        .collect()
+}
+
+/// checks that every pixel is on the circle. The curve is drawn with lines between pixels and the
+/// control points of the halves of a subdivided curve are rounded to pixels too, so they can be
+/// up to 2 pixels away
+fn assert_on_circle(pixels: &[(i32, i32)]) {
+    assert!(!pixels.is_empty());
+    for (x, y) in pixels {
+        let distance = (*x as f32 - CENTER.0).hypot(*y as f32 - CENTER.1);
+        assert!(
+            (distance - RADIUS).abs() <= 2.0,
+            "({x}, {y}) is {distance} away from the center"
+        );
+    }
+}
+
+#[test]
+fn weighted_quadratic_curves_draw_circles() {
+    let drawn = pixels(&[quarter_circle()]);
+    assert_on_circle(&drawn);
+    // the middle of the arc, 45 degrees
+    let middle = (CENTER.0 + RADIUS * FRAC_1_SQRT_2).round() as i32;
+    assert!(drawn
+        .iter()
+        .any(|(x, y)| (x - middle).abs() <= 1 && (y - middle).abs() <= 1));
+
+    // without weights the curve is a parabola that goes inside the circle
+    let parabola = ShapeCore {
+        weights: vec![],
+        ..quarter_circle()
+    };
+    assert!(pixels(&[parabola]).contains(&(85, 85)));
+}
+
+#[test]
+fn subdividing_and_elevating_keep_the_arc() {
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(quarter_circle()));
+    state.update_shape(0, UpdateOp::UpdateSubdivide(0.3));
+    state.subdivide_shape(0);
+
+    let halves: Vec<ShapeCore> = state.get_objects().iter().map(|s| s.get_core()).collect();
+    assert_eq!(halves.len(), 2);
+    for half in &halves {
+        assert_eq!(half.weights.len(), 3);
+        assert!(half.validate().is_ok());
+    }
+    assert_on_circle(&pixels(&halves));
+
+    // undoing brings the weights back
+    state.undo();
+    assert_eq!(state.get_objects().len(), 1);
+    assert_eq!(
+        state.get_object(0).get_core().weights,
+        quarter_circle().weights
+    );
+
+    let mut shape = new_shape_from_core(quarter_circle());
+    shape.update(&UpdateOp::DegreeElevate);
+    shape.update(&UpdateOp::DegreeElevate);
+    let elevated = shape.get_core();
+    assert_eq!(elevated.points.len(), 5);
+    assert_eq!(elevated.weights.len(), 5);
+    assert_on_circle(&pixels(&[elevated]));
+}
+
+#[test]
+fn changing_a_weight_can_be_undone() {
+    let mut core = quarter_circle();
+    core.weights.clear();
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(core));
+
+    state.update_shape(0, UpdateOp::ChangeWeight(1, FRAC_1_SQRT_2));
+    let changed = state.get_object(0).get_core();
+    assert_eq!(changed.weights, quarter_circle().weights);
+    assert_on_circle(&pixels(&[changed]));
+
+    state.undo();
+    assert!(state.get_object(0).get_core().weights.is_empty());
+    state.redo();
+    assert_eq!(
+        state.get_object(0).get_core().weights,
+        quarter_circle().weights
+    );
+    // the weight of another point is another step
+    state.update_shape(0, UpdateOp::ChangeWeight(1, 2.0));
+    state.update_shape(0, UpdateOp::ChangeWeight(1, 3.0));
+    state.update_shape(0, UpdateOp::ChangeWeight(2, 4.0));
+    assert_eq!(state.get_object(0).get_core().weights, vec![1.0, 3.0, 4.0]);
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().weights, vec![1.0, 3.0, 1.0]);
+    // the changes of the same point are a single step, like a drag
+    state.undo();
+    assert!(state.get_object(0).get_core().weights.is_empty());
+}
+
+#[test]
+fn weights_are_saved_and_checked() {
+    let json = serde_json::to_string(&quarter_circle()).unwrap();
+    let loaded: ShapeCore = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.weights, quarter_circle().weights);
+
+    let mut core = quarter_circle();
+    core.weights.pop();
+    assert!(core.validate().is_err());
+    core.weights = vec![1.0, 0.0, 1.0];
+    assert!(core.validate().is_err());
+    core.weights = vec![1.0, f32::NAN, 1.0];
+    assert!(core.validate().is_err());
+}
@@ -0,0 +1,54 @@
+// Regular polygon and star checks. The tool stores them as polygons, so these check the corners
+// it builds: all at the radius (or the inner radius), the first one where the cursor is.
+
+use paint_app::{
+    core::{FillRule, Point, Shape, ShapeCore, RGBA},
+    primitives::{new_shape_from_core, regular_polygon},
+};
+
+const CENTER: Point = Point(100, 100);
+
+#[test]
+fn corners_are_on_the_circle_and_evenly_spaced() {
+    for sides in 3..=12 {
+        let angle = 0.3;
+        let corners = regular_polygon(CENTER, 60.0, angle, sides, None);
+        assert_eq!(corners.len(), sides as usize);
+
+        // the first corner points where the cursor was
+        let first = corners[0];
+        let expected = Point::from((100.0 + 60.0 * angle.cos(), 100.0 + 60.0 * angle.sin()));
+        assert_eq!(first, expected);
+
+        // rounding to pixels moves each corner less than a pixel
+        let side = 2.0 * 60.0 * (std::f32::consts::PI / sides as f32).sin();
+        for (i, corner) in corners.iter().enumerate() {
+            assert!((corner.distance(CENTER) - 60.0).abs() <= 1.0);
+            let next = corners[(i + 1) % corners.len()];
+            assert!((corner.distance(next) - side).abs() <= 1.5);
+        }
+    }
+}
+
+#[test]
+fn stars_alternate_outer_and_inner_corners() {
+    let corners = regular_polygon(CENTER, 80.0, -std::f32::consts::FRAC_PI_2, 5, Some(0.4));
+    assert_eq!(corners.len(), 10);
+    assert_eq!(corners[0], Point(100, 20));
+    for (i, corner) in corners.iter().enumerate() {
+        let radius = if i % 2 == 0 { 80.0 } else { 32.0 };
+        assert!((corner.distance(CENTER) - radius).abs() <= 1.0);
+    }
+
+    // its a polygon like any other, the center of the star is filled
+    let star = new_shape_from_core(ShapeCore {
+        points: corners,
+        fill_color: RGBA::new(255, 0, 0, 255),
+        shape_type: Shape::Polygon,
+        fill_rule: FillRule::EvenOdd,
+        ..Default::default()
+    });
+    assert!(star.hit_test(CENTER));
+    // between two points of the star, outside of it
+    assert!(!star.hit_test(Point(140, 60)));
+}
@@ -0,0 +1,75 @@
+// Command line checks. The arguments of the render command are parsed on the library (see
+// headless.rs) so the sizes, colors and files given are checked here without running the app.
+
+use paint_app::{
+    core::RGBA,
+    headless::{parse_color, parse_render_args, parse_size, RenderArgs, MAX_SIZE},
+};
+
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn sizes_are_positive_and_limited() {
+    assert_eq!(parse_size("1"), Ok(1));
+    assert_eq!(parse_size("800"), Ok(800));
+    assert_eq!(parse_size(&MAX_SIZE.to_string()), Ok(MAX_SIZE));
+
+    for wrong in ["0", "-5", "12.5", "wide", "", "99999999999"] {
+        assert!(parse_size(wrong).is_err(), "{wrong}");
+    }
+    // a size that fits on a u32 but would need gigabytes of memory
+    let error = parse_size(&(MAX_SIZE + 1).to_string()).unwrap_err();
+    assert!(error.contains("too big"), "{error}");
+}
+
+#[test]
+fn colors_have_three_or_four_channels() {
+    assert_eq!(parse_color("255,0,10"), Ok(RGBA::new(255, 0, 10, 255)));
+    assert_eq!(parse_color("1, 2, 3, 4"), Ok(RGBA::new(1, 2, 3, 4)));
+
+    for wrong in ["255,0", "1,2,3,4,5", "256,0,0", "red", ""] {
+        assert!(parse_color(wrong).is_err(), "{wrong}");
+    }
+}
+
+#[test]
+fn render_arguments_are_parsed_in_any_order() {
+    let parsed = parse_render_args(
+        &args("--width 800 in.json --background 255,255,255 out.png --height 600"),
+        (640, 480),
+    );
+    assert_eq!(
+        parsed,
+        Ok(RenderArgs {
+            input: "in.json".to_string(),
+            output: "out.png".to_string(),
+            width: 800,
+            height: 600,
+            background: Some(RGBA::new(255, 255, 255, 255)),
+        })
+    );
+
+    // without options the default size and the background of the drawing are used
+    let parsed = parse_render_args(&args("in.json out.png"), (640, 480)).unwrap();
+    assert_eq!((parsed.width, parsed.height), (640, 480));
+    assert_eq!(parsed.background, None);
+}
+
+#[test]
+fn wrong_render_arguments_are_reported() {
+    for wrong in [
+        "in.json",
+        "in.json out.png extra.png",
+        "in.json out.png --width",
+        "in.json out.png --width 0",
+        "in.json out.png --height 100000",
+        "in.json out.png --background blue",
+    ] {
+        assert!(
+            parse_render_args(&args(wrong), (640, 480)).is_err(),
+            "{wrong}"
+        );
+    }
+}
@@ -0,0 +1,113 @@
+// Rounded rectangle checks. The corners are quarters of the midpoint circle joined by the sides,
+// together they must not draw a pixel twice nor leave gaps. Clicks on the cut corners must miss.
+
+use std::collections::HashSet;
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    primitives::new_shape_from_core,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+mod common;
+use common::{assert_connected, record_shape, NO_FILL, TRANSLUCENT};
+
+fn rectangle(a: (i32, i32), b: (i32, i32), radii: [u32; 4], fill_color: RGBA) -> ShapeCore {
+    ShapeCore {
+        points: vec![Point::from(a), Point::from(b)],
+        color: TRANSLUCENT,
+        fill_color,
+        shape_type: Shape::Rectangle,
+        corner_radii: radii,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn rounded_rectangles_have_no_overdraw_or_gaps() {
+    let mut rng = StdRng::seed_from_u64(16);
+    for _ in 0..300 {
+        let a = (rng.gen_range(10..100), rng.gen_range(10..100));
+        let b = (rng.gen_range(100..190), rng.gen_range(100..190));
+        let radii = [(); 4].map(|_| rng.gen_range(0..60));
+        let what = format!("rectangle {a:?} {b:?} with corners {radii:?}");
+
+        let border = record_shape(rectangle(a, b, radii, NO_FILL));
+        assert!(
+            border.values().all(|n| *n == 1),
+            "{what} draws pixels twice"
+        );
+        let border: HashSet<_> = border.into_keys().collect();
+        assert_connected(&border, &what);
+
+        // the fill adds the pixels inside the border, without touching it
+        let filled = record_shape(rectangle(a, b, radii, TRANSLUCENT));
+        assert!(
+            filled.values().all(|n| *n == 1),
+            "filled {what} draws pixels twice"
+        );
+        assert!(border.iter().all(|p| filled.contains_key(p)));
+        let middle = ((a.0 + b.0) / 2, (a.1 + b.1) / 2);
+        assert!(filled.contains_key(&middle), "{what} is not filled");
+    }
+}
+
+#[test]
+fn rounded_corners_are_not_hit() {
+    let filled = new_shape_from_core(rectangle((20, 20), (120, 100), [30; 4], TRANSLUCENT));
+    assert!(filled.hit_test(Point(70, 60)));
+    assert!(filled.hit_test(Point(70, 20)));
+    // the corner of the box is far from the round corner
+    assert!(!filled.hit_test(Point(22, 22)));
+    assert!(!filled.hit_test(Point(118, 98)));
+
+    let outline = new_shape_from_core(rectangle((20, 20), (120, 100), [30; 4], NO_FILL));
+    assert!(!outline.hit_test(Point(70, 60)));
+    // on the circle of the top left corner, at 45 degrees
+    assert!(outline.hit_test(Point(29, 29)));
+    assert!(!outline.hit_test(Point(20, 20)));
+}
+
+#[test]
+fn the_handle_changes_the_radius() {
+    let mut shape = new_shape_from_core(rectangle((20, 20), (120, 100), [0; 4], NO_FILL));
+    let handles = shape.control_points();
+    assert_eq!(handles.len(), 3);
+
+    // the same radius on every corner is changed on all of them
+    shape.update(&UpdateOp::ControlPoint(2, Point(45, 45)));
+    assert_eq!(shape.get_core().corner_radii, [25; 4]);
+    assert_eq!(shape.control_points()[2], Point(45, 45));
+
+    // different radii only change the top left one
+    shape.update(&UpdateOp::ChangeCornerRadii([25, 0, 10, 0]));
+    shape.update(&UpdateOp::ControlPoint(2, Point(35, 35)));
+    assert_eq!(shape.get_core().corner_radii, [15, 0, 10, 0]);
+
+    // the corners are still moved like before
+    shape.update(&UpdateOp::ControlPoint(1, Point(140, 100)));
+    assert_eq!(shape.control_points()[1], Point(140, 100));
+}
+
+#[test]
+fn editing_one_corner_keeps_the_others() {
+    let mut state = DrawState::new();
+    let radii = [5, 10, 15, 20];
+    state.add_shape(new_shape_from_core(rectangle(
+        (20, 20),
+        (120, 120),
+        radii,
+        NO_FILL,
+    )));
+
+    // like the corner fields of the ui: start from the radii of the selected rectangle and
+    // change only the bottom right one
+    let mut edited = state.get_object(0).get_core().corner_radii;
+    edited[2] = 40;
+    assert!(state.update_shape(0, UpdateOp::ChangeCornerRadii(edited)));
+    assert_eq!(state.get_object(0).get_core().corner_radii, [5, 10, 40, 20]);
+
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().corner_radii, radii);
+}
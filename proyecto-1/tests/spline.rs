@@ -0,0 +1,176 @@
+// B-spline and Catmull-Rom checks. Moving a control point must only change the part of the curve
+// close to it, a clamped b-spline starts and ends on its control points, a Catmull-Rom spline
+// goes through all of them and inserting a knot adds a point without changing the curve.
+
+use std::collections::HashSet;
+
+use paint_app::{
+    core::{KnotVector, Point, Shape, ShapeCore, UpdateOp, RGBA},
+    draw_state::DrawState,
+    headless::Frame,
+    primitives::new_shape_from_core,
+};
+
+/// a zigzag of 8 control points from left to right
+const ZIGZAG: [(i32, i32); 8] = [
+    (10, 100),
+    (40, 40),
+    (70, 160),
+    (100, 40),
+    (130, 160),
+    (160, 40),
+    (190, 160),
+    (220, 100),
+];
+
+fn spline(shape_type: Shape, points: &[(i32, i32)], knot_vector: KnotVector) -> ShapeCore {
+    ShapeCore {
+        points: points.iter().map(|p| Point::from(*p)).collect(),
+        color: RGBA::new(0, 0, 0, 255),
+        shape_type,
+        knot_vector,
+        ..Default::default()
+    }
+}
+
+/// the pixels the shape draws
+fn pixels(core: ShapeCore) -> HashSet<(i32, i32)> {
+    let mut frame = Frame::new(240, 200);
+    let mut canvas = frame.recording_canvas();
+    new_shape_from_core(core).draw(&mut canvas);
+    canvas
+        .written_pixels()
+        .into_iter()
+        .map(|(x, y, _)| (x, y))
+        .collect()
+}
+
+/// checks that every pixel of a is at most a pixel away from one of b
+fn is_close(a: &HashSet<(i32, i32)>, b: &HashSet<(i32, i32)>) -> bool {
+    a.iter()
+        .all(|(x, y)| (-1..=1).any(|dx| (-1..=1).any(|dy| b.contains(&(x + dx, y + dy)))))
+}
+
+#[test]
+fn clamped_b_splines_start_and_end_on_their_control_points() {
+    let clamped = pixels(spline(Shape::BSpline, &ZIGZAG, KnotVector::Clamped));
+    assert!(clamped.contains(&(10, 100)));
+    assert!(clamped.contains(&(220, 100)));
+    // the curve stays inside the control polygon, it never reaches the inner corners
+    assert!(!clamped.contains(&(40, 40)));
+
+    let uniform = pixels(spline(Shape::BSpline, &ZIGZAG, KnotVector::Uniform));
+    assert!(!uniform.contains(&(10, 100)));
+    assert!(uniform.iter().all(|(x, _)| *x > 30 && *x < 200));
+}
+
+#[test]
+fn moving_a_control_point_only_changes_the_curve_close_to_it() {
+    for shape_type in [Shape::BSpline, Shape::CatmullRom] {
+        let core = spline(shape_type, &ZIGZAG, KnotVector::Clamped);
+        let before = pixels(core.clone());
+
+        let mut shape = new_shape_from_core(core);
+        shape.update(&UpdateOp::ControlPoint(1, Point(40, 10)));
+        let after = pixels(shape.get_core());
+        assert_ne!(before, after);
+
+        // a cubic piece uses 4 control points, so the point 1 moves at most the pieces until the
+        // point 4 (x = 130). Catmull-Rom pieces use the point before and after them
+        let far = |pixels: &HashSet<(i32, i32)>| -> HashSet<(i32, i32)> {
+            pixels.iter().copied().filter(|(x, _)| *x > 135).collect()
+        };
+        assert_eq!(far(&before), far(&after), "{shape_type} changed far away");
+    }
+}
+
+#[test]
+fn catmull_rom_splines_go_through_every_point() {
+    let drawn = pixels(spline(Shape::CatmullRom, &ZIGZAG, KnotVector::Clamped));
+    for p in ZIGZAG {
+        assert!(drawn.contains(&p), "{p:?} is not on the curve");
+    }
+}
+
+#[test]
+fn low_degrees_and_few_points() {
+    // degree 1 is the control polygon
+    let mut core = spline(Shape::BSpline, &ZIGZAG, KnotVector::Clamped);
+    core.degree = 1;
+    let drawn = pixels(core);
+    assert!(ZIGZAG.iter().all(|p| drawn.contains(p)));
+
+    // 3 points can only make a quadratic curve, that goes from the first to the last point
+    let drawn = pixels(spline(
+        Shape::BSpline,
+        &[(10, 100), (100, 10), (190, 100)],
+        KnotVector::Clamped,
+    ));
+    assert!(drawn.contains(&(10, 100)) && drawn.contains(&(190, 100)));
+    assert!(drawn.contains(&(100, 55)));
+}
+
+#[test]
+fn inserting_a_knot_adds_a_point_without_changing_the_curve() {
+    for (shape_type, knot_vector) in [
+        (Shape::BSpline, KnotVector::Clamped),
+        (Shape::BSpline, KnotVector::Uniform),
+        (Shape::CatmullRom, KnotVector::Clamped),
+    ] {
+        let core = spline(shape_type, &ZIGZAG, knot_vector);
+        let before = pixels(core.clone());
+
+        let mut shape = new_shape_from_core(core);
+        for t in [0.3, 0.3, 0.71] {
+            shape.update(&UpdateOp::UpdateSubdivide(t));
+            shape.update(&UpdateOp::InsertKnot);
+        }
+        let inserted = shape.get_core();
+        assert_eq!(inserted.points.len(), ZIGZAG.len() + 3);
+        assert!(inserted.validate().is_ok());
+
+        // the new points are rounded to pixels, so the curve can move up to a pixel. Catmull-Rom
+        // pieces change a bit more since the new point changes the direction at its neighbours
+        let after = pixels(inserted);
+        if shape_type == Shape::BSpline {
+            let what = format!("{shape_type} with {knot_vector} knots");
+            assert!(is_close(&after, &before), "{what} changed");
+            assert!(is_close(&before, &after), "{what} changed");
+        }
+    }
+
+    // the knots are kept after saving and loading the curve
+    let mut shape = new_shape_from_core(spline(Shape::BSpline, &ZIGZAG, KnotVector::Clamped));
+    shape.update(&UpdateOp::InsertKnot);
+    let json = serde_json::to_string(&shape.get_core()).unwrap();
+    let loaded: ShapeCore = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.knots.len(), ZIGZAG.len() + 1 + 4);
+    assert_eq!(pixels(loaded), pixels(shape.get_core()));
+
+    // each knot is undone on its own
+    let five = &[(10, 100), (60, 40), (110, 160), (160, 40), (210, 100)];
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(spline(
+        Shape::BSpline,
+        five,
+        KnotVector::Clamped,
+    )));
+    state.update_shape(0, UpdateOp::InsertKnot);
+    state.update_shape(0, UpdateOp::InsertKnot);
+    assert_eq!(state.get_object(0).get_core().points.len(), 7);
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().points.len(), 6);
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().points.len(), 5);
+}
+
+#[test]
+fn invalid_knots_are_rejected() {
+    let mut core = spline(Shape::BSpline, &ZIGZAG, KnotVector::Clamped);
+    core.knots = vec![0.0; 5];
+    assert!(core.validate().is_err());
+    core.knots = (0..12).map(|i| (12 - i) as f32).collect();
+    assert!(core.validate().is_err());
+    core.knots = (0..12).map(|i| i as f32).collect();
+    assert!(core.validate().is_ok());
+}
@@ -0,0 +1,141 @@
+// Text checks. The letters come from the bundled font, so a headless render draws them without
+// any font installed, and a text must survive being moved, saved and loaded.
+
+use std::path::PathBuf;
+
+use paint_app::{
+    core::{Point, Shape, ShapeCore, UpdateOp, MAX_FONT_SIZE, RGBA},
+    draw_state::DrawState,
+    headless::{render, Frame},
+    primitives::new_shape_from_core,
+};
+
+const BLACK: RGBA = RGBA::new(0, 0, 0, 255);
+const TRANSLUCENT: RGBA = RGBA::new(200, 100, 50, 128);
+
+fn text(anchor: (i32, i32), text: &str, font_size: f32) -> ShapeCore {
+    ShapeCore {
+        points: vec![Point::from(anchor)],
+        color: BLACK,
+        shape_type: Shape::Text,
+        text: text.to_string(),
+        font_size,
+        ..Default::default()
+    }
+}
+
+/// the pixels of the frame that are not the background, which is left on the bottom right corner
+fn drawn_pixels(frame: &Frame) -> Vec<(u32, u32)> {
+    let background = frame.get_pixel(frame.width() - 1, frame.height() - 1);
+    let mut pixels = vec![];
+    for y in 0..frame.height() {
+        for x in 0..frame.width() {
+            if frame.get_pixel(x, y) != background {
+                pixels.push((x, y));
+            }
+        }
+    }
+    pixels
+}
+
+#[test]
+fn texts_are_drawn_once_inside_their_box() {
+    for antialias in [false, true] {
+        let core = ShapeCore {
+            color: TRANSLUCENT,
+            antialias,
+            ..text((20, 30), "Hello\nworld!", 24.0)
+        };
+        let mut frame = Frame::new(200, 200);
+        let mut canvas = frame.recording_canvas();
+        new_shape_from_core(core).draw(&mut canvas);
+        let writes = canvas.written_pixels();
+
+        assert!(writes.len() > 100, "the letters are drawn");
+        assert!(writes.iter().all(|(_, _, count)| *count == 1));
+        // two lines of 24 pixels, starting on the anchor
+        assert!(writes
+            .iter()
+            .all(|(x, y, _)| (20..120).contains(x) && (30..90).contains(y)));
+    }
+}
+
+#[test]
+fn moving_a_text_moves_its_pixels() {
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(text((10, 10), "Move", 20.0)));
+    let before = drawn_pixels(&render(&state, 120, 80));
+    assert!(!before.is_empty());
+
+    state.update_shape(0, UpdateOp::Move(Point(15, 30)));
+    let after = drawn_pixels(&render(&state, 120, 80));
+    let moved: Vec<_> = before.iter().map(|(x, y)| (x + 15, y + 30)).collect();
+    assert_eq!(after, moved);
+}
+
+#[test]
+fn texts_are_saved_and_loaded() {
+    let mut state = DrawState::new();
+    let core = ShapeCore {
+        color: RGBA::new(200, 0, 0, 255),
+        ..text((5, 8), "saved\ntext", 31.5)
+    };
+    state.add_shape(new_shape_from_core(core.clone()));
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("text_drawing.json");
+    state.save_to_file(path.clone()).unwrap();
+
+    let mut loaded = DrawState::new();
+    loaded.load_from_file(path).unwrap();
+    let loaded_core = loaded.get_objects()[0].get_core();
+    assert_eq!(loaded_core.text, "saved\ntext");
+    assert_eq!(loaded_core.font_size, 31.5);
+    assert_eq!(loaded_core.points, core.points);
+    assert_eq!(
+        drawn_pixels(&render(&loaded, 120, 100)),
+        drawn_pixels(&render(&state, 120, 100))
+    );
+}
+
+#[test]
+fn font_sizes_are_checked() {
+    assert!(text((0, 0), "a", MAX_FONT_SIZE).validate().is_ok());
+    assert!(text((0, 0), "a", 1e6).validate().is_err());
+    assert!(text((0, 0), "a", 0.0).validate().is_err());
+    assert!(text((0, 0), "a", f32::NAN).validate().is_err());
+}
+
+#[test]
+fn texts_are_hit_on_their_box() {
+    let shape = new_shape_from_core(text((50, 50), "Click", 20.0));
+    // between the letters still selects it
+    assert!(shape.hit_test(Point(52, 60)));
+    assert!(shape.hit_test(Point(48, 48)));
+    assert!(!shape.hit_test(Point(40, 60)));
+    assert!(!shape.hit_test(Point(60, 90)));
+    assert!(!shape.hit_test(Point(150, 60)));
+}
+
+#[test]
+fn dropped_empty_texts_are_not_undone_back() {
+    // placed and left empty: undo goes back to before it was placed
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(text((10, 10), "kept", 20.0)));
+    state.add_shape(new_shape_from_core(text((50, 50), "", 20.0)));
+    state.drop_shape(1);
+    assert_eq!(state.get_objects().len(), 1);
+    state.undo();
+    assert!(state.get_objects().is_empty());
+
+    // emptied later: undo gives back what it said, in one step
+    let mut state = DrawState::new();
+    state.add_shape(new_shape_from_core(text((10, 10), "hello", 20.0)));
+    state.add_shape(new_shape_from_core(text((50, 50), "other", 20.0)));
+    state.update_shape(0, UpdateOp::ChangeText(String::new()));
+    state.drop_shape(0);
+    assert_eq!(state.get_objects().len(), 1);
+    state.undo();
+    assert_eq!(state.get_object(0).get_core().text, "hello");
+    state.redo();
+    assert_eq!(state.get_objects().len(), 1);
+    assert_eq!(state.get_object(0).get_core().text, "other");
+}